@@ -0,0 +1,110 @@
+//! Relays actions between planner editors sharing a business, see `src/sync.rs` in the planner. The path of the
+//! address editors connect to picks their room. Rooms are kept while the relay runs, so whoever joins later is caught
+//! up on the plan.
+//!
+//! Usage: `daily-planner-relay [address]`, listening on 127.0.0.1:9031 by default
+
+use std::{collections::HashMap, io, net::{TcpListener, TcpStream}, sync::{atomic::{AtomicU64, Ordering}, mpsc, Arc, Mutex, MutexGuard, PoisonError}, thread, time::Duration};
+
+use room::Room;
+use tungstenite::{handshake::server::{Request, Response}, protocol::WebSocketConfig, Message};
+
+mod room;
+
+#[cfg(test)]
+mod tests;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:9031";
+/// The largest message accepted, so a bad client can't use up the relay's memory
+const MAX_MESSAGE: usize = 16 * 1024 * 1024;
+/// How long to wait for an editor's next message before passing on the room's
+const POLL: Duration = Duration::from_millis(20);
+
+type Rooms = Arc<Mutex<HashMap<String, Room>>>;
+
+/// The rooms, even if another editor's thread panicked while holding them. Rooms are only changed a message at a time,
+/// so they are never left half done.
+fn lock(rooms: &Rooms) -> MutexGuard<'_, HashMap<String, Room>> {
+    rooms.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn main() {
+    let address = std::env::args().nth(1).unwrap_or(DEFAULT_ADDRESS.to_string());
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Couldn't listen on {}; {}", address, e);
+            std::process::exit(1);
+        },
+    };
+    println!("Relaying on ws://{}/", address);
+
+    relay(listener, Rooms::default());
+}
+
+/// Take editors from the listener until it closes, each on their own thread
+fn relay(listener: TcpListener, rooms: Rooms) {
+    let next_client = AtomicU64::new(1);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Failed to accept a connection; {}", e);
+                continue;
+            },
+        };
+        let (rooms, client) = (rooms.clone(), next_client.fetch_add(1, Ordering::Relaxed));
+        thread::spawn(move || {
+            if let Err(e) = serve(stream, client, rooms) {
+                eprintln!("Editor {} disconnected; {}", client, e);
+            }
+        });
+    }
+}
+
+/// Relay for one editor until they disconnect. Pings and closing are answered by the socket itself.
+// The handshake callback's error type is tungstenite's own HTTP response
+#[allow(clippy::result_large_err)]
+fn serve(stream: TcpStream, client: u64, rooms: Rooms) -> io::Result<()> {
+    let mut room = String::new();
+    let config = WebSocketConfig { max_message_size: Some(MAX_MESSAGE), max_frame_size: Some(MAX_MESSAGE), ..Default::default() };
+    let mut socket = tungstenite::accept_hdr_with_config(stream, |request: &Request, response: Response| {
+        room = request.uri().path().to_string();
+        Ok(response)
+    }, Some(config)).map_err(|e| match e {
+        tungstenite::HandshakeError::Failure(e) => io::Error::other(e),
+        // Only for sockets which don't block, which this one does
+        tungstenite::HandshakeError::Interrupted(_) => io::ErrorKind::WouldBlock.into(),
+    })?;
+    println!("Editor {} joined {}", client, room);
+
+    // The room's messages wait here until the editor's socket is free, so a slow editor doesn't hold up the room
+    let (sender, receiver) = mpsc::channel::<String>();
+    lock(&rooms).entry(room.clone()).or_default().join(client, sender);
+    socket.get_ref().set_read_timeout(Some(POLL))?;
+
+    let result = 'relay: loop {
+        for message in receiver.try_iter() {
+            if let Err(e) = socket.send(Message::text(message)) {
+                break 'relay Err(e);
+            }
+        }
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                if let Some(room) = lock(&rooms).get_mut(&room) {
+                    room.receive(client, &text);
+                }
+            },
+            Ok(_) => {},
+            Err(tungstenite::Error::Io(e)) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {},
+            Err(tungstenite::Error::ConnectionClosed) => break Ok(()),
+            Err(e) => break Err(e),
+        }
+    };
+
+    if let Some(room) = lock(&rooms).get_mut(&room) {
+        room.leave(client);
+    }
+    println!("Editor {} left {}", client, room);
+    result.map_err(io::Error::other)
+}
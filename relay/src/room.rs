@@ -0,0 +1,79 @@
+//! Editors planning the same business, and every action they have shared, in the order everyone applies them. See
+//! `src/sync.rs` in the planner for the messages.
+
+use std::sync::mpsc::Sender;
+
+/// How long the log grows before the editor adding to it is asked for a snapshot to start it over. Asked again each
+/// time it grows this much more, in case they didn't answer.
+pub const LOG_LIMIT: usize = 500;
+
+#[derive(Default)]
+pub struct Room {
+    /// The last sequence number given out. It keeps counting after a snapshot, so editors can tell new from old.
+    seq: u64,
+    /// Every action since the last snapshot, as sent out, snapshot first
+    log: Vec<String>,
+    /// Where to send each connected editor's messages
+    clients: Vec<(u64, Sender<String>)>,
+    /// Each connected editor's latest presence
+    presence: Vec<(u64, String)>,
+} impl Room {
+    /// Welcome the editor, then catch them up on the log and who else is here
+    pub fn join(&mut self, client: u64, sender: Sender<String>) {
+        let _ = sender.send(format!("W {} {}", client, self.log.len()));
+        for entry in self.log.iter() {
+            let _ = sender.send(entry.clone());
+        }
+        for (other, presence) in self.presence.iter() {
+            let _ = sender.send(format!("P {} {}", other, presence));
+        }
+        self.clients.push((client, sender));
+    }
+
+    /// Handle a message from the editor. Anything unrecognised is ignored.
+    pub fn receive(&mut self, client: u64, message: &str) {
+        let (kind, payload) = message.split_once(' ').unwrap_or((message, ""));
+        match kind {
+            "M" | "S" => {
+                self.seq += 1;
+                let entry = format!("M {} {} {}", self.seq, client, payload);
+                // A snapshot replaces everything before it
+                if kind == "S" {
+                    self.log.clear();
+                }
+                self.log.push(entry.clone());
+                self.broadcast(&entry, None);
+                if self.log.len().is_multiple_of(LOG_LIMIT) {
+                    self.send(client, "R");
+                }
+            },
+            "P" => {
+                self.presence.retain(|(other, _)| *other != client);
+                self.presence.push((client, payload.to_string()));
+                self.broadcast(&format!("P {} {}", client, payload), Some(client));
+            },
+            _ => {},
+        }
+    }
+
+    pub fn leave(&mut self, client: u64) {
+        self.clients.retain(|(other, _)| *other != client);
+        self.presence.retain(|(other, _)| *other != client);
+        self.broadcast(&format!("L {}", client), None);
+    }
+
+    fn send(&self, client: u64, message: &str) {
+        if let Some((_, sender)) = self.clients.iter().find(|(other, _)| *other == client) {
+            let _ = sender.send(message.to_string());
+        }
+    }
+
+    /// Send to every editor, but the one excepted
+    fn broadcast(&self, message: &str, except: Option<u64>) {
+        for (client, sender) in self.clients.iter() {
+            if Some(*client) != except {
+                let _ = sender.send(message.to_string());
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::{net::TcpListener, sync::mpsc::{self, Receiver}, thread};
+
+use tungstenite::Message;
+
+use crate::{relay, room::{Room, LOG_LIMIT}, Rooms};
+
+fn drain(receiver: &Receiver<String>) -> Vec<String> {
+    receiver.try_iter().collect()
+}
+
+#[test]
+fn room_orders_and_replays() {
+    let mut room = Room::default();
+    let (first, first_rx) = mpsc::channel();
+    room.join(1, first);
+    assert_eq!(drain(&first_rx), vec!["W 1 0"]);
+
+    room.receive(1, "S (plan)");
+    room.receive(1, "P (name)");
+    room.receive(1, "M (action)");
+    assert_eq!(drain(&first_rx), vec!["M 1 1 (plan)", "M 2 1 (action)"]);
+
+    // Joining later catches up on the log and who else is here
+    let (second, second_rx) = mpsc::channel();
+    room.join(2, second);
+    assert_eq!(drain(&second_rx), vec!["W 2 2", "M 1 1 (plan)", "M 2 1 (action)", "P 1 (name)"]);
+
+    // A snapshot starts the log over, but numbering carries on
+    room.receive(2, "S (new plan)");
+    assert_eq!(drain(&first_rx), vec!["M 3 2 (new plan)"]);
+    room.leave(2);
+    assert_eq!(drain(&first_rx), vec!["L 2"]);
+
+    let (third, third_rx) = mpsc::channel();
+    room.join(3, third);
+    assert_eq!(drain(&third_rx), vec!["W 3 1", "M 3 2 (new plan)", "P 1 (name)"]);
+}
+
+#[test]
+fn long_logs_ask_for_a_snapshot() {
+    let mut room = Room::default();
+    let (first, first_rx) = mpsc::channel();
+    let (second, second_rx) = mpsc::channel();
+    room.join(1, first);
+    room.join(2, second);
+    room.receive(1, "S (plan)");
+    for _ in 1..LOG_LIMIT - 1 {
+        room.receive(1, "M (action)");
+    }
+    assert!(!drain(&second_rx).contains(&"R".to_string()));
+    // Only whoever fills the log is asked
+    room.receive(2, "M (action)");
+    assert_eq!(drain(&second_rx).last().map(String::as_str), Some("R"));
+    assert!(!drain(&first_rx).contains(&"R".to_string()));
+
+    room.receive(2, "S (new plan)");
+    let (third, third_rx) = mpsc::channel();
+    room.join(3, third);
+    assert_eq!(drain(&third_rx)[0], "W 3 1");
+}
+
+#[test]
+fn editors_are_relayed_over_websockets() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || relay(listener, Rooms::default()));
+    let url = format!("ws://{}/store-12", address);
+    let read = |socket: &mut tungstenite::WebSocket<_>| loop {
+        match socket.read().unwrap() {
+            Message::Text(text) => break text,
+            _ => continue,
+        }
+    };
+
+    let (mut first, _) = tungstenite::connect(&url).unwrap();
+    assert_eq!(read(&mut first), "W 1 0");
+    first.send(Message::text("S (plan)")).unwrap();
+    assert_eq!(read(&mut first), "M 1 1 (plan)");
+
+    let (mut second, _) = tungstenite::connect(&url).unwrap();
+    assert_eq!(read(&mut second), "W 2 1");
+    assert_eq!(read(&mut second), "M 1 1 (plan)");
+    second.send(Message::text("M (action)")).unwrap();
+    assert_eq!(read(&mut first), "M 2 2 (action)");
+    assert_eq!(read(&mut second), "M 2 2 (action)");
+
+    second.close(None).unwrap();
+    assert_eq!(read(&mut first), "L 2");
+}
@@ -0,0 +1,153 @@
+use chrono::TimeDelta;
+use serde::{Deserialize, Serialize};
+
+use crate::{data::{Business, BusinessError, Employee, Grid, Result, RoleTrait}, notifications::Level};
+
+/// Blocks of a role left without anyone when an employee was marked absent
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Vacancy {
+    pub role: usize,
+    /// In order, though not always in one run once some have been covered
+    pub blocks: Vec<usize>,
+}
+
+/// Who was marked absent and what still needs covering, see [`Business::mark_absent`]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Absence {
+    pub employee: usize,
+    pub vacancies: Vec<Vacancy>,
+} impl Absence {
+    pub fn is_covered(&self) -> bool {
+        self.vacancies.is_empty()
+    }
+}
+
+/// Someone who could take over some of an absent employee's blocks
+#[derive(Clone, PartialEq, Debug)]
+pub struct Replacement {
+    pub employee: usize,
+    /// Vacant blocks they are free for and can work
+    pub blocks: usize,
+    /// How many of the vacant roles they can work
+    pub roles: usize,
+    /// Paid time already on their day
+    pub hours: TimeDelta,
+}
+
+/// Consecutive blocks as (first, last) pairs
+pub fn block_runs(blocks: &[usize]) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = vec![];
+    for block in blocks {
+        match runs.last_mut() {
+            Some((_, last)) if *last + 1 == *block => *last = *block,
+            _ => runs.push((*block, *block)),
+        }
+    }
+    runs
+}
+
+impl Business {
+    /// Take the employee off the schedule for the day, freeing everything they were assigned, locked or not. Returns
+    /// the roles they leave uncovered, to find replacements for.
+    pub fn mark_absent(&mut self, employee: usize) -> Result<Absence> {
+        let grid = self.grid();
+        let emp = match self.employees.get_mut(&employee) {
+            Some(emp) => emp,
+            None => return Err(BusinessError::EmployeeNotFound),
+        };
+        let mut vacancies: Vec<Vacancy> = vec![];
+        for (block, role) in emp.assigned(&grid).into_iter().enumerate() {
+            // Lunch doesn't need covering
+            if role <= 2 {
+                continue;
+            }
+            match vacancies.last_mut() {
+                Some(vacancy) if vacancy.role == role && vacancy.blocks.last().is_some_and(|last| last + 1 == block) => vacancy.blocks.push(block),
+                _ => vacancies.push(Vacancy { role, blocks: vec![block] }),
+            }
+        }
+        emp.clear_assigned();
+        emp.scheduled = false;
+        if vacancies.is_empty() {
+            let message = format!("{} is marked absent; they had no roles to cover", emp.name);
+            self.notices.push(Level::Info, message);
+        }
+        Ok(Absence { employee, vacancies })
+    }
+
+    /// Whether the employee could take the block of the role without moving anyone
+    fn can_take(&self, grid: &Grid, emp: &Employee, role: usize, block: usize) -> bool {
+        if !emp.scheduled || !emp.roles.contains(&role) || emp.block_role(grid, block) != 1 {
+            return false;
+        }
+        match self.roles.get(&role).and_then(|role| role.capacity()) {
+            Some(capacity) => self.employees.values().filter(|other| other.block_role(grid, block) == role).count() < capacity,
+            None => true,
+        }
+    }
+
+    /// Everyone who could cover some of the absence, best first: whoever can take the most blocks, then whoever can work
+    /// the most of the roles, then whoever has the fewest hours so far
+    pub fn replacements(&self, absence: &Absence) -> Vec<Replacement> {
+        let grid = self.grid();
+        let totals = self.totals();
+        let mut roles: Vec<usize> = absence.vacancies.iter().map(|vacancy| vacancy.role).collect();
+        roles.sort();
+        roles.dedup();
+        let mut replacements: Vec<Replacement> = self.employees.values()
+            .filter(|emp| emp.scheduled && emp.id != absence.employee)
+            .map(|emp| Replacement {
+                employee: emp.id,
+                blocks: absence.vacancies.iter()
+                    .map(|vacancy| vacancy.blocks.iter().filter(|block| self.can_take(&grid, emp, vacancy.role, **block)).count())
+                    .sum(),
+                roles: roles.iter().filter(|role| emp.roles.contains(role)).count(),
+                hours: totals.employees.iter().find(|totals| totals.employee == emp.id).map(|totals| totals.paid()).unwrap_or_default(),
+            })
+            .filter(|replacement| replacement.blocks > 0)
+            .collect();
+        replacements.sort_by(|a, b| b.blocks.cmp(&a.blocks)
+            .then(b.roles.cmp(&a.roles))
+            .then(a.hours.cmp(&b.hours))
+            .then(a.employee.cmp(&b.employee)));
+        replacements
+    }
+
+    /// Give the employee every vacant block of the absence they can take. Returns how many they took; anything left
+    /// stays vacant for someone else.
+    pub fn apply_replacement(&mut self, employee: usize, absence: &mut Absence) -> Result<usize> {
+        let grid = self.grid();
+        let emp = match self.employees.get(&employee) {
+            Some(emp) => emp.clone(),
+            None => return Err(BusinessError::EmployeeNotFound),
+        };
+        let mut taken = 0;
+        let mut result = Ok(());
+        for vacancy in absence.vacancies.iter_mut() {
+            let (take, keep): (Vec<usize>, Vec<usize>) = vacancy.blocks.iter().partition(|block| self.can_take(&grid, &emp, vacancy.role, **block));
+            if take.is_empty() {
+                continue;
+            }
+            match self.assign_block(employee, vacancy.role, take.clone()) {
+                Ok(_) => {
+                    taken += take.len();
+                    vacancy.blocks = keep;
+                },
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                },
+            }
+        }
+        absence.vacancies.retain(|vacancy| !vacancy.blocks.is_empty());
+        result.map(|_| taken)
+    }
+    /// [`Business::apply_replacement`], telling the user how much the employee covers
+    pub fn cover_absence(&mut self, employee: usize, absence: &mut Absence) {
+        match self.apply_replacement(employee, absence) {
+            Ok(0) => self.notices.push(Level::Warning, format!("{} can't cover any of it", self.employee_name(employee))),
+            Ok(taken) => self.notices.push(Level::Info, format!("{} covers {} block(s)", self.employee_name(employee), taken)),
+            Err(e) => self.report(e),
+        }
+    }
+}
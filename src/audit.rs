@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{absence::block_runs, data::{Business, Employee}};
+
+/// The most entries kept, dropping the oldest, so the log fits in local storage
+pub const AUDIT_LIMIT: usize = 1000;
+
+/// One action taken on the business, see [`audit_entry`]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub time: NaiveDateTime,
+    /// As set in settings, if anyone has
+    pub editor: Option<String>,
+    /// The scenario being edited, if not the plan
+    pub scenario: Option<String>,
+    pub action: String,
+    /// Everyone whose details or schedule the action changed
+    pub employees: Vec<usize>,
+    pub blocks: Vec<AffectedBlocks>,
+}
+
+/// Stretches of one employee's schedule an action changed
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct AffectedBlocks {
+    pub employee: usize,
+    /// The employee's name when the action was taken, in case they are later deleted
+    pub name: String,
+    /// Start and end of each stretch
+    pub spans: Vec<(NaiveTime, NaiveTime)>,
+} impl AffectedBlocks {
+    /// e.g. "Employee 1 9:00-10:30, 2:00-3:00"
+    pub fn describe(&self) -> String {
+        let spans: Vec<String> = self.spans.iter()
+            .map(|(start, end)| format!("{}-{}", start.format("%-I:%M"), end.format("%-I:%M")))
+            .collect();
+        format!("{} {}", self.name, spans.join(", "))
+    }
+}
+
+/// Record what an action changed, comparing the employees before it with the business after it. `before` is None when
+/// the action switched to another business, such as between the plan and a scenario, which changes nothing in either,
+/// so no employees or blocks are listed for it. `scenario` is the one edited, if not the plan.
+pub fn audit_entry(before: Option<&HashMap<usize, Employee>>, after: &Business, scenario: Option<String>, action: String, editor: Option<String>, time: NaiveDateTime) -> AuditEntry {
+    let mut entry = AuditEntry { time, editor, scenario, action, employees: vec![], blocks: vec![] };
+    let before = match before {
+        Some(before) => before,
+        None => return entry,
+    };
+
+    let grid = after.grid();
+    let mut ids: Vec<usize> = before.keys().chain(after.employees.keys()).copied().collect();
+    ids.sort();
+    ids.dedup();
+    for id in ids {
+        let (old, new) = (before.get(&id), after.employees.get(&id));
+        if old == new {
+            continue;
+        }
+        entry.employees.push(id);
+        let view = |emp: Option<&Employee>| match emp {
+            Some(emp) if emp.scheduled => emp.assigned(&grid),
+            _ => vec![0; grid.blocks],
+        };
+        let (old_view, new_view) = (view(old), view(new));
+        let changed: Vec<usize> = (0..grid.blocks).filter(|block| old_view[*block] != new_view[*block]).collect();
+        if changed.is_empty() {
+            continue;
+        }
+        let name = match new.or(old) {
+            Some(emp) => emp.name.to_string(),
+            None => continue,
+        };
+        let spans = block_runs(&changed).into_iter()
+            .map(|(first, last)| (grid.block_time(first), grid.block_time(last + 1)))
+            .collect();
+        entry.blocks.push(AffectedBlocks { employee: id, name, spans });
+    }
+    entry
+}
+
+/// Entries involving the employee, or all of them for None, newest first
+pub fn filter_entries(entries: &[AuditEntry], employee: Option<usize>) -> Vec<&AuditEntry> {
+    entries.iter()
+        .rev()
+        .filter(|entry| employee.is_none_or(|employee| entry.employees.contains(&employee)))
+        .collect()
+}
+
+/// A field for CSV, quoted when it needs to be
+pub fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+/// The entries as CSV, with a header row
+pub fn audit_to_csv(entries: &[&AuditEntry]) -> String {
+    let mut result = String::from("time,editor,scenario,action,affected blocks\n");
+    for entry in entries {
+        let blocks: Vec<String> = entry.blocks.iter().map(|blocks| blocks.describe()).collect();
+        let fields = [
+            entry.time.format("%Y-%m-%d %H:%M:%S").to_string(),
+            entry.editor.clone().unwrap_or_default(),
+            entry.scenario.clone().unwrap_or_default(),
+            entry.action.clone(),
+            blocks.join("; "),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        result += &(fields.join(",") + "\n");
+    }
+    result
+}
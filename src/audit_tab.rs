@@ -0,0 +1,72 @@
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+use crate::{audit::{audit_to_csv, filter_entries}, persistence::{percent_encode, read_audit, write_audit}, BusinessContext};
+
+/// Every change made on this computer, newest first, optionally narrowed to one employee
+#[function_component]
+pub fn AuditTab() -> Html {
+    let business = use_context::<BusinessContext>().expect("No ctx found");
+    let employee = use_state_eq(|| None::<usize>);
+    // Bumped to redraw after clearing, since the log isn't part of any context
+    let cleared = use_state(|| 0);
+    let entries = read_audit();
+    let shown = filter_entries(&entries, *employee);
+
+    let mut employees: Vec<_> = business.employees.values().collect();
+    employees.sort_by(|a, b| a.name.cmp(&b.name));
+    let options: Vec<Html> = employees.iter().map(|emp| html!(
+        <option value={emp.id.to_string()} selected={*employee == Some(emp.id)}>{emp.name.clone()}</option>
+    )).collect();
+    let filter_cb = {
+        let employee = employee.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                employee.set(select.value().parse().ok());
+            }
+        })
+    };
+    let clear_cb = {
+        let cleared = cleared.clone();
+        Callback::from(move |_| {
+            write_audit(&[]);
+            cleared.set(*cleared + 1);
+        })
+    };
+
+    let oldest_first: Vec<_> = shown.iter().rev().copied().collect();
+    let export = format!("data:text/csv;charset=utf-8,{}", percent_encode(&audit_to_csv(&oldest_first)));
+    let rows: Vec<Html> = shown.iter().map(|entry| {
+        let blocks: Vec<Html> = entry.blocks.iter().map(|blocks| html!(<div>{blocks.describe()}</div>)).collect();
+        html!(<tr>
+            <td>{entry.time.format("%b %-d %-I:%M:%S %p").to_string()}</td>
+            <td>{entry.editor.clone().unwrap_or_default()}</td>
+            <td>{entry.action.clone()}{entry.scenario.as_ref().map(|name| format!(" (in scenario \"{}\")", name))}</td>
+            <td>{blocks}</td>
+        </tr>)
+    }).collect();
+
+    html!(<div class="audit">
+        <p>
+            <label for="audit_select">{"Changes to: "}</label>
+            <select name="audit" id="audit_select" onchange={filter_cb}>
+                <option value="" selected={employee.is_none()}>{"Everyone"}</option>
+                {options}
+            </select>
+            <a class="mui-btn" href={export} download="change-log.csv">{"Export CSV"}</a>
+            <input type="button" value="Clear Log" onclick={clear_cb} />
+        </p>
+        <p>{format!("Showing {} of {} change(s) made on this computer. Set your name in settings to have it recorded with your changes.", shown.len(), entries.len())}</p>
+        <table class="mui-table mui-table--bordered">
+            <thead>
+                <tr>
+                    <th>{"When"}</th>
+                    <th>{"Who"}</th>
+                    <th>{"What"}</th>
+                    <th>{"Blocks Changed"}</th>
+                </tr>
+            </thead>
+            <tbody>{rows}</tbody>
+        </table>
+    </div>)
+}
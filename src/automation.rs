@@ -1,73 +1,309 @@
-use crate::{data::{Business, Role, RoleTrait}, SettingsContext};
+use chrono::NaiveTime;
+
+use crate::{data::{Business, Employee, Grid, Role, RoleTrait}, notifications::Level, settings::AppSettings};
+
+/// Why [`Business::schedule_roles`] passed over an employee for a block
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Rejection {
+    NotScheduled,
+    NotAllowed,
+    NotClockedIn,
+    /// Already working the given role, or at lunch
+    Busy { role: usize },
+    /// Could have worked it, but someone with less time on the role was chosen
+    Outranked,
+    /// Could have worked it, but it would have meant switching roles more often than the settings allow
+    TooManySwitches,
+}
+
+/// How one block of a role came to be filled, or not
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    /// Already worked by the employee before the automation ran
+    AlreadyCovered { employee: usize },
+    /// Part of the run given to the employee at an earlier block
+    Continued { employee: usize, from: usize },
+    Chosen { employee: usize },
+    /// Nobody could work it
+    Empty,
+}
+
+/// What [`Business::schedule_roles`] decided for one role at one block
+#[derive(Clone, PartialEq, Debug)]
+pub struct Decision {
+    pub role: usize,
+    pub block: usize,
+    pub outcome: Outcome,
+    /// Everyone considered, in the order they were looked at, and why each was passed over. The chosen employee has
+    /// no rejection. Blocks which were already filled have no candidates.
+    pub candidates: Vec<(usize, Option<Rejection>)>,
+}
+
+/// Why the employee couldn't work the role at the block, if they could
+fn role_rejection(emp: &Employee, grid: &Grid, role: usize, block: usize) -> Option<Rejection> {
+    if !emp.roles.contains(&role) {
+        return Some(Rejection::NotAllowed);
+    }
+    match emp.block_role(grid, block) {
+        0 => Some(Rejection::NotClockedIn),
+        1 => None,
+        role => Some(Rejection::Busy { role }),
+    }
+}
+
+/// Where one employee's lunch could go. The best has the least of each, compared in order.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct LunchScore {
+    outside_window: bool,
+    over_cap: bool,
+    /// Blocks of a role nobody else is free to cover
+    uncovered: usize,
+    /// Other lunches at the same time, summed over the blocks
+    crowded: usize,
+    /// Blocks of a role someone else has to take over
+    reassigned: usize,
+    /// Minutes from the middle of the lunch to the middle of the shift, twice over
+    distance: i64,
+    start: usize,
+}
 
 impl Business {
 
-    pub fn schedule_lunch(&mut self) {
-        // Reset the schedule
-        self.update_business_hours(self.open, self.close, self.block_size);
+    /// Give every scheduled employee their lunch, as near the middle of their longest shift as the lunch window
+    /// allows. Lunches are staggered, with no more than the set number of people out at once where possible. Anyone
+    /// leaving a role for lunch is covered by someone available who can work it, and times where nobody could cover
+    /// are avoided. Existing roles are cleared first unless the settings keep them; locked assignments always stay, and
+    /// anyone with a locked lunch keeps it.
+    pub fn schedule_lunch(&mut self, settings: &AppSettings) {
+        if !self.roles.contains_key(&2) {
+            log::warn!("Business has no lunch role; skipping lunch scheduling");
+            self.notices.push(Level::Warning, "There's no lunch role to schedule");
+            return;
+        }
+        // Locked assignments stay, including lunches
+        for emp in self.employees.values_mut().filter(|emp| emp.scheduled) {
+            let keep_roles = settings.lunch_keep_roles;
+            emp.assignments.retain(|assignment| assignment.locked || (keep_roles && assignment.role != 2));
+        }
+        let grid = self.grid();
+        let mut on_lunch = vec![0; grid.blocks];
+        let has_lunch = |emp: &Employee| emp.assignments.iter().any(|assignment| assignment.role == 2);
+        for emp in self.employees.values().filter(|emp| has_lunch(emp)) {
+            for (block, role) in emp.assigned(&grid).into_iter().enumerate() {
+                if role == 2 {
+                    on_lunch[block] += 1;
+                }
+            }
+        }
 
-        let lunch = self.roles.get_mut(&2).unwrap();
+        // Whoever would ideally go first picks first
+        let mut order: Vec<(NaiveTime, usize)> = self.employees.values()
+            .filter(|emp| emp.scheduled && emp.lunch > 0 && emp.roles.contains(&2) && !has_lunch(emp))
+            .filter_map(|emp| {
+                let shift = emp.shifts.iter().max_by_key(|shift| shift.clock_out - shift.clock_in)?;
+                Some((shift.clock_in + (shift.clock_out - shift.clock_in) / 2, emp.id))
+            })
+            .collect();
+        order.sort();
 
-        for (_, emp) in self.employees.iter_mut() {
-            if !emp.scheduled {
+        let (mut missed, mut outside, mut crowded, mut uncovered) = (vec![], vec![], vec![], vec![]);
+        for (ideal, emp_id) in order {
+            let name = self.employee_name(emp_id);
+            let best = (0..grid.blocks)
+                .filter_map(|start| self.score_lunch(&grid, settings, &on_lunch, emp_id, ideal, start))
+                .min();
+            let score = match best {
+                Some(score) => score,
+                None => {
+                    missed.push(name);
+                    continue;
+                },
+            };
+            let blocks: Vec<usize> = (score.start..score.start + self.employees[&emp_id].lunch).collect();
+            // Find cover before they leave, so they aren't mistaken for being free
+            let covers: Vec<(usize, usize, Option<usize>)> = blocks.iter()
+                .map(|block| (*block, self.employees[&emp_id].block_role(&grid, *block)))
+                .filter(|(_, role)| *role > 2)
+                .map(|(block, role)| (block, role, self.lunch_cover(&grid, emp_id, role, block)))
+                .collect();
+            if let Err(e) = self.assign_block(emp_id, 2, blocks.clone()) {
+                log::warn!("Failed to assign lunch for employee {}; {}", emp_id, e);
+                missed.push(name);
                 continue;
             }
-            let mid_time = emp.clock_in + (emp.clock_out - emp.clock_in) / 2;
-            let mut curr_time = self.open.clone();
-            let mut index = 0;
-            while curr_time < mid_time {
-                curr_time += self.block_size;
-                index += 1;
+            blocks.iter().for_each(|block| on_lunch[*block] += 1);
+            for (block, role, cover) in covers {
+                match cover {
+                    Some(cover) => { let _ = self.assign_block(cover, role, vec![block]); },
+                    None => {
+                        if !uncovered.contains(&name) {
+                            uncovered.push(name.clone());
+                        }
+                    },
+                }
+            }
+            if score.outside_window {
+                outside.push(name.clone());
             }
-            let mut lunch_indexes = vec![];
-            let mut i = 0;
-            let (mut over, mut under) = (0, 1);
-            while i < emp.lunch {
-                if i % 2 == 0 {
-                    lunch_indexes.push((index + over).clamp(index, self.blocks));
-                    over += 1;
-                } else {
-                    lunch_indexes.push((index - under).clamp(0, index));
-                    under += 1;
+            if score.over_cap {
+                crowded.push(name);
+            }
+        }
+
+        if !missed.is_empty() {
+            self.notices.push(Level::Warning, format!("Couldn't find time in a shift for lunch for {}", missed.join(", ")));
+        }
+        if !outside.is_empty() {
+            self.notices.push(Level::Info, format!("Lunch falls outside the lunch window for {}", outside.join(", ")));
+        }
+        if !crowded.is_empty() {
+            self.notices.push(Level::Info, format!("More than {} are at lunch at once with {}", settings.lunch_overlap, crowded.join(", ")));
+        }
+        if !uncovered.is_empty() {
+            self.notices.push(Level::Warning, format!("Nobody was free to cover for {} at lunch", uncovered.join(", ")));
+        }
+    }
+    /// How good a lunch starting at the block would be for the employee, or None if it doesn't fit in one shift or
+    /// would move something locked
+    fn score_lunch(&self, grid: &Grid, settings: &AppSettings, on_lunch: &[usize], emp_id: usize, ideal: NaiveTime, start: usize) -> Option<LunchScore> {
+        let emp = &self.employees[&emp_id];
+        let end = start + emp.lunch;
+        if end > grid.blocks {
+            return None;
+        }
+        let (lunch_start, lunch_end) = (grid.block_span(start).0, grid.block_span(end - 1).1);
+        if emp.on_shift(lunch_start, lunch_end) != [(lunch_start, lunch_end)] || emp.locked(lunch_start, lunch_end) {
+            return None;
+        }
+        let mut score = LunchScore {
+            outside_window: lunch_start < settings.lunch_earliest || lunch_end > settings.lunch_latest,
+            over_cap: false,
+            uncovered: 0,
+            crowded: 0,
+            reassigned: 0,
+            distance: ((lunch_start - ideal) + (lunch_end - ideal)).num_minutes().abs(),
+            start,
+        };
+        for (block, at_lunch) in on_lunch.iter().enumerate().take(end).skip(start) {
+            score.over_cap |= *at_lunch >= settings.lunch_overlap;
+            score.crowded += at_lunch;
+            let role = emp.block_role(grid, block);
+            if role > 2 {
+                score.reassigned += 1;
+                if self.lunch_cover(grid, emp_id, role, block).is_none() {
+                    score.uncovered += 1;
                 }
-                i += 1;
             }
-            let _ = emp.assign_block(lunch_indexes.clone(), 2);
-            lunch.add_block(&emp.id, lunch_indexes);
         }
+        Some(score)
+    }
+    /// Someone else who is scheduled, available and can work the role for the block
+    fn lunch_cover(&self, grid: &Grid, emp_id: usize, role: usize, block: usize) -> Option<usize> {
+        let mut ids: Vec<&usize> = self.employees.keys().filter(|id| **id != emp_id).collect();
+        ids.sort();
+        ids.into_iter()
+            .find(|id| {
+                let other = &self.employees[id];
+                other.scheduled && other.roles.contains(&role) && other.block_role(grid, block) == 1
+            })
+            .copied()
     }
 
-    pub fn schedule_roles(&mut self, settings: SettingsContext) {
+    /// Fill the single roles block by block, in sort order, with whoever is available and can work them. Each block goes to whoever has
+    /// spent the least time on the role so far (over the recorded week as well as today, if the settings ask for it),
+    /// then whoever has worked least overall, so unpopular roles are shared out rather than landing on the same people.
+    /// Anyone who would switch roles more often than the settings allow is only used when nobody else can. Only
+    /// available time is used, so anything already assigned, locked or not, stays as it is. Returns what was decided for
+    /// each block, and why.
+    pub fn schedule_roles(&mut self, settings: &AppSettings) -> Vec<Decision> {
+        let grid = self.grid();
+        let mut unscheduled: Vec<usize> = self.employees.values().filter(|emp| !emp.scheduled).map(|emp| emp.id).collect();
+        unscheduled.sort();
         let mut employees = vec![];
         for emp in self.employees.values_mut() {
             if emp.scheduled {
                 employees.push(emp);
             }
         }
-        let mut curr_employee = 0;
-        let mut roles: Vec<&mut Role> = self.roles.values_mut().collect();
-        roles.sort_by(|a,b| a.sort().cmp(&b.sort()));
-        for role in roles {
-            if role.is_multi() {continue;}
-            let mut assigned: Vec<usize> = role.assigned().into();
-            for time_index in 0.. assigned.len() {
-                if assigned[time_index] > 0 {
-                    continue;
+        employees.sort_by_key(|emp| emp.id);
+        let mut trace = vec![];
+        let mut over_switches = vec![];
+        let mut roles: Vec<&Role> = self.roles.values().filter(|role| !role.is_multi()).collect();
+        // Ties on sort go by name then id, so every editor fills roles in the same order
+        roles.sort();
+        let roles: Vec<usize> = roles.into_iter().map(|role| role.id()).collect();
+        let mut outcomes: Vec<Vec<Option<Outcome>>> = vec![vec![None; self.blocks]; roles.len()];
+        for emp in employees.iter() {
+            for (time_index, role_id) in emp.assigned(&grid).iter().enumerate() {
+                if let Some(r) = roles.iter().position(|id| id == role_id) {
+                    outcomes[r][time_index].get_or_insert(Outcome::AlreadyCovered { employee: emp.id });
                 }
-                for _attempt in 0.. employees.len() {
-                    if curr_employee >= employees.len() {
-                        curr_employee = 0;
-                    }
-                    if employees[curr_employee].roles.contains(&role.id()) && employees[curr_employee].assigned[time_index] == 1 {
-                        employees[curr_employee].assign_area(role, time_index, settings.app.shift_length);
-                        curr_employee += 1;
-                        assigned = role.assigned().into();
-                        break;
-                    }
-                    curr_employee += 1;
+            }
+        }
+        // Block by block rather than role by role, so everyone's day is built up together and each choice can see the
+        // role switches it would add
+        for time_index in 0..self.blocks {
+            for (r, role) in roles.iter().enumerate() {
+                if let Some(outcome) = outcomes[r][time_index] {
+                    trace.push(Decision { role: *role, block: time_index, outcome, candidates: vec![] });
+                    continue;
                 }
+                // Everyone free is ranked; the first is chosen and the rest are only checked to explain why they weren't
+                let mut ranked: Vec<(usize, Option<Rejection>, bool, usize)> = employees.iter().enumerate().map(|(i, emp)| {
+                    let rejection = role_rejection(emp, &grid, *role, time_index);
+                    let (too_many, switches) = match rejection {
+                        Some(_) => (false, 0),
+                        None => {
+                            let mut trial = (**emp).clone();
+                            trial.assign_area(&grid, *role, time_index, settings.shift_length);
+                            let switches = trial.role_switches();
+                            (settings.max_role_switches > 0 && switches > settings.max_role_switches, switches - emp.role_switches())
+                        },
+                    };
+                    (i, rejection, too_many, switches)
+                }).collect();
+                ranked.sort_by_key(|(i, rejection, too_many, switches)| {
+                    let emp = &employees[*i];
+                    let worked: i64 = emp.assignments.iter()
+                        .filter(|assignment| assignment.role > 2)
+                        .map(|assignment| (assignment.end - assignment.start).num_minutes())
+                        .sum();
+                    (rejection.is_some(), *too_many, emp.role_minutes(*role, settings.fairness_week), *switches, worked, emp.id)
+                });
+                let chosen = ranked.first().filter(|(_, rejection, _, _)| rejection.is_none()).map(|(i, _, too_many, _)| (*i, *too_many));
+                let mut candidates: Vec<(usize, Option<Rejection>)> = ranked.iter().map(|(i, rejection, too_many, _)| {
+                    let rejection = match (rejection, too_many) {
+                        (Some(rejection), _) => Some(*rejection),
+                        _ if chosen.is_some_and(|(chosen, _)| chosen == *i) => None,
+                        (None, true) => Some(Rejection::TooManySwitches),
+                        (None, false) => Some(Rejection::Outranked),
+                    };
+                    (employees[*i].id, rejection)
+                }).collect();
+                candidates.extend(unscheduled.iter().map(|id| (*id, Some(Rejection::NotScheduled))));
+                let outcome = match chosen {
+                    Some((i, too_many)) => {
+                        let employee = employees[i].id;
+                        if too_many && !over_switches.contains(&employee) {
+                            over_switches.push(employee);
+                        }
+                        for index in employees[i].assign_area(&grid, *role, time_index, settings.shift_length) {
+                            if index != time_index {
+                                outcomes[r][index] = Some(Outcome::Continued { employee, from: time_index });
+                            }
+                        }
+                        Outcome::Chosen { employee }
+                    },
+                    None => Outcome::Empty,
+                };
+                trace.push(Decision { role: *role, block: time_index, outcome, candidates });
             }
         }
+        for employee in over_switches {
+            self.notices.push(Level::Info, format!("{} switches roles more than {} times; nobody else was free to cover",
+                self.employees[&employee].name, settings.max_role_switches));
+        }
+        trace
     }
 
 }
\ No newline at end of file
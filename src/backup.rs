@@ -0,0 +1,183 @@
+//! Backup files holding everything the planner keeps for a business: roles, employees, settings, the schedule and any
+//! scenarios. A file starts with a header line naming its version, then the backup as RON:
+//!
+//! ```text
+//! daily-planner backup 1
+//! (business: (roles: { ... }, ...), settings: (...), schedule: "...", scenarios: [...])
+//! ```
+//!
+//! Files without the header are taken as version 0, the bare business kept in the page hash.
+
+use chrono::{NaiveTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+use yew::AttrValue;
+
+use std::rc::Rc;
+
+use crate::{data::{Business, RoleTrait}, notifications::Notices, persistence::{csv_to_schedule, schedule_to_csv}, scenarios::Scenario, settings::{valid_block_size, Settings}, workspace::Workspace};
+
+const BACKUP_HEADER: &str = "daily-planner backup";
+/// The version backups are written as. Anything older is migrated when read, see [`read_backup`].
+pub const BACKUP_VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq)]
+pub enum BackupError {
+    /// The header line names a version which isn't a number
+    BadHeader { header: String },
+    /// Written by a later version of the planner, which this one can't know how to read
+    NewerVersion { version: u32 },
+    Unreadable(ron::error::SpannedError),
+    /// Read, but not something the planner could use
+    Invalid(String),
+} impl std::fmt::Display for BackupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackupError::BadHeader { header } => write!(f, "the first line \"{}\" doesn't give a version", header),
+            BackupError::NewerVersion { version } => write!(f, "it was made by a newer planner (version {}, this reads up to {})", version, BACKUP_VERSION),
+            BackupError::Unreadable(e) => write!(f, "it isn't a backup, or is damaged ({})", e),
+            BackupError::Invalid(problem) => write!(f, "{}", problem),
+        }
+    }
+} impl From<ron::error::SpannedError> for BackupError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        Self::Unreadable(value)
+    }
+}
+
+/// A scenario as saved, with its own hours since they can differ from the plan's
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SavedScenario {
+    pub name: AttrValue,
+    pub business: Business,
+    pub open: NaiveTime,
+    pub close: NaiveTime,
+    pub block_size: TimeDelta,
+    /// See [`schedule_to_csv`]
+    pub schedule: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Backup {
+    /// The plan. Its hours are the settings'.
+    pub business: Business,
+    pub settings: Settings,
+    /// The plan's schedule, see [`schedule_to_csv`]. Empty to leave everyone working their default hours.
+    #[serde(default)]
+    pub schedule: String,
+    #[serde(default)]
+    pub scenarios: Vec<SavedScenario>,
+} impl Backup {
+    /// Everything the editor has. The plan is saved even while a scenario is being edited.
+    pub fn new(workspace: &Workspace, settings: &Settings) -> Backup {
+        let plan = workspace.plan();
+        let mut settings = settings.clone();
+        settings.app.business_set(plan.open, plan.close, plan.block_size);
+        let scenarios = (0..workspace.scenarios.len())
+            .filter_map(|index| {
+                let scenario = workspace.scenario(index)?;
+                Some(SavedScenario {
+                    name: workspace.scenarios[index].name.clone(),
+                    business: scenario.document(),
+                    open: scenario.open,
+                    close: scenario.close,
+                    block_size: scenario.block_size,
+                    schedule: schedule_to_csv(scenario),
+                })
+            })
+            .collect();
+        Backup { business: plan.document(), settings, schedule: schedule_to_csv(plan), scenarios }
+    }
+
+    /// The backup as a file, see the [module docs](self)
+    pub fn to_file(&self) -> Result<String, ron::Error> {
+        let body = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        Ok(format!("{} {}\n{}\n", BACKUP_HEADER, BACKUP_VERSION, body))
+    }
+
+    /// Anything which would keep the backup from being restored
+    fn validate(&self) -> Result<(), BackupError> {
+        let app = &self.settings.app;
+        check_business(&self.business, "the plan", app.open, app.close, app.block_size)?;
+        csv_to_schedule(self.schedule.clone())
+            .map_err(|e| BackupError::Invalid(format!("the plan's schedule can't be read ({})", e)))?;
+        for saved in self.scenarios.iter() {
+            let name = format!("the scenario \"{}\"", saved.name);
+            check_business(&saved.business, &name, saved.open, saved.close, saved.block_size)?;
+            csv_to_schedule(saved.schedule.clone())
+                .map_err(|e| BackupError::Invalid(format!("{}'s schedule can't be read ({})", name, e)))?;
+        }
+        Ok(())
+    }
+}
+
+fn check_business(business: &Business, name: &str, open: NaiveTime, close: NaiveTime, block_size: TimeDelta) -> Result<(), BackupError> {
+    if open >= close || !valid_block_size(block_size.num_minutes()) {
+        return Err(BackupError::Invalid(format!("{} has business hours of {} to {} in {} minute blocks", name, open, close, block_size.num_minutes())));
+    }
+    if !business.roles.contains_key(&2) {
+        return Err(BackupError::Invalid(format!("{} has no lunch role", name)));
+    }
+    if let Some((id, _)) = business.roles.iter().find(|(id, role)| **id != role.id()) {
+        return Err(BackupError::Invalid(format!("{} has role {} saved under another id", name, id)));
+    }
+    if let Some((id, _)) = business.employees.iter().find(|(id, emp)| **id != emp.id) {
+        return Err(BackupError::Invalid(format!("{} has employee {} saved under another id", name, id)));
+    }
+    Ok(())
+}
+
+/// Read a backup file, bringing older versions up to date. The backup is checked, but not yet restored; see
+/// [`Workspace::restore`].
+pub fn read_backup(text: &str) -> Result<Backup, BackupError> {
+    let text = text.trim_start_matches('\u{feff}');
+    let (first, rest) = text.split_once('\n').unwrap_or((text, ""));
+    let (version, body) = match first.trim().strip_prefix(BACKUP_HEADER) {
+        Some(version) => match version.trim().parse() {
+            Ok(version) => (version, rest),
+            Err(_) => return Err(BackupError::BadHeader { header: first.trim().to_string() }),
+        },
+        None => (0, text),
+    };
+    let backup = match version {
+        // The business as kept in the page hash, whose settings were kept apart from it
+        0 => Backup { business: ron::from_str(body)?, settings: Settings::default(), schedule: String::new(), scenarios: vec![] },
+        BACKUP_VERSION => ron::from_str(body)?,
+        version => return Err(BackupError::NewerVersion { version }),
+    };
+    backup.validate()?;
+    Ok(backup)
+}
+
+/// A saved business ready to edit, with anything worth telling the user added to `notices`
+fn restored(mut business: Business, open: NaiveTime, close: NaiveTime, block_size: TimeDelta, schedule: String, notices: &mut Notices) -> Business {
+    business.init(open, close, block_size);
+    if !schedule.is_empty() {
+        for (level, problem) in business.load_schedule(schedule) {
+            notices.push(level, problem);
+        }
+    }
+    business.repair_and_report(true);
+    for (level, message) in business.notices.items.drain(..) {
+        notices.push(level, message);
+    }
+    business
+}
+
+impl Workspace {
+    /// Replace the plan and scenarios with the backup's, going back to editing the plan. The backup's settings are
+    /// left to the caller.
+    pub fn restore(&mut self, backup: Backup) {
+        // Safe, as the plan is always there
+        let _ = self.open_scenario(None);
+        self.absence = None;
+        let app = &backup.settings.app;
+        let plan = restored(backup.business, app.open, app.close, app.block_size, backup.schedule, &mut self.business.notices);
+        self.replace_document(plan);
+        self.scenarios = backup.scenarios.into_iter()
+            .map(|saved| Scenario {
+                name: saved.name,
+                business: Rc::new(restored(saved.business, saved.open, saved.close, saved.block_size, saved.schedule, &mut self.business.notices)),
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Business, BusinessError, EmployeeError, Result};
+
+/// A rectangle of the schedule grid: every listed employee over every listed block
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct GridSelection {
+    pub employees: Vec<usize>,
+    pub blocks: Vec<usize>,
+}
+
+/// Roles copied from a selection, one row per employee and one entry per block (see [`crate::data::Employee::assigned`])
+pub type BlockPattern = Vec<Vec<usize>>;
+
+impl Business {
+
+    /// Assign a role to every block of the selection. Every employee is attempted; the first failure is returned.
+    pub fn bulk_assign(&mut self, selection: &GridSelection, role: usize) -> Result<()> {
+        let mut result = Ok(());
+        for emp in selection.employees.iter() {
+            if let Err(e) = self.assign_block(*emp, role, selection.blocks.clone()) {
+                result = result.and(Err(e));
+            }
+        }
+        result
+    }
+
+    /// Return every block of the selection to available
+    pub fn bulk_clear(&mut self, selection: &GridSelection) -> Result<()> {
+        let mut result = Ok(());
+        for emp in selection.employees.iter() {
+            if let Err(e) = self.remove_block(*emp, selection.blocks.clone()) {
+                result = result.and(Err(e));
+            }
+        }
+        result
+    }
+
+    /// One row for each selected employee, in order. Employees who no longer exist are copied as clocked out, so the
+    /// rows still line up with the selection.
+    pub fn copy_blocks(&self, selection: &GridSelection) -> BlockPattern {
+        let grid = self.grid();
+        let mut pattern = vec![];
+        for emp in selection.employees.iter() {
+            let assigned = self.employees.get(emp).map(|employee| employee.assigned(&grid)).unwrap_or_default();
+            pattern.push(selection.blocks.iter().map(|i| assigned.get(*i).copied().unwrap_or(0)).collect());
+        }
+        pattern
+    }
+
+    /// Paste a copied pattern with its top-left corner at the first employee and the start block. Rows continue down
+    /// the given employees; blocks which would fall past close are dropped.
+    ///
+    /// Copied roles are assigned, copied available blocks clear the target, and copied clocked-out blocks are skipped.
+    pub fn paste_blocks(&mut self, employees: &[usize], start: usize, pattern: &BlockPattern) -> Result<()> {
+        let mut result = Ok(());
+        for (emp, row) in employees.iter().zip(pattern) {
+            let mut to_assign: HashMap<usize, Vec<usize>> = HashMap::new();
+            let mut to_clear = vec![];
+            for (offset, role) in row.iter().enumerate() {
+                let index = start + offset;
+                if index >= self.blocks {
+                    break;
+                }
+                match role {
+                    0 => continue,
+                    1 => to_clear.push(index),
+                    role => to_assign.entry(*role).or_default().push(index),
+                }
+            }
+            if let Err(e) = self.remove_block(*emp, to_clear) {
+                result = result.and(Err(e));
+            }
+            for (role, blocks) in to_assign {
+                if let Err(e) = self.assign_block(*emp, role, blocks) {
+                    result = result.and(Err(e));
+                }
+            }
+        }
+        result
+    }
+
+    /// Move every assignment within the selection by `offset` blocks, keeping each on the same employee. Assignments
+    /// moved outside of business hours are dropped. Nothing changes if any of it can't be moved.
+    pub fn shift_blocks(&mut self, selection: &GridSelection, offset: isize) -> Result<()> {
+        // Work out where everything goes first, so nothing is cleared unless it can all be placed again
+        let pattern = self.copy_blocks(selection);
+        let mut moves = vec![];
+        for (emp, row) in selection.employees.iter().zip(pattern) {
+            let employee = self.employees.get(emp).ok_or(BusinessError::EmployeeNotFound)?;
+            let mut to_assign: HashMap<usize, Vec<usize>> = HashMap::new();
+            for (block, role) in selection.blocks.iter().zip(row) {
+                if role < 2 {
+                    continue;
+                }
+                match block.checked_add_signed(offset) {
+                    Some(index) if index < self.blocks => to_assign.entry(role).or_default().push(index),
+                    _ => continue,
+                }
+            }
+            for role in to_assign.keys() {
+                if !self.roles.contains_key(role) {
+                    return Err(BusinessError::RoleNotFound);
+                }
+                if !employee.roles.contains(role) {
+                    return Err(BusinessError::EmployeeError(EmployeeError::NotAssignedRole { employee: *emp, failed: *role, allowed: employee.roles.clone() }));
+                }
+            }
+            moves.push((*emp, to_assign));
+        }
+        self.bulk_clear(selection)?;
+        for (emp, to_assign) in moves {
+            for (role, blocks) in to_assign {
+                self.assign_block(emp, role, blocks)?;
+            }
+        }
+        Ok(())
+    }
+
+}
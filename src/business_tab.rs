@@ -1,8 +1,8 @@
 use log::warn;
-use web_sys::HtmlInputElement;
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
 use yew::prelude::*;
 
-use crate::{data::*, events::BusinessEvents, BusinessContext};
+use crate::{data::*, events::BusinessEvents, persistence::{percent_encode, read_text_file}, roster::RosterRow, totals::{format_money, parse_money}, BusinessContext};
 
 #[function_component]
 pub fn BusinessTab() -> Html {
@@ -46,12 +46,21 @@ pub fn BusinessTab() -> Html {
             {"Clock-in"}
         </th>
         <th>
-            {"Clock-out"}
+            <div class="tooltip">
+                {"Clock-out \u{24D8}"}
+                <span class="tooltiptext">{"Use + to add a second shift later in the day, for anyone who leaves and comes back"}</span>
+            </div>
         </th>
         <th>
             <div class="tooltip">
                 {lunch_header_text}
-                <span class="tooltiptext">{"Duration of employee lunch in minutes (will be rounded to nearest 30)"}</span>
+                <span class="tooltiptext">{format!("Duration of employee lunch in minutes (will be rounded to the nearest block of {} minutes)", business.block_size.num_minutes())}</span>
+            </div>
+        </th>
+        <th>
+            <div class="tooltip">
+                {"Wage \u{24D8}"}
+                <span class="tooltiptext">{"Hourly pay, used for the cost totals under the schedule"}</span>
             </div>
         </th>
     </>));
@@ -100,6 +109,7 @@ pub fn BusinessTab() -> Html {
                 <EmpNew />
             </tbody>
         </table>
+        <RosterImport />
     </>)
 }
 
@@ -218,49 +228,104 @@ fn EmpRow(props: &EmpProp) -> Html {
     let business = use_context::<BusinessContext>().expect("No ctx found");
     let emp = &props.emp;
     let mut emp_row = vec![];
-    let (clock_in_ref, clock_out_ref, lunch_ref) = (use_node_ref(), use_node_ref(), use_node_ref());
+    let (lunch_ref, wage_ref) = (use_node_ref(), use_node_ref());
     // let b2 = business.clone();
     let scheduled_cb = {
         let b2 = business.clone(); 
         let scheduled_cb_event = BusinessEvents::ToggleEmployeeScheduled { employee: emp.id.clone() };
         move |_| b2.dispatch(scheduled_cb_event.clone())
     };
-    let clock_cb = {
-        let b2 = business.clone();
-        let emp_id = emp.id.clone();
-        let (clock_in_ref, clock_out_ref) = (clock_in_ref.clone(), clock_out_ref.clone());
-        move |_| b2.dispatch(BusinessEvents::UpdateEmployeeHours {
-            employee: emp_id,
-            clock_in: clock_in_ref.cast::<HtmlInputElement>().unwrap().value(),
-            clock_out: clock_out_ref.cast::<HtmlInputElement>().unwrap().value()
-        })
-    };
-    let lunch = business.block_size.checked_mul(emp.lunch.try_into().unwrap()).unwrap();
+    // One pair of clock inputs per shift; each input sends its new time along with the shift's other one
+    let (open, close) = (business.open.format("%H:%M").to_string(), business.close.format("%H:%M").to_string());
+    let (mut clock_ins, mut clock_outs) = (vec![], vec![]);
+    for (i, shift) in emp.shifts.iter().enumerate() {
+        let (clock_in, clock_out) = (shift.clock_in.format("%H:%M").to_string(), shift.clock_out.format("%H:%M").to_string());
+        let clock_cb = |clock_in: Option<String>, clock_out: Option<String>| {
+            let b2 = business.clone();
+            let emp_id = emp.id;
+            Callback::from(move |e: FocusEvent| {
+                let value = match e.target_dyn_into::<HtmlInputElement>() {
+                    Some(input) => input.value(),
+                    None => return,
+                };
+                b2.dispatch(BusinessEvents::UpdateEmployeeHours {
+                    employee: emp_id,
+                    shift: i,
+                    clock_in: clock_in.clone().unwrap_or(value.clone()),
+                    clock_out: clock_out.clone().unwrap_or(value),
+                })
+            })
+        };
+        clock_ins.push(html!(
+            <div class="shift">
+                <input type="time" name="clock_in" step="60" min={open.clone()} max={close.clone()} value={clock_in.clone()} onblur={clock_cb(None, Some(clock_out.clone()))} disabled={!emp.scheduled} />
+            </div>
+        ));
+        let shift_button = match i + 1 == emp.shifts.len() {
+            true => {
+                let b2 = business.clone();
+                let employee = emp.id;
+                let onclick = move |_| b2.dispatch(BusinessEvents::AddEmployeeShift { employee });
+                html!(<input type="button" value="+" title="Add a shift" onclick={onclick} disabled={!emp.scheduled} />)
+            },
+            false => html!(),
+        };
+        let remove_button = match emp.shifts.len() > 1 {
+            true => {
+                let b2 = business.clone();
+                let employee = emp.id;
+                let onclick = move |_| b2.dispatch(BusinessEvents::RemoveEmployeeShift { employee, shift: i });
+                html!(<input type="button" value="\u{2715}" title="Remove this shift" onclick={onclick} disabled={!emp.scheduled} />)
+            },
+            false => html!(),
+        };
+        clock_outs.push(html!(
+            <div class="shift">
+                <input type="time" name="clock_out" step="60" min={open.clone()} max={close.clone()} value={clock_out.clone()} onblur={clock_cb(Some(clock_in), None)} disabled={!emp.scheduled} />
+                {remove_button}
+                {shift_button}
+            </div>
+        ));
+    }
+    let lunch = i32::try_from(emp.lunch).ok()
+        .and_then(|blocks| business.block_size.checked_mul(blocks))
+        .unwrap_or(business.block_size);
     let lunch_cb = {
         let b = business.clone();
         let emp_id = emp.id.clone();
-        let block_mins = b.block_size.num_minutes();
         let curr_lunch_mins = lunch.num_minutes();
         let lunch_ref = lunch_ref.clone();
         move |_| {
             let lunch_node = lunch_ref.cast::<HtmlInputElement>().unwrap();
             let input: i64 = match lunch_node.value().parse() {
-                Ok(num) => num,
+                Ok(num) if num >= 0 => num,
+                Ok(num) => {
+                    warn!("Lunch cannot be negative; got {}", num);
+                    lunch_node.set_value(&curr_lunch_mins.to_string());
+                    return;
+                },
                 Err(e) => {
                     warn!("Could not parse input lunch as number; {}", e);
                     lunch_node.set_value(&curr_lunch_mins.to_string());
                     return;
                 }
             };
-            let mut full_blocks = input / block_mins;
-            let remainder = input % block_mins;
-            if remainder > 0 {
-                let block_half = b.block_size.checked_div(2).unwrap().num_minutes();
-                if remainder > block_half {
-                    full_blocks += 1;
-                }
+            b.dispatch(BusinessEvents::UpdateEmployeeLunch { emp_id, blocks: b.lunch_blocks(input) });
+        }
+    };
+    let wage_cb = {
+        let b = business.clone();
+        let emp_id = emp.id;
+        let wage_ref = wage_ref.clone();
+        let curr_wage = format_money(emp.wage);
+        move |_| {
+            let wage_node = wage_ref.cast::<HtmlInputElement>().unwrap();
+            let wage = wage_node.value();
+            // Put back the saved wage if this one won't be taken; the reducer reports why
+            if parse_money(&wage).is_none() {
+                wage_node.set_value(&curr_wage);
             }
-            b.dispatch(BusinessEvents::UpdateEmployeeLunch { emp_id: emp_id, blocks: full_blocks.try_into().unwrap() });
+            b.dispatch(BusinessEvents::UpdateEmployeeWage { emp_id, wage })
         }
     };
     emp_row.push(html!(<>
@@ -271,14 +336,17 @@ fn EmpRow(props: &EmpProp) -> Html {
             {emp.name.clone()}
         </td>
         <td>
-            <input id="clock_in" type="time" name="clock_in" step="1800" min={business.open.format("%H:%M").to_string()} max={business.close.format("%H:%M").to_string()} value={emp.clock_in.format("%H:%M").to_string()} ref={clock_in_ref} onblur={clock_cb.clone()} disabled={!emp.scheduled} />
+            {for clock_ins}
         </td>
         <td>
-            <input id="clock_out" type="time" name="clock_out" step="1800" min={business.open.format("%H:%M").to_string()} max={business.close.format("%H:%M").to_string()} value={emp.clock_out.format("%H:%M").to_string()} ref={clock_out_ref} onblur={clock_cb.clone()} disabled={!emp.scheduled} />
+            {for clock_outs}
         </td>
         <td>
             <input id="lunch_time" type="number" name="lunch_time" min={0} value={lunch.num_minutes().to_string()} onblur={lunch_cb} ref={lunch_ref} />
         </td>
+        <td>
+            <input id="wage" type="number" name="wage" min={0} step="0.01" value={format_money(emp.wage)} onblur={wage_cb} ref={wage_ref} />
+        </td>
     </>));
     let mut roles_list: Vec<&Role> = business.roles.values().collect();
     roles_list.sort();
@@ -349,4 +417,110 @@ fn EmpNew() -> Html {
         // <td>
         // </td>
     </tr>)
-}
\ No newline at end of file
+}
+
+/// Paste or open a roster to add and update employees, with a preview of what importing it would do. The current
+/// employees can be exported in the same format.
+#[function_component]
+fn RosterImport() -> Html {
+    let business = use_context::<BusinessContext>().expect("No ctx found");
+    let roster = use_state_eq(String::new);
+    let create_roles = use_state_eq(|| true);
+
+    let oninput = {
+        let roster = roster.clone();
+        Callback::from(move |e: InputEvent| roster.set(e.target_unchecked_into::<HtmlTextAreaElement>().value()))
+    };
+    let onchange = {
+        let roster = roster.clone();
+        Callback::from(move |e: Event| {
+            let roster = roster.clone();
+            read_text_file(&e.target_unchecked_into::<HtmlInputElement>(), move |text| roster.set(text));
+        })
+    };
+    let toggle_create = {
+        let create_roles = create_roles.clone();
+        Callback::from(move |_| create_roles.set(!*create_roles))
+    };
+    let import = {
+        let b = business.clone();
+        let roster = roster.clone();
+        let create_roles = create_roles.clone();
+        Callback::from(move |_| {
+            b.dispatch(BusinessEvents::ImportRoster { roster: (*roster).clone(), create_roles: *create_roles });
+            roster.set(String::new());
+        })
+    };
+
+    let export = format!("data:text/csv;charset=utf-8,{}", percent_encode(&business.roster_csv()));
+    let preview = business.preview_roster(&roster, *create_roles);
+    let (added, updated) = preview.counts();
+    let row = |row: &RosterRow| {
+        let action = match (row.is_valid(), row.employee) {
+            (false, _) => "Skip",
+            (true, None) => "Add",
+            (true, Some(_)) => "Update",
+        };
+        let hours = row.shifts.as_ref().map(|shifts| shifts.iter()
+            .map(|shift| format!("{}\u{2013}{}", shift.clock_in.format("%-H:%M"), shift.clock_out.format("%-H:%M")))
+            .collect::<Vec<_>>().join(", "));
+        let lunch = row.lunch.and_then(|blocks| i32::try_from(blocks).ok())
+            .and_then(|blocks| business.block_size.checked_mul(blocks))
+            .map(|lunch| format!("{} min", lunch.num_minutes()));
+        let problems = row.errors.iter().chain(row.warnings.iter()).cloned().collect::<Vec<_>>().join("; ");
+        html!(<tr class={classes!((!row.is_valid()).then_some("roster-skip"))}>
+            <td>{row.line}</td>
+            <td>{&row.name}</td>
+            <td>{action}</td>
+            <td>{row.roles.as_ref().map(|roles| roles.join(", ")).unwrap_or_default()}</td>
+            <td>{hours.unwrap_or_default()}</td>
+            <td>{lunch.unwrap_or_default()}</td>
+            <td>{row.wage.map(format_money).unwrap_or_default()}</td>
+            <td>{problems}</td>
+        </tr>)
+    };
+    let notes = preview.errors.iter().chain(preview.warnings.iter());
+    let new_roles = match preview.new_roles.is_empty() {
+        true => html!(),
+        false => html!(<p>{format!("Adds the roles {}", preview.new_roles.join(", "))}</p>),
+    };
+
+    html!(<div class="roster">
+        <h4>{"Import a roster"}</h4>
+        <p><a class="mui-btn" href={export} download="roster.csv">{"Export roster"}</a></p>
+        <p>{"A CSV file, or rows pasted from a spreadsheet, with a header row. Columns: name, roles (separated by semicolons), \
+            hours (e.g. 9:00-17:00, or 9:00-12:00; 13:00-17:00), lunch (minutes) and wage. Employees are matched by name, \
+            and blank cells are left as they are."}</p>
+        <textarea rows="6" placeholder="name,roles,hours,lunch,wage" value={(*roster).clone()} oninput={oninput} />
+        <div>
+            <input type="file" accept=".csv,.tsv,.txt,text/csv" onchange={onchange} />
+            <label>
+                <input type="checkbox" checked={*create_roles} onchange={toggle_create} />
+                {"Add roles that don't exist yet"}
+            </label>
+        </div>
+        if !roster.trim().is_empty() {
+            <ul class="roster-notes">
+                {for notes.map(|note| html!(<li>{note}</li>))}
+            </ul>
+            {new_roles}
+            <table class={classes!("mui-table","mui-table--bordered")}>
+                <thead><tr>
+                    <th>{"Line"}</th>
+                    <th>{"Name"}</th>
+                    <th>{"Action"}</th>
+                    <th>{"Roles"}</th>
+                    <th>{"Hours"}</th>
+                    <th>{"Lunch"}</th>
+                    <th>{"Wage"}</th>
+                    <th>{"Problems"}</th>
+                </tr></thead>
+                <tbody>
+                    {for preview.rows.iter().map(row)}
+                </tbody>
+            </table>
+            <input type="button" class="mui-btn" value={format!("Import: add {}, update {}", added, updated)}
+                onclick={import} disabled={!preview.errors.is_empty() || added + updated == 0} />
+        }
+    </div>)
+}
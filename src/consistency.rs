@@ -0,0 +1,127 @@
+use chrono::NaiveTime;
+
+use crate::{data::{Business, RoleTrait}, notifications::Level};
+
+/// A way the schedule breaks its own rules, or refers to something which no longer exists. See
+/// [`Business::check_consistency`].
+#[derive(Clone, PartialEq, Debug)]
+pub enum Inconsistency {
+    /// An employee's assignments overlap, are out of order, or fall outside their hours
+    Misplaced { employee: usize },
+    /// An employee is assigned a role which no longer exists
+    UnknownRole { employee: usize, role: usize, start: NaiveTime },
+    /// An employee is assigned a role they are not allowed to work
+    NotAllowed { employee: usize, role: usize, start: NaiveTime },
+    /// More employees work a role at once than it allows
+    OverCapacity { role: usize, start: NaiveTime, employees: Vec<usize> },
+    /// An employee is allowed to work a role which no longer exists
+    UnknownAllowedRole { employee: usize, role: usize },
+}
+
+impl Business {
+
+    /// Every place the schedule breaks its rules or refers to ids which no longer exist
+    pub fn check_consistency(&self) -> Vec<Inconsistency> {
+        let mut found = vec![];
+        let mut employees: Vec<_> = self.employees.values().collect();
+        employees.sort_by_key(|emp| emp.id);
+        let mut roles: Vec<_> = self.roles.values().collect();
+        roles.sort_by_key(|role| role.id());
+
+        for emp in employees {
+            for role in emp.roles.iter().filter(|role| !self.roles.contains_key(role)) {
+                found.push(Inconsistency::UnknownAllowedRole { employee: emp.id, role: *role });
+            }
+            let mut trimmed = emp.clone();
+            trimmed.trim_assigned();
+            if trimmed.assignments != emp.assignments {
+                found.push(Inconsistency::Misplaced { employee: emp.id });
+            }
+            for assignment in emp.assignments.iter().filter(|assignment| assignment.role >= 2) {
+                let (role, start) = (assignment.role, assignment.start);
+                if !self.roles.contains_key(&role) {
+                    found.push(Inconsistency::UnknownRole { employee: emp.id, role, start });
+                } else if !emp.roles.contains(&role) {
+                    found.push(Inconsistency::NotAllowed { employee: emp.id, role, start });
+                }
+            }
+        }
+
+        for role in roles {
+            let capacity = match role.capacity() {
+                Some(capacity) => capacity,
+                None => continue,
+            };
+            for (start, _, employees) in self.crowded(role.id(), capacity) {
+                found.push(Inconsistency::OverCapacity { role: role.id(), start, employees });
+            }
+        }
+        found
+    }
+
+    /// Fix everything [`Business::check_consistency`] finds: unknown and disallowed roles are freed, misplaced
+    /// assignments are trimmed to the employee's hours, and crowded roles keep the employees with the lowest ids.
+    /// Returns what was found.
+    pub fn repair(&mut self) -> Vec<Inconsistency> {
+        let found = self.check_consistency();
+        if found.is_empty() {
+            return found;
+        }
+
+        for emp in self.employees.values_mut() {
+            emp.roles.retain(|role| self.roles.contains_key(role));
+            emp.trim_assigned();
+            emp.assignments.retain(|assignment| emp.roles.contains(&assignment.role));
+        }
+        let role_ids: Vec<usize> = self.roles.keys().copied().collect();
+        for role in role_ids {
+            self.enforce_capacity(role);
+        }
+        found
+    }
+
+    /// Repair the business, leaving a notice for the user if anything needed fixing. `quiet` skips the notice when
+    /// nothing was wrong. Returns whether anything was repaired.
+    pub fn repair_and_report(&mut self, quiet: bool) -> bool {
+        let found = self.repair();
+        if found.is_empty() {
+            if !quiet {
+                self.notices.push(Level::Info, "No problems found in the schedule");
+            }
+            return false;
+        }
+        for problem in found.iter() {
+            log::warn!("Repaired: {:?}", problem);
+        }
+        let examples: Vec<String> = found.iter().take(3).map(|problem| self.describe_inconsistency(problem)).collect();
+        let more = match found.len() > examples.len() {
+            true => format!("; and {} more", found.len() - examples.len()),
+            false => String::new(),
+        };
+        self.notices.push(Level::Warning, format!("Repaired {} problem(s) in the schedule: {}{}", found.len(), examples.join("; "), more));
+        true
+    }
+
+    pub fn describe_inconsistency(&self, problem: &Inconsistency) -> String {
+        let at = |time: &NaiveTime| time.format("%-I:%M");
+        match problem {
+            Inconsistency::Misplaced { employee } => {
+                format!("{} had overlapping assignments or ones outside their hours", self.employee_name(*employee))
+            },
+            Inconsistency::UnknownRole { employee, role, start } => {
+                format!("{} was assigned deleted {} at {}", self.employee_name(*employee), self.role_name(*role), at(start))
+            },
+            Inconsistency::NotAllowed { employee, role, start } => {
+                format!("{} was assigned {} at {}, which they can't work", self.employee_name(*employee), self.role_name(*role), at(start))
+            },
+            Inconsistency::OverCapacity { role, start, employees } => {
+                let names: Vec<String> = employees.iter().map(|emp| self.employee_name(*emp)).collect();
+                format!("{} were all working {} at {}", names.join(", "), self.role_name(*role), at(start))
+            },
+            Inconsistency::UnknownAllowedRole { employee, role } => {
+                format!("{} was allowed to work deleted {}", self.employee_name(*employee), self.role_name(*role))
+            },
+        }
+    }
+
+}
@@ -5,7 +5,7 @@ use enum_dispatch::enum_dispatch;
 use serde::{Deserialize, Serialize};
 use yew::{AttrValue, Properties};
 
-use crate::settings::Settings;
+use crate::{index::IndexCache, notifications::{Level, Notices}, scheduler::TimeBlock, settings::Settings};
 
 const DEFAULT_COLOR: &'static str = "#AAC406";
 
@@ -14,13 +14,24 @@ pub type Result<T> = std::result::Result<T, BusinessError>;
 pub enum BusinessError {
     EmployeeNotFound,
     RoleNotFound,
-    EmployeeError(EmployeeError)
+    EmployeeError(EmployeeError),
+    /// A swap would leave an employee working a role they can't, or while clocked out
+    CannotSwap(EmployeeError),
+    /// Text from the user could not be understood as the given field
+    InvalidInput { field: &'static str, input: String },
+    /// An employee's last shift already runs too close to closing for another
+    NoTimeForShift,
+    ScenarioNotFound,
 } impl std::fmt::Display for BusinessError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             BusinessError::EmployeeNotFound => write!(f, "Employee not found"),
             BusinessError::RoleNotFound => write!(f, "Role not found"),
             BusinessError::EmployeeError(employee_error) => write!(f, "{}", employee_error),
+            BusinessError::CannotSwap(reason) => write!(f, "Cannot swap; {}", reason),
+            BusinessError::InvalidInput { field, input } => write!(f, "Invalid {}: {}", field, input),
+            BusinessError::NoTimeForShift => write!(f, "No time left for another shift"),
+            BusinessError::ScenarioNotFound => write!(f, "Scenario not found"),
         }
     }
 }
@@ -29,6 +40,9 @@ pub enum BusinessError {
 pub struct Business {
     pub roles: HashMap<usize, Role>,
     pub employees: HashMap<usize, Employee>,
+    /// How many days of role time employees have in their week, see [`Business::record_day`]
+    #[serde(default)]
+    pub days_recorded: usize,
     
     // Computed
     #[serde(skip)]
@@ -43,6 +57,13 @@ pub struct Business {
     pub close: NaiveTime,
     #[serde(skip)]
     pub block_size: TimeDelta, // The size of the schedule's blocks
+
+    /// Messages for the user from the most recent action
+    #[serde(skip)]
+    pub notices: Notices,
+    /// See [`Business::index`]
+    #[serde(skip)]
+    pub index_cache: IndexCache,
 } impl Business {
     pub fn init(&mut self, open: NaiveTime, close: NaiveTime, block_size: TimeDelta) {
         for (_, role) in self.roles.iter() {
@@ -61,10 +82,11 @@ pub struct Business {
                 break;
             }
         }
-        self.roles.insert(id.clone(), SingleRole::new(id, name, self.blocks).into());
+        self.roles.insert(id, SingleRole::new(id, name).into());
         self.role_colors.insert(id.clone(), self.roles[&id].color());
     }
-    pub fn new_employee(&mut self, name: AttrValue) {
+    /// Add an employee working all day, returning their id
+    pub fn new_employee(&mut self, name: AttrValue) -> usize {
         let mut id = 1;
         loop {
             if self.employees.contains_key(&id) {
@@ -73,7 +95,8 @@ pub struct Business {
                 break;
             }
         }
-        self.employees.insert(id.clone(), Employee::new(id, name, self.open.clone(), self.close.clone()).new_blank(self.blocks));
+        self.employees.insert(id, Employee::new(id, name, self.open, self.close));
+        id
     }
     pub fn delete_role(&mut self, role: usize) {
         let role_get = self.roles.remove(&role);
@@ -81,34 +104,13 @@ pub struct Business {
             return;
         }
         let role = role_get.unwrap();
-        let role_assigned = match role.assigned() {
-            RoleAssigned::SingleAssinged(items) => vec![items],
-            RoleAssigned::MultiAssigned(items) => items,
-        };
-        // let role_assigned = role.assigned();
-        for time_slot in role_assigned {
-            for i in 0..time_slot.len() {
-                let x = time_slot[i];
-                if x.eq(&0) {
-                    continue;
-                }
-                let employee = self.employees.get_mut(&x);
-                if employee.is_none() {
-                    continue;
-                }
-                employee.unwrap().remove_block(vec![i]);
-            }
+        for employee in self.employees.values_mut() {
+            employee.release_role(role.id());
+            employee.roles.retain(|id| role.id().ne(id));
         }
     }
     pub fn delete_employee(&mut self, emp: usize) {
-        let emp_get = self.employees.remove(&emp);
-        if emp_get.is_none() {
-            return;
-        }
-        let emp = emp_get.unwrap();
-        for (_,role) in self.roles.iter_mut() {
-            role.clear_employee(&emp.id);
-        }
+        self.employees.remove(&emp);
     }
 
     pub fn update_business_hours(&mut self, open: NaiveTime, close: NaiveTime, block_size: TimeDelta) {
@@ -117,155 +119,584 @@ pub struct Business {
         self.block_size = block_size;
         self.blocks = 0;
         let mut test_time = self.open.clone();
-        let mut empty_vec: Vec<Vec<usize>> = vec![];
         while test_time < self.close {
             test_time += self.block_size;
             self.blocks += 1;
-            empty_vec.push(vec![0]);
-        }
-        let empty_vec_enum = RoleAssigned::MultiAssigned(empty_vec);
-        for (_, role) in self.roles.iter_mut() {
-            role.assigned_set(empty_vec_enum.clone());
         }
+        // Assignments are kept as times; they only move to fit the new blocks, or go if outside the new hours
+        let grid = self.grid();
         for (_, employee) in self.employees.iter_mut() {
-            if employee.clock_in < self.open {employee.clock_in = self.open.clone()};
-            if employee.clock_out > self.close || employee.clock_out < self.open {employee.clock_out = self.close.clone()};
-            employee.clear_assigned(&self.open, &self.close, self.block_size.clone());
+            let shifts = employee.shifts.iter()
+                .map(|shift| Shift { clock_in: shift.clock_in.max(self.open), clock_out: shift.clock_out.min(self.close) })
+                .collect();
+            if !employee.set_shifts(shifts) {
+                employee.shifts = vec![Shift { clock_in: self.open, clock_out: self.close }];
+            }
+            employee.round_assigned(&grid);
         }
     }
+    /// What [`Business::update_business_hours`] would take out of the schedule, by employee id then time. Time an
+    /// assignment gains from rounding isn't listed.
+    pub fn preview_business_hours(&self, open: NaiveTime, close: NaiveTime, block_size: TimeDelta) -> Vec<LostTime> {
+        let mut changed = self.clone();
+        changed.update_business_hours(open, close, block_size);
+        let mut emp_ids: Vec<&usize> = self.employees.keys().collect();
+        emp_ids.sort();
+        let mut lost = vec![];
+        for emp_id in emp_ids {
+            let after = &changed.employees[emp_id].assignments;
+            for assignment in self.employees[emp_id].assignments.iter() {
+                // Whatever the same role doesn't still cover
+                let mut start = assignment.start;
+                for kept in after.iter().filter(|kept| kept.role == assignment.role && kept.end > assignment.start && kept.start < assignment.end) {
+                    if kept.start > start {
+                        lost.push(LostTime { employee: *emp_id, role: assignment.role, start, end: kept.start });
+                    }
+                    start = start.max(kept.end);
+                }
+                if start < assignment.end {
+                    lost.push(LostTime { employee: *emp_id, role: assignment.role, start, end: assignment.end });
+                }
+            }
+        }
+        lost
+    }
     pub fn update_role_color(&mut self, role_id: usize, color: AttrValue) {
         if let Some(role) =  self.roles.get_mut(&role_id) {
             role.color_set(color.clone());
             self.role_colors.insert(role_id, color);
         }
     }
+    /// Switch a role between allowing one or many employees per block. Assignments are kept; going back to a single
+    /// employee keeps whoever has the lowest id at each block and frees the rest.
     pub fn toggle_role_multi(&mut self, role_id: usize) {
         let role = match self.roles.remove(&role_id) {
             Some(x) => x,
             None => return,
         };
-        let new_role = match role {
-            Role::SingleRole(single_role) => {
-                MultiRole::new(single_role.id(), single_role.name(), self.blocks).into()
-            },
-            Role::MultiRole(multi_role) => {
-                SingleRole::new(multi_role.id(), multi_role.name(), self.blocks).into()
-            },
+        let new_role: Role = match role {
+            Role::SingleRole(SingleRole { id, name, sort, color }) => MultiRole { id, name, sort, color }.into(),
+            Role::MultiRole(MultiRole { id, name, sort, color }) => SingleRole { id, name, sort, color }.into(),
         };
         self.roles.insert(role_id, new_role);
+        self.enforce_capacity(role_id);
     }
-    pub fn update_employee_hours(&mut self, id: usize, clock_in: NaiveTime, clock_out: NaiveTime) {
-        let employee = self.employees.get_mut(&id).unwrap();
-        employee.clock_in = clock_in;
-        employee.clock_out = clock_out;
-        employee.clear_assigned(&self.open, &self.close, self.block_size.clone());
-        for (_, role) in self.roles.iter_mut() {
-            role.clear_employee(&id);
+    /// Free employees from times where more are working a role than it allows, keeping those with the lowest ids.
+    /// Returns the (employee, time) pairs which were freed.
+    pub fn enforce_capacity(&mut self, role_id: usize) -> Vec<(usize, Assignment)> {
+        let capacity = match self.roles.get(&role_id).and_then(|role| role.capacity()) {
+            Some(capacity) => capacity,
+            None => return vec![],
+        };
+        let mut freed = vec![];
+        for (start, end, working) in self.crowded(role_id, capacity) {
+            for emp_id in working.into_iter().skip(capacity) {
+                if let Some(emp) = self.employees.get_mut(&emp_id) {
+                    emp.set_span(1, start, end);
+                    freed.push((emp_id, Assignment { role: role_id, start, end, locked: false }));
+                }
+            }
         }
+        freed
     }
-    pub fn toggle_employee_scheduled(&mut self, emp_id: usize) {
-        let employee = self.employees.get_mut(&emp_id).expect("Attempted to toggle scheduled on non-existant employee");
+    /// Stretches of time where more than `capacity` employees work the role, as (start, end, employee ids in ascending
+    /// order)
+    pub fn crowded(&self, role_id: usize, capacity: usize) -> Vec<(NaiveTime, NaiveTime, Vec<usize>)> {
+        let mut emp_ids: Vec<usize> = self.employees.keys().copied().collect();
+        emp_ids.sort();
+        let mut bounds: Vec<NaiveTime> = self.employees.values()
+            .flat_map(|emp| emp.assignments.iter())
+            .filter(|assignment| assignment.role == role_id)
+            .flat_map(|assignment| [assignment.start, assignment.end])
+            .collect();
+        bounds.sort();
+        bounds.dedup();
+        let mut crowded: Vec<(NaiveTime, NaiveTime, Vec<usize>)> = vec![];
+        for pair in bounds.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            let working: Vec<usize> = emp_ids.iter().copied()
+                .filter(|id| self.employees[id].works(role_id, start, end))
+                .collect();
+            if working.len() <= capacity {
+                continue;
+            }
+            match crowded.last_mut() {
+                Some(last) if last.1 == start && last.2 == working => last.1 = end,
+                _ => crowded.push((start, end, working)),
+            }
+        }
+        crowded
+    }
+    /// Employees other than `employee` working the role at any point from start to end
+    fn others_working(&self, employee: usize, role: usize, start: NaiveTime, end: NaiveTime) -> Vec<usize> {
+        let mut others: Vec<usize> = self.employees.values()
+            .filter(|emp| emp.id != employee && emp.works(role, start, end))
+            .map(|emp| emp.id)
+            .collect();
+        others.sort();
+        others
+    }
+    /// Free anyone past the role's capacity from start to end, so `employee` can work it then. Returns how many were
+    /// freed.
+    fn displace(&mut self, employee: usize, role: usize, start: NaiveTime, end: NaiveTime) -> usize {
+        let capacity = match self.roles.get(&role).and_then(|role| role.capacity()) {
+            Some(capacity) => capacity,
+            None => return 0,
+        };
+        let others = self.others_working(employee, role, start, end);
+        let mut displaced = 0;
+        for emp_id in others.iter().skip(capacity.saturating_sub(1)) {
+            if let Some(emp) = self.employees.get_mut(emp_id) {
+                emp.set_span(1, start, end);
+                displaced += 1;
+            }
+        }
+        displaced
+    }
+    /// Have the employee work a single shift from clock_in to clock_out
+    pub fn update_employee_hours(&mut self, id: usize, clock_in: NaiveTime, clock_out: NaiveTime) -> Result<()> {
+        self.update_employee_shifts(id, vec![Shift { clock_in, clock_out }])
+    }
+    /// Replace the employee's shifts, trimming their assignments to fit. Overlapping shifts are joined up.
+    pub fn update_employee_shifts(&mut self, id: usize, shifts: Vec<Shift>) -> Result<()> {
+        let employee = match self.employees.get_mut(&id) {
+            Some(emp) => emp,
+            None => return Err(BusinessError::EmployeeNotFound),
+        };
+        if let Some(shift) = shifts.iter().find(|shift| shift.clock_in >= shift.clock_out) {
+            return Err(BusinessError::InvalidInput { field: "shift", input: format!("{}-{}", shift.clock_in.format("%-I:%M"), shift.clock_out.format("%-I:%M")) });
+        }
+        if !employee.set_shifts(shifts) {
+            return Err(BusinessError::InvalidInput { field: "shift", input: "no shifts".into() });
+        }
+        employee.trim_assigned();
+        Ok(())
+    }
+    /// Move the clock times of one of the employee's shifts, see [`Business::update_employee_shifts`]
+    pub fn update_employee_shift(&mut self, id: usize, shift: usize, clock_in: NaiveTime, clock_out: NaiveTime) -> Result<()> {
+        let mut shifts = match self.employees.get(&id) {
+            Some(emp) => emp.shifts.clone(),
+            None => return Err(BusinessError::EmployeeNotFound),
+        };
+        match shifts.get_mut(shift) {
+            Some(shift) => *shift = Shift { clock_in, clock_out },
+            None => return Err(BusinessError::InvalidInput { field: "shift", input: (shift + 1).to_string() }),
+        }
+        self.update_employee_shifts(id, shifts)
+    }
+    /// Add a shift after the employee's last, starting a block after they clock out and running to close
+    pub fn add_employee_shift(&mut self, id: usize) -> Result<()> {
+        let (block_size, close) = (self.block_size, self.close);
+        let employee = match self.employees.get_mut(&id) {
+            Some(emp) => emp,
+            None => return Err(BusinessError::EmployeeNotFound),
+        };
+        let clock_in = employee.clock_out() + block_size;
+        if clock_in >= close || clock_in <= employee.clock_out() {
+            return Err(BusinessError::NoTimeForShift);
+        }
+        employee.shifts.push(Shift { clock_in, clock_out: close });
+        Ok(())
+    }
+    /// Drop one of the employee's shifts, along with anything they were assigned during it. Their last shift can't
+    /// be removed.
+    pub fn remove_employee_shift(&mut self, id: usize, shift: usize) -> Result<()> {
+        let employee = match self.employees.get_mut(&id) {
+            Some(emp) => emp,
+            None => return Err(BusinessError::EmployeeNotFound),
+        };
+        if shift >= employee.shifts.len() || employee.shifts.len() == 1 {
+            return Err(BusinessError::InvalidInput { field: "shift", input: (shift + 1).to_string() });
+        }
+        employee.shifts.remove(shift);
+        employee.trim_assigned();
+        Ok(())
+    }
+    pub fn update_employee_wage(&mut self, id: usize, wage: usize) -> Result<()> {
+        match self.employees.get_mut(&id) {
+            Some(emp) => emp.wage = wage,
+            None => return Err(BusinessError::EmployeeNotFound),
+        }
+        Ok(())
+    }
+    /// Minutes of lunch as whole blocks, to the nearest block; half a block rounds down
+    pub fn lunch_blocks(&self, minutes: i64) -> usize {
+        let block_mins = self.block_size.num_minutes();
+        let mut full_blocks = minutes / block_mins;
+        if minutes % block_mins > block_mins / 2 {
+            full_blocks += 1;
+        }
+        full_blocks.max(0) as usize
+    }
+    pub fn toggle_employee_scheduled(&mut self, emp_id: usize) -> Result<()> {
+        let employee = match self.employees.get_mut(&emp_id) {
+            Some(emp) => emp,
+            None => return Err(BusinessError::EmployeeNotFound),
+        };
         employee.scheduled = !employee.scheduled;
+        Ok(())
     }
-    pub fn assign_role(&mut self, emp: usize, role: usize) {
-        let employee = self.employees.get_mut(&emp).unwrap();
+    pub fn assign_role(&mut self, emp: usize, role: usize) -> Result<()> {
+        if !self.roles.contains_key(&role) {
+            return Err(BusinessError::RoleNotFound);
+        }
+        let employee = match self.employees.get_mut(&emp) {
+            Some(emp) => emp,
+            None => return Err(BusinessError::EmployeeNotFound),
+        };
         employee.add_role(role);
+        Ok(())
     }
     pub fn restrict_role(&mut self, emp: usize, role: usize) -> Result<()> {
-        let employee = self.employees.get_mut(&emp).unwrap();
-        let role = self.roles.get_mut(&role).unwrap();
-        match employee.remove_role(role.id()) {
-            Ok(_) => {role.clear_employee(&emp); Ok(())},
-            Err(e) => Err(e)
+        let employee = match self.employees.get_mut(&emp) {
+            Some(emp) => emp,
+            None => return Err(BusinessError::EmployeeNotFound),
+        };
+        if !self.roles.contains_key(&role) {
+            return Err(BusinessError::RoleNotFound);
         }
+        employee.remove_role(role)
     }
 
     pub fn assign_block(&mut self, employee: usize, role: usize, blocks: Vec<usize>) -> Result<usize> {
+        if !self.roles.contains_key(&role) {
+            return Err(BusinessError::RoleNotFound);
+        }
+        let grid = self.grid();
         let emp = match self.employees.get_mut(&employee) {
             Some(emp) => emp,
             None => return Err(BusinessError::EmployeeNotFound),
         };
-        let role = match self.roles.get_mut(&role) {
-            Some(x) => x,
-            None => return Err(BusinessError::RoleNotFound),
-        };
         // Assign the block to the target employee
-        let mut modified_blocks = 0;
-        match emp.assign_block(blocks, role.id()) {
-            Ok(x) => {
-                let (successful_indexes,changed_roles) = x;
-                // Assign the target employee to the role
-                modified_blocks += successful_indexes.len();
-                let displaced_employees = role.add_block(&emp.id, successful_indexes);
-                // If this would cause an overlap within a role, remove over employees from that role for overlapping indexes
-                if !displaced_employees.is_empty() {
-                    modified_blocks += displaced_employees.len();
-                    for (emp_id, index) in displaced_employees {
-                        if let Some(emp) = self.employees.get_mut(&emp_id) {
-                            emp.remove_block(vec![index]);
-                        }
-                    }
+        let successful_indexes = emp.assign_block(&grid, blocks, role)?;
+        let mut modified_blocks = successful_indexes.len();
+        // If this would put too many employees on the role, free the others for those blocks
+        for index in successful_indexes {
+            let (start, end) = grid.block_span(index);
+            modified_blocks += self.displace(employee, role, start, end);
+        }
+        Ok(modified_blocks)
+    }
+    /// Assign a role from start to end, within the employee's hours, displacing anyone else as
+    /// [`Business::assign_block`] does
+    pub fn assign_span(&mut self, employee: usize, role: usize, start: NaiveTime, end: NaiveTime) -> Result<()> {
+        if !self.roles.contains_key(&role) {
+            return Err(BusinessError::RoleNotFound);
+        }
+        let emp = match self.employees.get_mut(&employee) {
+            Some(emp) => emp,
+            None => return Err(BusinessError::EmployeeNotFound),
+        };
+        if !emp.roles.contains(&role) {
+            return Err(BusinessError::EmployeeError(EmployeeError::NotAssignedRole { employee, failed: role, allowed: emp.roles.clone() }));
+        }
+        let on_shift = emp.on_shift(start, end);
+        if on_shift.is_empty() {
+            return Err(BusinessError::EmployeeError(EmployeeError::NotClockedIn { employee }));
+        }
+        emp.set_span(role, start, end);
+        for (start, end) in on_shift {
+            self.displace(employee, role, start, end);
+        }
+        Ok(())
+    }
+    /// Move one edge of an employee's run of a role to the target block. Growing assigns the new blocks (displacing
+    /// anyone else as [`Business::assign_block`] does), shrinking frees them. The run always keeps at least one block.
+    pub fn resize_run(&mut self, employee: usize, role: usize, start: usize, len: usize, edge: RunEdge, target: usize) -> Result<()> {
+        let end = start + len.max(1) - 1;
+        match edge {
+            RunEdge::Start if target < start => self.assign_block(employee, role, (target..start).collect()).map(|_| ()),
+            RunEdge::Start => self.remove_block(employee, (start..target.min(end)).collect()),
+            RunEdge::End if target > end => self.assign_block(employee, role, (end + 1..=target).collect()).map(|_| ()),
+            RunEdge::End => self.remove_block(employee, (target.max(start) + 1..=end).collect()),
+        }
+    }
+    /// Lock or unlock the employee's run of blocks through the given block, so automations leave it alone. Blocks which
+    /// aren't assigned a role are left as they are.
+    pub fn toggle_lock(&mut self, employee: usize, block: usize) -> Result<()> {
+        let grid = self.grid();
+        let emp = match self.employees.get_mut(&employee) {
+            Some(emp) => emp,
+            None => return Err(BusinessError::EmployeeNotFound),
+        };
+        let assigned = emp.assigned(&grid);
+        let role = match assigned.get(block) {
+            Some(role) if *role >= 2 => *role,
+            _ => return Ok(()),
+        };
+        let first = (0..block).rev().take_while(|i| assigned[*i] == role).last().unwrap_or(block);
+        let last = (block + 1..assigned.len()).take_while(|i| assigned[*i] == role).last().unwrap_or(block);
+        let (start, end) = (grid.block_span(first).0, grid.block_span(last).1);
+        let locked = !emp.locked_at(&grid, block);
+        emp.set_locked(role, start, end, locked);
+        Ok(())
+    }
+    /// Move a run of blocks from one place on the grid to another, as when dropping `drag_block` onto `target_block`.
+    /// `held_block` is the block of the run the user picked it up by, so the run keeps its position relative to the
+    /// cursor. Dropping onto a row with no employee (the role key) clears the run instead.
+    pub fn drag_assign_block(&mut self, target_block: &TimeBlock, drag_block: &TimeBlock, held_block: &TimeBlock) -> Result<()> {
+        if target_block.role == drag_block.role && target_block.time_index == held_block.time_index {
+            return Ok(());
+        }
+        let mut target_block_time_indexes;
+        let mut drag_block_time_indexes;
+        if drag_block.len <= 1 {
+            target_block_time_indexes = vec![target_block.time_index];
+            drag_block_time_indexes = vec![drag_block.time_index]
+        } else {
+            target_block_time_indexes = vec![];
+            drag_block_time_indexes = vec![];
+            for i in 0..held_block.len {
+                // Blocks which would land before open are dropped
+                let (target_index, drag_index) = match i <= held_block.len_index {
+                    true => (target_block.time_index.checked_sub(i), held_block.time_index.checked_sub(i)),
+                    false => (
+                        Some(target_block.time_index + (i - held_block.len_index)),
+                        Some(held_block.time_index + (i - held_block.len_index))
+                    ),
+                };
+                if let (Some(index), true) = (target_index, target_block.emp_id != 0) {
+                    target_block_time_indexes.push(index);
                 }
-                // If target employee was already assigned a role for an index, remove employee from that role
-                for (role_id, index) in changed_roles {
-                    if let Some(role) = self.roles.get_mut(&role_id) {
-                        role.remove_block(&employee, vec![index]);
-                    }
+                if let Some(index) = drag_index {
+                    drag_block_time_indexes.push(index);
                 }
+            }
+        }
+        match self.assign_block(target_block.emp_id, drag_block.role, target_block_time_indexes.clone()) {
+            Err(_) if target_block.emp_id == 0 => self.remove_block(drag_block.emp_id, drag_block_time_indexes),
+            Err(e) => Err(e),
+            Ok(_) if drag_block.emp_id == 0 => Ok(()),
+            Ok(_) if drag_block.emp_id == target_block.emp_id => {
+                drag_block_time_indexes.retain(|index| !target_block_time_indexes.contains(index));
+                self.remove_block(drag_block.emp_id, drag_block_time_indexes)
             },
-            Err(e) => return Err(e)
+            Ok(_) => self.remove_block(drag_block.emp_id, drag_block_time_indexes),
         }
-        Ok(modified_blocks)
+    }
+    /// Exchange two employees' assignments over the given blocks. The swap is refused, with nothing changed, if either
+    /// employee would be given a role they are not allowed to work or a role while clocked out.
+    pub fn swap_blocks(&mut self, first: usize, second: usize, blocks: Vec<usize>) -> Result<()> {
+        if first == second {
+            return Ok(());
+        }
+        let (emp_1, emp_2) = match (self.employees.get(&first), self.employees.get(&second)) {
+            (Some(emp_1), Some(emp_2)) => (emp_1, emp_2),
+            _ => return Err(BusinessError::EmployeeNotFound),
+        };
+        let grid = self.grid();
+        let (assigned_1, assigned_2) = (emp_1.assigned(&grid), emp_2.assigned(&grid));
+        let mut first_roles: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut second_roles: HashMap<usize, Vec<usize>> = HashMap::new();
+        for index in blocks.iter() {
+            let (role_1, role_2) = match (assigned_1.get(*index), assigned_2.get(*index)) {
+                (Some(role_1), Some(role_2)) => (*role_1, *role_2),
+                _ => continue,
+            };
+            for (to, to_assigned, role) in [(emp_2, &assigned_2, role_1), (emp_1, &assigned_1, role_2)] {
+                if role < 2 {
+                    continue;
+                }
+                if to_assigned[*index] == 0 {
+                    return Err(BusinessError::CannotSwap(EmployeeError::NotClockedIn { employee: to.id }));
+                }
+                if !to.roles.contains(&role) {
+                    return Err(BusinessError::CannotSwap(EmployeeError::NotAssignedRole { employee: to.id, failed: role, allowed: to.roles.clone() }));
+                }
+                let to_roles = match to.id == first { true => &mut first_roles, false => &mut second_roles };
+                to_roles.entry(role).or_default().push(*index);
+            }
+        }
+        self.remove_block(first, blocks.clone())?;
+        self.remove_block(second, blocks)?;
+        for (role, indexes) in first_roles {
+            self.assign_block(first, role, indexes)?;
+        }
+        for (role, indexes) in second_roles {
+            self.assign_block(second, role, indexes)?;
+        }
+        Ok(())
     }
     pub fn remove_block(&mut self, employee: usize, blocks: Vec<usize>) -> Result<()>{
+        let grid = self.grid();
         let emp_get = self.employees.get_mut(&employee);
         if emp_get.is_none() {
             return Err(BusinessError::EmployeeNotFound);
         }
-        let emp = emp_get.unwrap();
-        let roles_to_clear = emp.remove_block(blocks);
-        if roles_to_clear.is_empty() {
-            return Ok(());
+        emp_get.unwrap().remove_block(&grid, blocks);
+        Ok(())
+    }
+}
+
+impl Business {
+    /// Name of an employee for messages, falling back to their id if they no longer exist
+    pub fn employee_name(&self, id: usize) -> String {
+        match self.employees.get(&id) {
+            Some(emp) => emp.name.to_string(),
+            None => format!("employee #{}", id),
         }
-        for (role_id, index) in roles_to_clear {
-            let role_get = self.roles.get_mut(&role_id.try_into().unwrap());
-            if role_get.is_none() {
-                return Err(BusinessError::RoleNotFound);
-            }
-            role_get.unwrap().remove_block(&employee, vec![index]);
+    }
+    /// Name of a role for messages, falling back to its id if it no longer exists
+    pub fn role_name(&self, id: usize) -> String {
+        match self.roles.get(&id) {
+            Some(role) => role.name().to_string(),
+            None => format!("role #{}", id),
+        }
+    }
+
+    /// The blocks of the current business hours
+    pub fn grid(&self) -> Grid {
+        Grid { open: self.open, close: self.close, block_size: self.block_size, blocks: self.blocks }
+    }
+    /// Time at which a block starts
+    pub fn block_time(&self, block: usize) -> NaiveTime {
+        self.grid().block_time(block)
+    }
+
+    /// User facing description of an error, using employee and role names rather than ids
+    pub fn describe_error(&self, error: &BusinessError) -> String {
+        match error {
+            BusinessError::EmployeeNotFound => "That employee no longer exists".into(),
+            BusinessError::RoleNotFound => "That role no longer exists".into(),
+            BusinessError::EmployeeError(employee_error) => self.describe_employee_error(employee_error),
+            BusinessError::CannotSwap(reason) => format!("Swap cancelled: {}", self.describe_employee_error(reason)),
+            BusinessError::InvalidInput { field, input } => format!("\"{}\" isn't a valid {}", input, field),
+            BusinessError::NoTimeForShift => "There's no time left in the day for another shift".into(),
+            BusinessError::ScenarioNotFound => "That scenario no longer exists".into(),
+        }
+    }
+    fn describe_employee_error(&self, error: &EmployeeError) -> String {
+        match error {
+            EmployeeError::NotAssignedRole { employee, failed, allowed } => {
+                let allowed: Vec<String> = allowed.iter()
+                    .filter(|role| **role != 2)
+                    .map(|role| self.role_name(*role))
+                    .collect();
+                let allowed = match allowed.is_empty() {
+                    true => "no roles yet".to_string(),
+                    false => allowed.join(", "),
+                };
+                format!("{} can't work {} (allowed: {})", self.employee_name(*employee), self.role_name(*failed), allowed)
+            },
+            EmployeeError::NotClockedIn { employee } => format!("{} isn't clocked in then", self.employee_name(*employee)),
+        }
+    }
+
+    /// Queue an error for the user, as well as logging it
+    pub fn report(&mut self, error: BusinessError) {
+        log::warn!("{}", error);
+        let message = self.describe_error(&error);
+        self.notices.push(Level::Error, message);
+    }
+}
+
+/// How business hours are divided into blocks for display and editing. Assignments are kept as times, so the same
+/// schedule can be shown on any grid.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Grid {
+    pub open: NaiveTime,
+    pub close: NaiveTime,
+    pub block_size: TimeDelta,
+    pub blocks: usize,
+} impl Grid {
+    /// Time at which a block starts
+    pub fn block_time(&self, block: usize) -> NaiveTime {
+        self.open + self.block_size * block as i32
+    }
+    /// The block boundary nearest to the time, from open to close. Halfway rounds later.
+    pub fn round(&self, time: NaiveTime) -> NaiveTime {
+        let size = self.block_size.num_seconds().max(1);
+        let offset = (time - self.open).num_seconds();
+        if offset <= 0 {
+            return self.open;
+        }
+        let blocks = (offset + size / 2) / size;
+        match self.open.overflowing_add_signed(TimeDelta::seconds(blocks * size)) {
+            (rounded, 0) if rounded < self.close => rounded,
+            _ => self.close,
+        }
+    }
+    /// Start and end of a block. The last block of the day ends at close, even if that makes it short.
+    pub fn block_span(&self, block: usize) -> (NaiveTime, NaiveTime) {
+        let start = self.block_time(block);
+        let end = match start.overflowing_add_signed(self.block_size) {
+            (end, 0) if end < self.close => end,
+            _ => self.close,
+        };
+        (start, end)
+    }
+}
+
+/// A role worked by an employee from `start` until `end`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Assignment {
+    pub role: usize,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// Pinned by the user; automations work around it rather than moving or clearing it
+    pub locked: bool,
+}
+
+/// A stretch of the day an employee is clocked in for. Someone working a split shift has more than one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Shift {
+    pub clock_in: NaiveTime,
+    pub clock_out: NaiveTime,
+}
+
+/// Part of an assignment which a change would remove, see [`Business::preview_business_hours`]
+#[derive(Clone, PartialEq, Debug)]
+pub struct LostTime {
+    pub employee: usize,
+    pub role: usize,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+/// Which end of a run of blocks is being resized
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum RunEdge {
+    Start,
+    End
+} impl std::fmt::Display for RunEdge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunEdge::Start => write!(f, "start"),
+            RunEdge::End => write!(f, "end"),
+        }
+    }
+} impl std::str::FromStr for RunEdge {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "start" => Ok(RunEdge::Start),
+            "end" => Ok(RunEdge::End),
+            _ => Err(format!("Invalid run edge: {}", s)),
         }
-        Ok(())
     }
 }
 
 // dyn_clone::clone_trait_object!(RoleTrait);
+/// Roles don't store who is working them; that lives only in [`Employee::assigned`], and role views are derived through
+/// [`crate::index::ScheduleIndex`]. The kind of role only decides how many employees may work it at once.
 #[enum_dispatch]
 pub trait RoleTrait: std::fmt::Debug {
-    // fn new(id: usize, name: AttrValue, blocks: usize) -> Self where Self: Sized;
-    // // fn new_with_assigned(id: usize, name: AttrValue, assigned: Vec<usize>, empty: bool) -> Self where Self: Sized;
-    // /// Only to be used when future init of assigned is planned
-    // fn new_blank(id: usize, name: AttrValue, color: AttrValue) -> Self where Self: Sized;
+    // fn new(id: usize, name: AttrValue) -> Self where Self: Sized;
 
-    /// Replaces the employee ID at the given indexes, returning a list of (replaced employee id, at time index)
-    fn add_block(&mut self, id: &usize, indexes: Vec<usize>) -> Vec<(usize, usize)>;
-    fn remove_block(&mut self, emp: &usize, indexes: Vec<usize>);
-    /// Completely remove all of an employee's scheduled times from the role
-    fn clear_employee(&mut self, id: &usize);
+    /// Most employees who may work the role in the same block, or None for no limit
+    fn capacity(&self) -> Option<usize>;
 
     // Getters and setters
     fn id(&self) -> usize;
     fn name(&self) -> AttrValue;
     fn sort(&self) -> usize;
     fn sort_set(&mut self, sort: usize);
-    fn is_multi(&self) -> bool;
-    /// Public for debug purposes only, table of roles will likely not be visible in final product
-    fn assigned(&self) -> RoleAssigned;
-    fn assigned_set(&mut self, assigned: RoleAssigned);
+    fn is_multi(&self) -> bool {
+        self.capacity().is_none()
+    }
     fn color(&self) -> AttrValue;
     fn color_set(&mut self, color: AttrValue);
-    fn is_empty(&self) -> bool;
-
-    fn blank_out(&mut self, blocks: usize);
 }
 
 #[enum_dispatch(RoleTrait)]
@@ -281,7 +712,7 @@ impl PartialEq for Role {
         self.id() == other.id() &&
         self.name() == other.name() &&
         self.sort() == other.sort() &&
-        self.assigned() == other.assigned() &&
+        self.is_multi() == other.is_multi() &&
         self.color() == other.color()
     }
 }
@@ -299,6 +730,7 @@ impl Ord for Role {
     }
 }
 
+/// Who works a role at each block, shaped by the role's capacity. See [`crate::index::ScheduleIndex::role_assigned`].
 #[derive(Clone, PartialEq, Debug)]
 pub enum RoleAssigned {
     SingleAssinged(Vec<usize>),
@@ -325,132 +757,41 @@ pub enum RoleAssigned {
     }
 }
 
+/// A role worked by one employee at a time
 #[derive(Clone, PartialEq, Debug, Properties, Serialize, Deserialize)]
 pub struct SingleRole {
     id: usize,
     name: AttrValue,
     sort: usize,
-    #[serde(skip)]
-    assigned: Vec<usize>,
     color: AttrValue,
-    #[serde(skip)]
-    empty: bool
 } impl RoleTrait for SingleRole {
-
-    fn add_block(&mut self, id: &usize, indexes: Vec<usize>) -> Vec<(usize, usize)> {
-        let mut replaced_employees = vec![];
-        for index in indexes {
-            if self.assigned[index] > 0 {
-                replaced_employees.push((self.assigned[index].clone(), index.clone()));
-            }
-            self.assigned[index] = id.clone();
-        }
-        if self.empty {
-            self.empty = false;
-        }
-        replaced_employees
-    }
-    fn remove_block(&mut self, _emp: &usize, indexes: Vec<usize>) {
-        for index in indexes {
-            self.assigned[index] = 0;
-        }
-    }
-    fn clear_employee(&mut self, id: &usize) {
-        for i in 0..self.assigned.len() {
-            if self.assigned[i].eq(id) {
-                self.assigned[i] = 0;
-            }
-        }
-    }
+    fn capacity(&self) -> Option<usize> {Some(1)}
 
     fn id(&self) -> usize {self.id.clone()}
     fn name(&self) -> AttrValue {self.name.clone()}
     fn sort(&self) -> usize {self.sort.clone()}
     fn sort_set(&mut self, sort: usize) {self.sort = sort;}
-    fn is_multi(&self) -> bool {false}
-    fn assigned(&self) -> RoleAssigned {RoleAssigned::SingleAssinged(self.assigned.clone())}
-    fn assigned_set(&mut self, assigned: RoleAssigned) {
-        self.assigned = match assigned {
-            RoleAssigned::MultiAssigned(items) => {
-                        let flat: Vec<usize> = items.clone().into_iter().flatten().collect();
-                        if flat.len().eq(&items.len()) {
-                            flat
-                        } else {
-                            panic!("Improper init of role! Tried to assign {:#?} to role {}", items, self.name);
-                        }
-                    }
-            RoleAssigned::SingleAssinged(items) => items,
-        };
-    }
     fn color(&self) -> AttrValue {self.color.clone()}
     fn color_set(&mut self, color: AttrValue) {self.color = color;}
-    fn is_empty(&self) -> bool {self.empty}
-
-    fn blank_out(&mut self, blocks: usize) {
-        self.assigned = vec![0;blocks];
-    }
 } impl SingleRole {
-    fn new(id: usize, name: AttrValue, blocks: usize) -> SingleRole {
-        let mut assigned = vec![];
-        for _ in 0..blocks {
-            assigned.push(0);
-        }
-        SingleRole { id: id.clone(), name: name, sort: id, assigned: assigned, color: DEFAULT_COLOR.into(), empty: true }
+    fn new(id: usize, name: AttrValue) -> SingleRole {
+        SingleRole { id, name, sort: id, color: DEFAULT_COLOR.into() }
     }
-    // fn new_with_assigned(id: usize, name: AttrValue, assigned: Vec<usize>, empty: bool) -> SingleRole {
-    //     SingleRole { id: id.clone(), name: name, sort: id, assigned: assigned, color: DEFAULT_COLOR.into(), empty: empty }
-    // }
-    fn new_blank(id: usize, name: AttrValue, color: AttrValue) -> SingleRole {
-        SingleRole { id: id.clone(), name: name, sort: id, assigned: vec![], color: color, empty: true }
+    fn new_with_color(id: usize, name: AttrValue, color: AttrValue) -> SingleRole {
+        SingleRole { id, name, sort: id, color }
     }
 }
 
+/// A role any number of employees can work at once, such as lunch
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct MultiRole {
     id: usize,
     name: AttrValue,
     sort: usize,
-    #[serde(skip)]
-    assigned: Vec<Vec<usize>>,
     color: AttrValue,
-    #[serde(skip)]
-    empty: bool
 } impl RoleTrait for MultiRole {
-
-    fn add_block(&mut self, id: &usize, indexes: Vec<usize>) -> Vec<(usize, usize)> {
-        for index in indexes {
-            let block_get = self.assigned.get_mut(index);
-            if let Some(block) = block_get {
-                if !block.contains(id) {
-                    block.push(id.clone());
-                }
-            }
-        }
-        if self.empty {
-            self.empty = false;
-        }
-        vec![]
-    }
-    fn remove_block(&mut self, emp: &usize, indexes: Vec<usize>) {
-        for time_block_index in indexes {
-            if let Some(time_block) = self.assigned.get_mut(time_block_index) {
-                for i in 0..time_block.len() {
-                    if time_block[i].eq(emp) {
-                        time_block.remove(i);
-                        break;
-                    }
-                }
-            }
-        }
-    }
-    fn clear_employee(&mut self, id: &usize) {
-        for block in self.assigned.iter_mut() {
-            for i in block.len()..0 {
-                if block[i].eq(id) {
-                    block.remove(i);
-                }
-            }
-        }
+    fn capacity(&self) -> Option<usize> {
+        None
     }
 
     fn id(&self) -> usize {
@@ -465,66 +806,33 @@ pub struct MultiRole {
     fn sort_set(&mut self, sort: usize) {
         self.sort = sort;
     }
-    fn is_multi(&self) -> bool {
-        true
-    }
-    fn assigned(&self) -> RoleAssigned {
-        RoleAssigned::MultiAssigned(self.assigned.clone())
-    }
-    fn assigned_set(&mut self, assigned: RoleAssigned) {
-        self.assigned = match assigned {
-            RoleAssigned::SingleAssinged(items) => {
-                let mut multi_vec = vec![];
-                for item in items {
-                    multi_vec.push(vec![item]);
-                }
-                multi_vec
-            },
-            RoleAssigned::MultiAssigned(items) => items,
-        };
-    }
     fn color(&self) -> AttrValue {
         self.color.clone()
     }
     fn color_set(&mut self, color: AttrValue) {
         self.color = color;
     }
-    fn is_empty(&self) -> bool {
-        self.empty
-    }
-
-    fn blank_out(&mut self,blocks:usize) {
-        let mut assigned = Vec::new();
-        for _ in 0..blocks {
-            assigned.push(Vec::new());
-        }
-        self.assigned = assigned;
-    }
 } impl MultiRole {
-    fn new(id: usize, name: AttrValue, blocks: usize) -> Self where Self: Sized {
-        let mut assigned = vec![];
-        for _ in 0..blocks {
-            assigned.push(vec![]);
-        }
-        MultiRole { id: id.clone(), name, sort: id, assigned, color: DEFAULT_COLOR.into(), empty: true }
+    fn new(id: usize, name: AttrValue) -> Self where Self: Sized {
+        MultiRole { id, name, sort: id, color: DEFAULT_COLOR.into() }
     }
 }
 
 #[derive(Debug)]
 pub enum EmployeeError {
-    NotAssignedRole { failed: usize, allowed: Vec<usize> },
-    NotClockedIn
+    NotAssignedRole { employee: usize, failed: usize, allowed: Vec<usize> },
+    NotClockedIn { employee: usize }
 } impl std::fmt::Display for EmployeeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            EmployeeError::NotAssignedRole { failed, allowed } => {
-                write!(f, "Employee not assigned to role {}; can be assigned: ", failed)?;
+            EmployeeError::NotAssignedRole { employee, failed, allowed } => {
+                write!(f, "Employee {} not assigned to role {}; can be assigned: ", employee, failed)?;
                 for allowed in allowed {
                     write!(f, "{},", allowed)?;
                 }
                 write!(f, "")
             },
-            EmployeeError::NotClockedIn => write!(f, "Employee not clocked in"),
+            EmployeeError::NotClockedIn { employee } => write!(f, "Employee {} not clocked in", employee),
         }
     }
 }
@@ -548,11 +856,197 @@ pub struct Employee {
     pub roles: Vec<usize>, // Roles an employee can be assigned to
     #[serde(skip)]
     pub scheduled: bool,
-    pub lunch: usize, // Length of lunch break in 30-minute intervals (1 hour is 2)
+    pub lunch: usize, // Length of lunch break in blocks (1 hour is 2 with 30-minute blocks)
+    /// Hourly pay in cents, for cost totals
+    #[serde(default)]
+    pub wage: usize,
+    /// Minutes on each role on the days recorded so far this week, for sharing roles out fairly over the week
+    #[serde(default)]
+    pub week_minutes: HashMap<usize, i64>,
+    /// When the employee is clocked in, in order and with time off between each. There is always at least one.
     #[serde(skip)]
-    pub clock_in: NaiveTime,
+    pub shifts: Vec<Shift>,
+    /// Roles the employee works and when, in order and without overlaps, all within their shifts. This is the only
+    /// record of who works what; block and role views are derived from it (see [`Business::index`]).
     #[serde(skip)]
-    pub clock_out: NaiveTime,
+    pub assignments: Vec<Assignment>
+} impl Employee {
+    pub fn new(id: usize, name: AttrValue, clock_in: NaiveTime, clock_out: NaiveTime) -> Employee {
+        Employee { id, name, roles: vec![2], scheduled: true, lunch: 2, wage: 0, week_minutes: HashMap::new(), shifts: vec![Shift { clock_in, clock_out }], assignments: vec![] }
+    }
+    /// When the employee first clocks in
+    pub fn clock_in(&self) -> NaiveTime {
+        self.shifts.first().map_or(NaiveTime::MIN, |shift| shift.clock_in)
+    }
+    /// When the employee last clocks out
+    pub fn clock_out(&self) -> NaiveTime {
+        self.shifts.last().map_or(NaiveTime::MIN, |shift| shift.clock_out)
+    }
+    /// Put the shifts in order, dropping empty ones and joining any which overlap or meet. Leaves the shifts alone and
+    /// returns false if none would be left.
+    pub fn set_shifts(&mut self, mut shifts: Vec<Shift>) -> bool {
+        shifts.retain(|shift| shift.clock_in < shift.clock_out);
+        shifts.sort_by_key(|shift| shift.clock_in);
+        let mut joined: Vec<Shift> = vec![];
+        for shift in shifts {
+            match joined.last_mut() {
+                Some(last) if shift.clock_in <= last.clock_out => last.clock_out = last.clock_out.max(shift.clock_out),
+                _ => joined.push(shift),
+            }
+        }
+        if joined.is_empty() {
+            return false;
+        }
+        self.shifts = joined;
+        true
+    }
+    /// The parts of start to end the employee is clocked in for
+    pub fn on_shift(&self, start: NaiveTime, end: NaiveTime) -> Vec<(NaiveTime, NaiveTime)> {
+        self.shifts.iter()
+            .map(|shift| (start.max(shift.clock_in), end.min(shift.clock_out)))
+            .filter(|(start, end)| start < end)
+            .collect()
+    }
+    /// Drop every assignment, leaving the employee available for all of their shifts
+    pub fn clear_assigned(&mut self) {
+        self.assignments.clear();
+    }
+    /// Put the assignments back in order, cut them down to the employee's shifts and join up neighbours of the same
+    /// role and lock. Where two overlap, the earlier one keeps the time. An assignment running across time off is split
+    /// in two.
+    pub fn trim_assigned(&mut self) {
+        let mut assignments = std::mem::take(&mut self.assignments);
+        assignments.sort_by_key(|assignment| (assignment.start, assignment.end));
+        let mut trimmed: Vec<Assignment> = vec![];
+        for mut assignment in assignments.into_iter().filter(|assignment| assignment.role >= 2) {
+            if let Some(last) = trimmed.last_mut() {
+                assignment.start = assignment.start.max(last.end);
+                if last.role == assignment.role && last.locked == assignment.locked && last.end == assignment.start {
+                    last.end = last.end.max(assignment.end);
+                    continue;
+                }
+            }
+            if assignment.start < assignment.end {
+                trimmed.push(assignment);
+            }
+        }
+        for assignment in trimmed {
+            for (start, end) in self.on_shift(assignment.start, assignment.end) {
+                self.assignments.push(Assignment { start, end, ..assignment });
+            }
+        }
+    }
+    /// Move both ends of every assignment to the nearest block boundary, then trim them to the employee's hours.
+    /// Assignments shorter than half a block may be rounded away.
+    pub fn round_assigned(&mut self, grid: &Grid) {
+        for assignment in self.assignments.iter_mut() {
+            assignment.start = grid.round(assignment.start);
+            assignment.end = grid.round(assignment.end);
+        }
+        self.trim_assigned();
+    }
+    /// Work the role from start to end, replacing whatever was there. Role 1 (available) just clears the time.
+    pub fn set_span(&mut self, role: usize, start: NaiveTime, end: NaiveTime) {
+        let (start, end) = (start.max(self.clock_in()), end.min(self.clock_out()));
+        if start >= end {
+            return;
+        }
+        let mut kept = vec![];
+        for assignment in self.assignments.drain(..) {
+            if assignment.end <= start || assignment.start >= end {
+                kept.push(assignment);
+                continue;
+            }
+            if assignment.start < start {
+                kept.push(Assignment { end: start, ..assignment });
+            }
+            if assignment.end > end {
+                kept.push(Assignment { start: end, ..assignment });
+            }
+        }
+        kept.push(Assignment { role, start, end, locked: false });
+        self.assignments = kept;
+        self.trim_assigned();
+    }
+    /// Lock or unlock the role from start to end, splitting assignments which run past either end
+    pub fn set_locked(&mut self, role: usize, start: NaiveTime, end: NaiveTime, locked: bool) {
+        let mut split = vec![];
+        for assignment in self.assignments.drain(..) {
+            if assignment.role != role || assignment.end <= start || assignment.start >= end {
+                split.push(assignment);
+                continue;
+            }
+            if assignment.start < start {
+                split.push(Assignment { end: start, ..assignment });
+            }
+            split.push(Assignment { start: assignment.start.max(start), end: assignment.end.min(end), locked, ..assignment });
+            if assignment.end > end {
+                split.push(Assignment { start: end, ..assignment });
+            }
+        }
+        self.assignments = split;
+        self.trim_assigned();
+    }
+    /// What the employee is doing from start to end, as back to back spans with role 0 for clocked out and 1 for
+    /// available
+    pub fn segments(&self, start: NaiveTime, end: NaiveTime) -> Vec<Assignment> {
+        fn push(segments: &mut Vec<Assignment>, role: usize, start: NaiveTime, end: NaiveTime) {
+            if start >= end {
+                return;
+            }
+            match segments.last_mut() {
+                Some(last) if last.role == role => last.end = end,
+                _ => segments.push(Assignment { role, start, end, locked: false }),
+            }
+        }
+        let mut segments = vec![];
+        let mut time = start;
+        for shift in self.shifts.iter() {
+            let clock_in = shift.clock_in.clamp(time, end.max(time));
+            let clock_out = shift.clock_out.clamp(clock_in, end.max(clock_in));
+            push(&mut segments, 0, time, clock_in);
+            time = clock_in;
+            for assignment in self.assignments.iter().filter(|assignment| assignment.end > clock_in && assignment.start < clock_out) {
+                let (assignment_start, assignment_end) = (assignment.start.max(clock_in), assignment.end.min(clock_out));
+                push(&mut segments, 1, time, assignment_start);
+                push(&mut segments, assignment.role, assignment_start, assignment_end);
+                time = assignment_end;
+            }
+            push(&mut segments, 1, time, clock_out);
+            time = clock_out;
+        }
+        push(&mut segments, 0, time, end);
+        segments
+    }
+    /// What the employee does for most of the block they are clocked in for: 0 if they aren't in at all, 1 if
+    /// available, otherwise the role id. Ties go to whatever comes first.
+    pub fn block_role(&self, grid: &Grid, block: usize) -> usize {
+        let (start, end) = grid.block_span(block);
+        let mut totals: Vec<(usize, TimeDelta)> = vec![];
+        for segment in self.segments(start, end).iter().filter(|segment| segment.role != 0) {
+            match totals.iter_mut().find(|(role, _)| *role == segment.role) {
+                Some((_, total)) => *total += segment.end - segment.start,
+                None => totals.push((segment.role, segment.end - segment.start)),
+            }
+        }
+        totals.into_iter()
+            .fold((0, TimeDelta::zero()), |best, (role, total)| if total > best.1 { (role, total) } else { best })
+            .0
+    }
+    /// Whether the employee works the role at any point from start to end
+    pub fn works(&self, role: usize, start: NaiveTime, end: NaiveTime) -> bool {
+        self.assignments.iter().any(|assignment| assignment.role == role && assignment.start < end && assignment.end > start)
+    }
+    /// Whether any locked assignment falls between start and end
+    pub fn locked(&self, start: NaiveTime, end: NaiveTime) -> bool {
+        self.assignments.iter().any(|assignment| assignment.locked && assignment.start < end && assignment.end > start)
+    }
+    /// Whether the role shown for the block (see [`Employee::block_role`]) is locked
+    pub fn locked_at(&self, grid: &Grid, block: usize) -> bool {
+        let (start, end) = grid.block_span(block);
+        let role = self.block_role(grid, block);
+        self.assignments.iter().any(|assignment| assignment.locked && assignment.role == role && assignment.start < end && assignment.end > start)
+    }
     ///```assigned = vec![0,0, 1, 1, 2, 2, 1, 0]```
     /// 
     ///       0 = clocked out
@@ -563,73 +1057,39 @@ pub struct Employee {
     /// 
     ///     etc = role id
     /// 
-    #[serde(skip)]
-    pub assigned: Vec<usize> // Roles an employee is currently assigned to work
-} impl Employee {
-    pub fn new(id: usize, name: AttrValue, clock_in: NaiveTime, clock_out: NaiveTime) -> Employee {
-        Employee { id: id, name: name, roles: vec![2], scheduled: true, lunch: 2, clock_in, clock_out, assigned: vec![] }
-    }
-    pub fn new_blank(mut self, blocks: usize) -> Self {
-        self.assigned = vec![1;blocks];
-        self
-    }
-    pub fn clear_assigned(&mut self, open: &NaiveTime, close: &NaiveTime, block_size: TimeDelta) {
-        let mut assigned = vec![];
-        let mut curr_time = open.clone();
-        while curr_time < self.clock_in {
-            assigned.push(0);
-            curr_time += block_size;
-        }
-        while curr_time < self.clock_out {
-            assigned.push(1);
-            curr_time += block_size;
-        }
-        while curr_time.lt(close) {
-            assigned.push(0);
-            curr_time += block_size;
-        }
-        self.assigned = assigned;
+    /// The employee's schedule laid out on the grid, going by what they do for most of each block (see
+    /// [`Employee::block_role`])
+    pub fn assigned(&self, grid: &Grid) -> Vec<usize> {
+        (0..grid.blocks).map(|block| self.block_role(grid, block)).collect()
     }
 
-    /// Assigns role to employee at given indexes. Returns tuple of a vector of all sucessfully changed indexes and every role that was replaced
-    /// at each index. 
-    pub fn assign_block(&mut self, indexes: Vec<usize>, role: usize) -> Result<(Vec<usize>, Vec<(usize, usize)>)> {
+    /// Assigns role to employee at given indexes, replacing whatever role they had. Returns every index which changed.
+    pub fn assign_block(&mut self, grid: &Grid, indexes: Vec<usize>, role: usize) -> Result<Vec<usize>> {
         if !self.roles.contains(&role) {
-            return Err(BusinessError::EmployeeError(EmployeeError::NotAssignedRole { failed: role, allowed: self.roles.clone() }));
+            return Err(BusinessError::EmployeeError(EmployeeError::NotAssignedRole { employee: self.id, failed: role, allowed: self.roles.clone() }));
         }
         let mut successful_indexes = vec![];
-        let mut swapped_roles = vec![];
-        for index in indexes.iter() {
-            match self.assigned.get(*index) {
-                Some(x @ 3..) => {
-                    if role.ne(x) {
-                        successful_indexes.push(*index);
-                        swapped_roles.push((*x, *index));
-                        self.assigned[*index] = role;
-                    }
-                },
-                Some(1..=2) => {
-                    successful_indexes.push(*index);
-                    self.assigned[*index] = role
-                },
-                Some(0) | None => continue,
+        for index in indexes.iter().filter(|index| **index < grid.blocks) {
+            let (start, end) = grid.block_span(*index);
+            // Skip blocks where they aren't in at all, or already work the role for all of it
+            if self.segments(start, end).iter().all(|segment| segment.role == 0 || segment.role == role) {
+                continue;
             }
+            successful_indexes.push(*index);
+            self.set_span(role, start, end);
         }
-        return Ok((successful_indexes, swapped_roles))
+        Ok(successful_indexes)
     }
-    pub fn remove_block(&mut self, indexes: Vec<usize>) -> Vec<(usize, usize)> {
-        let mut cleared = vec![];
-        for index in indexes {
-            let curr = self.assigned[index];
-            if curr == 0 {
-                continue;
-            }
-            if curr > 1 { 
-                cleared.push((curr, index));
-            }
-            self.assigned[index] = 1;
+    /// Return the given indexes to available, leaving blocks where the employee is clocked out
+    pub fn remove_block(&mut self, grid: &Grid, indexes: Vec<usize>) {
+        for index in indexes.into_iter().filter(|index| *index < grid.blocks) {
+            let (start, end) = grid.block_span(index);
+            self.set_span(1, start, end);
         }
-        cleared
+    }
+    /// Return every block working the given role to available
+    pub fn release_role(&mut self, role: usize) {
+        self.assignments.retain(|assignment| assignment.role != role);
     }
 
     pub fn add_role(&mut self, role: usize) {
@@ -640,7 +1100,7 @@ pub struct Employee {
     pub fn remove_role(&mut self, role: usize) -> Result<()> {
         // let index_find = self.roles.iter().find(|&&x| x.eq(&role));
         // match index_find {
-        //     None => return Err(BusinessError::EmployeeError(EmployeeError::NotAssignedRole { failed: role, allowed: self.roles.clone() })),
+        //     None => return Err(BusinessError::EmployeeError(EmployeeError::NotAssignedRole { employee: self.id, failed: role, allowed: self.roles.clone() })),
         //     Some(index) => {
         //         let index = index.clone();
         //         self.roles.remove(index.try_into().unwrap());
@@ -656,71 +1116,54 @@ pub struct Employee {
             }
         }
         if failed {
-            return Err(BusinessError::EmployeeError(EmployeeError::NotAssignedRole { failed: role, allowed: self.roles.clone() }))
-        }
-        for i in 0..self.assigned.len() {
-            // let assigned_get = self.assigned.get(i);
-            if let Some(assigned) = self.assigned.get(i) {
-                if role.eq(assigned) {
-                    self.remove_block(vec![i]);
-                }
-            }
+            return Err(BusinessError::EmployeeError(EmployeeError::NotAssignedRole { employee: self.id, failed: role, allowed: self.roles.clone() }))
         }
+        self.release_role(role);
         Ok(())
     }
 
     /// Helper function to find the first block where the employee is clocked in, but not currently assigned a role
     /// 
     /// Returns None when no such block exists
-    pub fn first_open(&self) -> Option<usize> {
-        for i in 0..self.assigned.len() {
-            if let Some(role) = self.assigned.get(i) {
-                if 1.eq(role) {
-                    return Some(i);
-                }
-            }
-        }
-        None
+    pub fn first_open(&self, grid: &Grid) -> Option<usize> {
+        (0..grid.blocks).find(|block| self.block_role(grid, *block) == 1)
     }
 
-    pub fn assign_area(&mut self, role: &mut Role, time_index: usize, preferred_length: usize) {
-        for i in 0..preferred_length {
-            match self.assigned.get(time_index + i) {
-                None => return,
-                Some(curr_role) => {
-                    if 1.eq(curr_role) {
-                        self.assigned[time_index + i] = role.id();
-                        role.add_block(&self.id, vec![time_index+i]);
-                    } else {
-                        return;
-                    }
-                }
+    /// Assign the role from the time index for up to the preferred length, stopping at the first block which isn't
+    /// available. Returns the indexes assigned.
+    pub fn assign_area(&mut self, grid: &Grid, role: usize, time_index: usize, preferred_length: usize) -> Vec<usize> {
+        let mut assigned = vec![];
+        for index in (time_index..time_index + preferred_length).take_while(|index| *index < grid.blocks) {
+            if self.block_role(grid, index) != 1 {
+                break;
             }
+            let (start, end) = grid.block_span(index);
+            self.set_span(role, start, end);
+            assigned.push(index);
         }
+        assigned
     }
 
-    pub fn deschedule(&mut self, blocks: usize) {
+    pub fn deschedule(&mut self) {
         self.scheduled = false;
-        self.assigned = vec![0;blocks];
+        self.clear_assigned();
     }
 
     pub fn cmp(&self, other: &Employee, order: EmployeeSort) -> std::cmp::Ordering{
         self.scheduled.cmp(&other.scheduled).reverse() // true > false, but we want scheduled employees first
         .then(match order {
             EmployeeSort::Name => std::cmp::Ordering::Equal,
-            EmployeeSort::ClockIn => self.clock_in.cmp(&other.clock_in),
-            EmployeeSort::ClockOut => self.clock_out.cmp(&other.clock_out),
+            EmployeeSort::ClockIn => self.clock_in().cmp(&other.clock_in()),
+            EmployeeSort::ClockOut => self.clock_out().cmp(&other.clock_out()),
             EmployeeSort::Assigned { id } => {
-                for i in 0..self.assigned.len() {
-                    if self.assigned[i] != id && other.assigned[i] != id {
-                        continue;
-                    } else if self.assigned[i] == id {
-                        return std::cmp::Ordering::Less
-                    } else if other.assigned[i] == id {
-                        return std::cmp::Ordering::Greater
-                    }
+                // Whoever starts the role first, then anyone not working it at all
+                let first_start = |emp: &Employee| emp.assignments.iter().find(|assignment| assignment.role == id).map(|assignment| assignment.start);
+                match (first_start(self), first_start(other)) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => std::cmp::Ordering::Equal,
                 }
-                std::cmp::Ordering::Equal
             }
             EmployeeSort::Role { id } => self.roles.contains(&id).cmp(&other.roles.contains(&id)),
         }).then(self.name.cmp(&other.name))
@@ -729,8 +1172,11 @@ pub struct Employee {
 }
 
 
+/// The role every business has, for employees' lunch breaks
+pub const LUNCH_ROLE: usize = 2;
+
 fn business_base() -> Role {
-    Role::MultiRole(MultiRole::new(2, "Lunch".into(), 0))
+    Role::MultiRole(MultiRole::new(LUNCH_ROLE, "Lunch".into()))
 }
 
 impl Business {
@@ -741,9 +1187,9 @@ impl Business {
         let block_size = settings.app.block_size.clone();
         let role_vec = vec![
             business_base(),
-            SingleRole::new_blank(3, "Role 1".into(), "#00AAFF".into()).into(),
-            SingleRole::new_blank(4, "Role 2".into(), "#C70039".into()).into(),
-            SingleRole::new_blank(5, "Role 3".into(), "#11E000".into()).into()
+            SingleRole::new_with_color(3, "Role 1".into(), "#00AAFF".into()).into(),
+            SingleRole::new_with_color(4, "Role 2".into(), "#C70039".into()).into(),
+            SingleRole::new_with_color(5, "Role 3".into(), "#11E000".into()).into()
         ];
         let mut roles = HashMap::new();
         let mut role_colors = HashMap::new();
@@ -759,9 +1205,10 @@ impl Business {
                 roles: vec![2,3,4],
                 scheduled: true,
                 lunch: 2, 
-                clock_in: open.clone(),
-                clock_out: close.clone(),
-                assigned: vec![]
+                wage: 1500,
+                week_minutes: HashMap::new(),
+                shifts: vec![Shift { clock_in: open, clock_out: close }],
+                assignments: vec![]
             },
             Employee { 
                 id: 2, 
@@ -769,9 +1216,10 @@ impl Business {
                 roles: vec![2,3,4,5],
                 scheduled: true,
                 lunch: 1, 
-                clock_in: open.clone(),
-                clock_out: close.clone(),
-                assigned: vec![]
+                wage: 1650,
+                week_minutes: HashMap::new(),
+                shifts: vec![Shift { clock_in: open, clock_out: close }],
+                assignments: vec![]
             },
             Employee { 
                 id: 3, 
@@ -779,9 +1227,10 @@ impl Business {
                 roles: vec![2,3,4,5],
                 scheduled: true,
                 lunch: 2, 
-                clock_in: open.clone(),
-                clock_out: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
-                assigned: vec![]
+                wage: 1425,
+                week_minutes: HashMap::new(),
+                shifts: vec![Shift { clock_in: open, clock_out: NaiveTime::from_hms_opt(17, 0, 0).unwrap() }],
+                assignments: vec![]
             },
             Employee { 
                 id: 4, 
@@ -789,9 +1238,10 @@ impl Business {
                 roles: vec![2,3,4,5],
                 scheduled: true,
                 lunch: 2, 
-                clock_in: NaiveTime::from_hms_opt(11, 0, 0).unwrap(),
-                clock_out: close.clone(),
-                assigned: vec![]
+                wage: 1500,
+                week_minutes: HashMap::new(),
+                shifts: vec![Shift { clock_in: NaiveTime::from_hms_opt(11, 0, 0).unwrap(), clock_out: close }],
+                assignments: vec![]
             },
             Employee { 
                 id: 5, 
@@ -799,9 +1249,10 @@ impl Business {
                 roles: vec![2,3,4,5],
                 scheduled: false,
                 lunch: 2, 
-                clock_in: open.clone(),
-                clock_out: close.clone(),
-                assigned: vec![]
+                wage: 1800,
+                week_minutes: HashMap::new(),
+                shifts: vec![Shift { clock_in: open, clock_out: close }],
+                assignments: vec![]
             },
         ];
         let mut employees = HashMap::new();
@@ -816,7 +1267,10 @@ impl Business {
             employees,
             blocks: 0,
             block_size: block_size,
-            role_colors
+            role_colors,
+            notices: Notices::default(),
+            days_recorded: 0,
+            index_cache: IndexCache::default(),
         };
         business.update_business_hours(open, close, block_size);
         // business.schedule_lunch();
@@ -1,10 +1,12 @@
-use chrono::{NaiveTime, TimeDelta};
-use log::warn;
+use chrono::{Local, NaiveTime, TimeDelta};
+use serde::{Deserialize, Serialize};
 use yew::prelude::*;
 
-use crate::{data::{Business, RoleTrait}, persistence::write_business, scheduler::{blocks::HeldBlock, TimeBlock}, SettingsContext};
+use crate::{absence::Absence, audit::audit_entry, backup::Backup, bulk::{BlockPattern, GridSelection}, data::{Business, BusinessError, RoleTrait, RunEdge}, index::IndexCache, notifications::Level, persistence::{append_audit, read_editor, write_business}, scenarios::Scenario, scheduler::TimeBlock, settings::AppSettings, sync::SyncEvent, totals::parse_money, workspace::Workspace};
 
-#[derive(Clone, PartialEq)]
+/// Everything that can be done to the business. Actions are plain data so they can be sent to other editors, see
+/// [`crate::sync`].
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum BusinessEvents {
     NewRole { name: AttrValue },
     NewEmployee { name: AttrValue },
@@ -14,44 +16,191 @@ pub enum BusinessEvents {
     UpdateRoleSort { role_id: usize, increase_priority: bool },
     UpdateRoleColor { role_id: usize, color: String },
     ToggleRoleMulti { role_id: usize },
-    UpdateEmployeeHours { employee: usize, clock_in: String, clock_out: String },
+    /// New clock times for one of the employee's shifts
+    UpdateEmployeeHours { employee: usize, shift: usize, clock_in: String, clock_out: String },
+    AddEmployeeShift { employee: usize },
+    RemoveEmployeeShift { employee: usize, shift: usize },
     ToggleEmployeeScheduled { employee: usize },
+    /// Take the employee off the schedule, freeing their assignments to be covered by others
+    MarkAbsent { employee: usize },
+    /// Give the employee whatever they can take of the absence, as it was when they were chosen
+    ApplyReplacement { employee: usize, absence: Absence },
+    DismissAbsence,
     UpdateEmployeeLunch { emp_id: usize, blocks: usize },
+    /// Hourly pay as typed, e.g. "15.50"
+    UpdateEmployeeWage { emp_id: usize, wage: String },
     ToggleEmployeeRole { employee: usize, role: usize },
+    /// Add or update employees from a roster, see [`crate::roster`]
+    ImportRoster { roster: String, create_roles: bool },
     AssignBlock { employee: usize, role: usize, blocks: Vec<usize> },
+    AssignSpan { employee: usize, role: usize, start: NaiveTime, end: NaiveTime },
     RemoveBlock { employee: usize, blocks: Vec<usize> },
-    DragAssignBlock { target_block: TimeBlock, drag_block: TimeBlock, held_block: HeldBlock },
+    DragAssignBlock { target_block: TimeBlock, drag_block: TimeBlock, held_block: TimeBlock },
+    ResizeBlock { run: TimeBlock, edge: RunEdge, target_block: TimeBlock },
+    SwapBlocks { first: usize, second: usize, blocks: Vec<usize> },
+    /// Lock or unlock the employee's run through the block against automations
+    ToggleLock { employee: usize, block: usize },
+    BulkAssign { selection: GridSelection, role: usize },
+    BulkClear { selection: GridSelection },
+    PasteBlocks { employees: Vec<usize>, start: usize, pattern: BlockPattern },
+    ShiftBlocks { selection: GridSelection, offset: isize },
 
-    ScheduleLunch,
-    ScheduleRoles { settings: SettingsContext },
+    ScheduleLunch { settings: AppSettings },
+    ScheduleRoles { settings: AppSettings },
+    /// Add today's time on each role to the week, for balancing roles across days
+    RecordDay,
+    ClearWeek,
     LoadSchedule { schedule: String },
+    /// Look for and fix assignments which overlap or fall outside an employee's hours, refer to deleted or disallowed
+    /// roles, or put more employees on a role than it allows
+    RepairSchedule,
 
-    InitFromHash { new_business: Business }
+    /// Copy whatever is being edited into a new scenario and start editing it
+    NewScenario { name: AttrValue },
+    /// Edit the scenario, or the plan if None
+    OpenScenario { scenario: Option<usize> },
+    PromoteScenario { scenario: usize },
+    DeleteScenario { scenario: usize },
+
+    InitFromHash { new_business: Box<Business> },
+    /// Replace the plan and scenarios with a backup's, see [`crate::backup`]
+    Restore { backup: Box<Backup> },
+    /// Replace the business, its hours and its schedule (from [`crate::persistence::schedule_to_csv`]) with another
+    /// editor's
+    Snapshot { business: Box<Business>, open: NaiveTime, close: NaiveTime, block_size: TimeDelta, schedule: String },
+    /// News from the sync relay
+    #[serde(skip)]
+    Sync(SyncEvent),
 }
 
-impl Reducible for crate::data::Business {
-    type Action = BusinessEvents;
+/// Blocks as a time or stretch of time, e.g. "9:00" or "9:00 to 10:30"
+fn describe_blocks(business: &Business, blocks: &[usize]) -> String {
+    let first = blocks.iter().min().copied().unwrap_or(0);
+    let last = blocks.iter().max().copied().unwrap_or(0);
+    match first == last {
+        true => business.block_time(first).format("%-I:%M").to_string(),
+        false => format!("{} to {}", business.block_time(first).format("%-I:%M"), business.block_time(last + 1).format("%-I:%M")),
+    }
+}
 
-    fn reduce(self: std::rc::Rc<Self>, action: Self::Action) -> std::rc::Rc<Self> {
-        let mut business = std::rc::Rc::unwrap_or_clone(self);
+fn describe_selection(business: &Business, selection: &GridSelection) -> String {
+    let names: Vec<String> = selection.employees.iter().map(|id| business.employee_name(*id)).collect();
+    format!("{} at {}", names.join(", "), describe_blocks(business, &selection.blocks))
+}
+
+impl BusinessEvents {
+    /// What the action does, for the change log. Names are taken from the business and scenarios before the action.
+    pub fn describe(&self, business: &Business, scenarios: &[Scenario]) -> String {
+        let emp = |id: &usize| business.employee_name(*id);
+        let role = |id: &usize| business.role_name(*id);
+        let scenario = |index: &usize| match scenarios.get(*index) {
+            Some(scenario) => format!("\"{}\"", scenario.name),
+            None => format!("#{}", index),
+        };
+        match self {
+            BusinessEvents::NewRole { name } => format!("Added role {}", name),
+            BusinessEvents::NewEmployee { name } => format!("Added employee {}", name),
+            BusinessEvents::DeleteRole { role: id } => format!("Deleted role {}", role(id)),
+            BusinessEvents::DeleteEmployee { emp: id } => format!("Deleted employee {}", emp(id)),
+            BusinessEvents::UpdateBusinessHours { open, close, block_size } => format!("Set business hours to {} to {} in {} minute blocks",
+                open.format("%-I:%M"), close.format("%-I:%M"), block_size.num_minutes()),
+            BusinessEvents::UpdateRoleSort { role_id, increase_priority } => format!("Moved {} {} in the role order", role(role_id),
+                if *increase_priority {"up"} else {"down"}),
+            BusinessEvents::UpdateRoleColor { role_id, color } => format!("Colored {} {}", role(role_id), color),
+            BusinessEvents::ToggleRoleMulti { role_id } => format!("Changed whether more than one person can work {}", role(role_id)),
+            BusinessEvents::UpdateEmployeeHours { employee, shift, clock_in, clock_out } => format!("Set {}'s shift {} to {} to {}",
+                emp(employee), shift + 1, clock_in, clock_out),
+            BusinessEvents::AddEmployeeShift { employee } => format!("Added a shift for {}", emp(employee)),
+            BusinessEvents::RemoveEmployeeShift { employee, shift } => format!("Removed {}'s shift {}", emp(employee), shift + 1),
+            BusinessEvents::ToggleEmployeeScheduled { employee } => match business.employees.get(employee).is_some_and(|emp| emp.scheduled) {
+                true => format!("Took {} off the schedule", emp(employee)),
+                false => format!("Put {} on the schedule", emp(employee)),
+            },
+            BusinessEvents::MarkAbsent { employee } => format!("Marked {} absent", emp(employee)),
+            BusinessEvents::ApplyReplacement { employee, absence } => format!("Had {} cover for {}", emp(employee), emp(&absence.employee)),
+            BusinessEvents::DismissAbsence => "Closed the call-out assistant".into(),
+            BusinessEvents::UpdateEmployeeLunch { emp_id, blocks } => format!("Set {}'s lunch to {} block(s)", emp(emp_id), blocks),
+            BusinessEvents::UpdateEmployeeWage { emp_id, wage } => format!("Set {}'s wage to {}", emp(emp_id), wage),
+            BusinessEvents::ToggleEmployeeRole { employee, role: id } => match business.employees.get(employee).is_some_and(|emp| emp.roles.contains(id)) {
+                true => format!("Stopped {} working {}", emp(employee), role(id)),
+                false => format!("Let {} work {}", emp(employee), role(id)),
+            },
+            BusinessEvents::ImportRoster { .. } => "Imported a roster".into(),
+            BusinessEvents::AssignBlock { employee, role: id, blocks } => format!("Assigned {} to {} at {}", emp(employee), role(id),
+                describe_blocks(business, blocks)),
+            BusinessEvents::AssignSpan { employee, role: id, start, end } => format!("Assigned {} to {} at {} to {}", emp(employee), role(id),
+                start.format("%-I:%M"), end.format("%-I:%M")),
+            BusinessEvents::RemoveBlock { employee, blocks } => format!("Cleared {} at {}", emp(employee), describe_blocks(business, blocks)),
+            BusinessEvents::DragAssignBlock { target_block, drag_block, .. } => format!("Moved {}'s {} at {} to {} at {}",
+                emp(&drag_block.emp_id), role(&drag_block.role), business.block_time(drag_block.time_index).format("%-I:%M"),
+                emp(&target_block.emp_id), business.block_time(target_block.time_index).format("%-I:%M")),
+            BusinessEvents::ResizeBlock { run, target_block, .. } => format!("Resized {}'s {} at {} to reach {}", emp(&run.emp_id), role(&run.role),
+                business.block_time(run.time_index).format("%-I:%M"), business.block_time(target_block.time_index).format("%-I:%M")),
+            BusinessEvents::SwapBlocks { first, second, blocks } => format!("Swapped {} and {} at {}", emp(first), emp(second),
+                describe_blocks(business, blocks)),
+            BusinessEvents::ToggleLock { employee, block } => format!("Locked or unlocked {} at {}", emp(employee),
+                business.block_time(*block).format("%-I:%M")),
+            BusinessEvents::BulkAssign { selection, role: id } => format!("Assigned {} to {}", role(id), describe_selection(business, selection)),
+            BusinessEvents::BulkClear { selection } => format!("Cleared {}", describe_selection(business, selection)),
+            BusinessEvents::PasteBlocks { employees, start, .. } => {
+                let names: Vec<String> = employees.iter().map(emp).collect();
+                format!("Pasted blocks onto {} from {}", names.join(", "), business.block_time(*start).format("%-I:%M"))
+            },
+            BusinessEvents::ShiftBlocks { selection, offset } => format!("Moved {} by {} block(s)", describe_selection(business, selection), offset),
+            BusinessEvents::ScheduleLunch { .. } => "Guessed lunches".into(),
+            BusinessEvents::ScheduleRoles { .. } => "Filled in roles".into(),
+            BusinessEvents::RecordDay => "Added today to the week".into(),
+            BusinessEvents::ClearWeek => "Started a new week".into(),
+            BusinessEvents::LoadSchedule { .. } => "Loaded a schedule".into(),
+            BusinessEvents::RepairSchedule => "Checked the schedule".into(),
+            BusinessEvents::NewScenario { name } => format!("Started scenario \"{}\"", name),
+            BusinessEvents::OpenScenario { scenario: Some(index) } => format!("Opened scenario {}", scenario(index)),
+            BusinessEvents::OpenScenario { scenario: None } => "Went back to the plan".into(),
+            BusinessEvents::PromoteScenario { scenario: index } => format!("Made scenario {} the plan", scenario(index)),
+            BusinessEvents::DeleteScenario { scenario: index } => format!("Deleted scenario {}", scenario(index)),
+            BusinessEvents::InitFromHash { .. } => "Opened the saved business".into(),
+            BusinessEvents::Restore { .. } => "Restored a backup".into(),
+            BusinessEvents::Snapshot { .. } => "Shared their whole business".into(),
+            BusinessEvents::Sync(_) => "Heard from the sync server".into(),
+        }
+    }
+
+    /// Whether other editors are sent the action while syncing. Scenarios, the call-out assistant and the page loading
+    /// are this editor's own. Whole new plans, from promoting a scenario or restoring a backup, are sent as a snapshot
+    /// instead.
+    pub fn is_shared(&self) -> bool {
+        !matches!(self, BusinessEvents::NewScenario { .. } | BusinessEvents::OpenScenario { .. } | BusinessEvents::PromoteScenario { .. }
+            | BusinessEvents::DeleteScenario { .. } | BusinessEvents::DismissAbsence | BusinessEvents::InitFromHash { .. }
+            | BusinessEvents::Restore { .. } | BusinessEvents::Sync(_))
+    }
+}
+
+impl Business {
+    /// Carry out the action on the business alone. Returns whether the business saved in the page hash needs writing
+    /// again. Anything the editor keeps beside the business, such as scenarios, is left to [`Workspace::apply`].
+    pub fn apply(&mut self, action: BusinessEvents) -> bool {
         let mut update_fragment = true;
         match action {
-            BusinessEvents::NewRole { name } => business.new_role(name),
-            BusinessEvents::NewEmployee { name } => business.new_employee(name),
-            BusinessEvents::DeleteRole { role } => business.delete_role(role),
-            BusinessEvents::DeleteEmployee { emp } => business.delete_employee(emp),
+            BusinessEvents::NewRole { name } => self.new_role(name),
+            BusinessEvents::NewEmployee { name } => {
+                self.new_employee(name);
+            },
+            BusinessEvents::DeleteRole { role } => self.delete_role(role),
+            BusinessEvents::DeleteEmployee { emp } => self.delete_employee(emp),
             BusinessEvents::UpdateBusinessHours { open, close, block_size } => {
-                business.update_business_hours(open, close, block_size);
+                self.update_business_hours(open, close, block_size);
                 update_fragment = false;
             },
             BusinessEvents::UpdateRoleSort { role_id, increase_priority } => {
-                // let curr_role = match business.roles.get_mut(&role_id) {
-                //     Some(role) => role,
-                //     None => return business.into()
-                // };
-                let curr_sort = business.roles[&role_id].sort();
+                let curr_sort = match self.roles.get(&role_id) {
+                    Some(role) => role.sort(),
+                    None => {
+                        self.report(BusinessError::RoleNotFound);
+                        return false;
+                    }
+                };
                 let mut best_swap_role: Option<&mut crate::data::Role> = None;
-                for role in business.roles.values_mut() {
+                for role in self.roles.values_mut() {
                     if increase_priority {
                         if let Some(curr_best) = best_swap_role {
                             if role.id() > curr_best.id() && curr_sort > role.sort() {
@@ -77,115 +226,314 @@ impl Reducible for crate::data::Business {
                 if let Some(op_role) = best_swap_role {
                     let new_sort = op_role.sort();
                     op_role.sort_set(curr_sort);
-                    business.roles.get_mut(&role_id).unwrap().sort_set(new_sort);
+                    if let Some(role) = self.roles.get_mut(&role_id) {
+                        role.sort_set(new_sort);
+                    }
+                }
+            },
+            BusinessEvents::UpdateRoleColor { role_id, color } => self.update_role_color(role_id, color.into()),
+            BusinessEvents::ToggleRoleMulti { role_id } => self.toggle_role_multi(role_id),
+            BusinessEvents::UpdateEmployeeHours { employee, shift, clock_in, clock_out } => {
+                let parsed = clock_in.parse().map_err(|_| clock_in.clone())
+                    .and_then(|clock_in| Ok((clock_in, clock_out.parse().map_err(|_| clock_out.clone())?)));
+                let result = match parsed {
+                    Ok((clock_in, clock_out)) => self.update_employee_shift(employee, shift, clock_in, clock_out),
+                    Err(input) => Err(BusinessError::InvalidInput { field: "clock time", input }),
+                };
+                if let Err(e) = result {
+                    self.report(e);
                 }
+                update_fragment = false;
             },
-            BusinessEvents::UpdateRoleColor { role_id, color } => business.update_role_color(role_id, color.into()),
-            BusinessEvents::ToggleRoleMulti { role_id } => business.toggle_role_multi(role_id),
-            BusinessEvents::UpdateEmployeeHours { employee, clock_in, clock_out } => {
-                business.update_employee_hours(employee, clock_in.parse().unwrap(), clock_out.parse().unwrap());
+            BusinessEvents::AddEmployeeShift { employee } => {
+                if let Err(e) = self.add_employee_shift(employee) {
+                    self.report(e);
+                }
+                update_fragment = false;
+            },
+            BusinessEvents::RemoveEmployeeShift { employee, shift } => {
+                if let Err(e) = self.remove_employee_shift(employee, shift) {
+                    self.report(e);
+                }
                 update_fragment = false;
             },
             BusinessEvents::ToggleEmployeeScheduled { employee } => {
-                business.toggle_employee_scheduled(employee);
+                if let Err(e) = self.toggle_employee_scheduled(employee) {
+                    self.report(e);
+                }
+                update_fragment = false;
+            },
+            BusinessEvents::MarkAbsent { employee } => {
+                if let Err(e) = self.mark_absent(employee) {
+                    self.report(e);
+                }
+                update_fragment = false;
+            },
+            BusinessEvents::ApplyReplacement { employee, mut absence } => {
+                self.cover_absence(employee, &mut absence);
                 update_fragment = false;
             },
             BusinessEvents::UpdateEmployeeLunch { emp_id, blocks } => {
-                match business.employees.get_mut(&emp_id) {
-                    Some(emp) => {emp.lunch = blocks},
-                    None => {},
+                if let Some(emp) = self.employees.get_mut(&emp_id) {
+                    emp.lunch = blocks;
                 }
             }
+            BusinessEvents::UpdateEmployeeWage { emp_id, wage } => {
+                let result = match parse_money(&wage) {
+                    Some(cents) => self.update_employee_wage(emp_id, cents),
+                    None => Err(BusinessError::InvalidInput { field: "wage", input: wage }),
+                };
+                if let Err(e) = result {
+                    self.report(e);
+                    update_fragment = false;
+                }
+            },
             BusinessEvents::ToggleEmployeeRole { employee, role } => {
-                let emp_get = business.employees.get(&employee);
+                let emp_get = self.employees.get(&employee);
                 if let Some(emp) = emp_get {
                     if emp.roles.contains(&role) {
-                        match business.restrict_role(employee, role) {
-                            Ok(_) => (),
-                            Err(e) => warn!("{:#?}", e)
+                        if let Err(e) = self.restrict_role(employee, role) {
+                            self.report(e);
                         }
-                    } else {
-                        business.assign_role(employee, role);
+                    } else if let Err(e) = self.assign_role(employee, role) {
+                        self.report(e);
                     }
                 }
             },
+            BusinessEvents::ImportRoster { roster, create_roles } => {
+                let preview = self.import_roster(&roster, create_roles);
+                for error in preview.errors.iter() {
+                    self.notices.push(Level::Error, error.clone());
+                }
+                let (added, updated) = preview.counts();
+                self.notices.push(Level::Info, format!("Added {} and updated {} employee(s)", added, updated));
+                let skipped = preview.rows.len() - added - updated;
+                if skipped > 0 {
+                    self.notices.push(Level::Warning, format!("Skipped {} row(s) with problems", skipped));
+                }
+            },
             BusinessEvents::AssignBlock { employee, role, blocks } => {
-                match business.assign_block(employee, role, blocks) {
-                    Ok(_) => (),
-                    Err(e) => warn!("{:#?}", e)
+                if let Err(e) = self.assign_block(employee, role, blocks) {
+                    self.report(e);
+                }
+                update_fragment = false;
+            },
+            BusinessEvents::AssignSpan { employee, role, start, end } => {
+                if let Err(e) = self.assign_span(employee, role, start, end) {
+                    self.report(e);
                 }
                 update_fragment = false;
             },
             BusinessEvents::RemoveBlock { employee, blocks } => {
-                match business.remove_block(employee, blocks) {
-                    _ => ()
+                if let Err(e) = self.remove_block(employee, blocks) {
+                    self.report(e);
                 }
                 update_fragment = false;
             },
 
-            BusinessEvents::DragAssignBlock { target_block, drag_block , held_block} => {
-                if target_block.role != drag_block.role || target_block.time_index != held_block.time_index {
-                    let mut target_block_time_indexes;
-                    let mut drag_block_time_indexes;
-                    if drag_block.len <= 1 {
-                        target_block_time_indexes = vec![target_block.time_index];
-                        drag_block_time_indexes = vec![drag_block.time_index]
-                    } else {
-                        target_block_time_indexes = vec![];
-                        drag_block_time_indexes = vec![];
-                        for i in 0..held_block.len {
-                            if i <= held_block.len_index {
-                                if target_block.emp_id != 0 {
-                                    target_block_time_indexes.push(target_block.time_index - i);
-                                }
-                                drag_block_time_indexes.push(held_block.time_index - i);
-                            } else {
-                                if target_block.emp_id != 0 {
-                                    target_block_time_indexes.push(target_block.time_index + (i - held_block.len_index));
-                                }
-                                drag_block_time_indexes.push(held_block.time_index + (i - held_block.len_index));
-                            }
-                        }
-                    }
-                    match business.assign_block(target_block.emp_id, drag_block.role, target_block_time_indexes.clone()) {
-                        Err(e) => {
-                            if target_block.emp_id == 0 {
-                                let _ = business.remove_block(drag_block.emp_id, drag_block_time_indexes);
-                            } else {
-                                warn!("Could not assign drag block {:#?}", e);
-                            }
-                        },
-                        Ok(_) => {
-                            if drag_block.emp_id != 0 {
-                                if drag_block.emp_id == target_block.emp_id {
-                                    let mut old_time_indexes = vec![];
-                                    for index in drag_block_time_indexes {
-                                        if !target_block_time_indexes.contains(&index) {
-                                            old_time_indexes.push(index);
-                                        }
-                                    }
-                                    let _ = business.remove_block(drag_block.emp_id, old_time_indexes);
-                                } else {
-                                    let _ = business.remove_block(drag_block.emp_id, drag_block_time_indexes);
-                                }
-                            }
-                        },
-                    }
+            BusinessEvents::DragAssignBlock { target_block, drag_block, held_block } => {
+                if let Err(e) = self.drag_assign_block(&target_block, &drag_block, &held_block) {
+                    self.report(e);
                 }
                 update_fragment = false;
             }
-            BusinessEvents::ScheduleLunch => {business.schedule_lunch(); update_fragment = false;},
-            BusinessEvents::ScheduleRoles {settings} => {business.schedule_roles(settings); update_fragment = false;},
-            BusinessEvents::LoadSchedule { schedule } => {business.load_schedule(schedule); update_fragment = false;},
+            BusinessEvents::ResizeBlock { run, edge, target_block } => {
+                // Runs can only be resized along their own row
+                if run.emp_id != 0 && run.emp_id == target_block.emp_id {
+                    if let Err(e) = self.resize_run(run.emp_id, run.role, run.time_index, run.len, edge, target_block.time_index) {
+                        self.report(e);
+                    }
+                }
+                update_fragment = false;
+            },
+            BusinessEvents::SwapBlocks { first, second, blocks } => {
+                if let Err(e) = self.swap_blocks(first, second, blocks) {
+                    self.report(e);
+                }
+                update_fragment = false;
+            },
+            BusinessEvents::ToggleLock { employee, block } => {
+                if let Err(e) = self.toggle_lock(employee, block) {
+                    self.report(e);
+                }
+                update_fragment = false;
+            },
+            BusinessEvents::BulkAssign { selection, role } => {
+                if let Err(e) = self.bulk_assign(&selection, role) {
+                    self.report(e);
+                }
+                update_fragment = false;
+            },
+            BusinessEvents::BulkClear { selection } => {
+                if let Err(e) = self.bulk_clear(&selection) {
+                    self.report(e);
+                }
+                update_fragment = false;
+            },
+            BusinessEvents::PasteBlocks { employees, start, pattern } => {
+                if let Err(e) = self.paste_blocks(&employees, start, &pattern) {
+                    self.report(e);
+                }
+                update_fragment = false;
+            },
+            BusinessEvents::ShiftBlocks { selection, offset } => {
+                if let Err(e) = self.shift_blocks(&selection, offset) {
+                    self.report(e);
+                }
+                update_fragment = false;
+            },
+            BusinessEvents::ScheduleLunch {settings} => {self.schedule_lunch(&settings); update_fragment = false;},
+            BusinessEvents::ScheduleRoles {settings} => {self.schedule_roles(&settings); update_fragment = false;},
+            BusinessEvents::RecordDay => {
+                self.record_day();
+                self.notices.push(Level::Info, format!("Added today to the week; {} day(s) so far", self.days_recorded));
+            },
+            BusinessEvents::ClearWeek => self.clear_week(),
+            BusinessEvents::LoadSchedule { schedule } => {
+                for (level, problem) in self.load_schedule(schedule) {
+                    self.notices.push(level, problem);
+                }
+                self.repair_and_report(true);
+                update_fragment = false;
+            },
+            BusinessEvents::RepairSchedule => update_fragment = self.repair_and_report(false),
 
             BusinessEvents::InitFromHash { new_business } => {
-                business = new_business;
-                update_fragment = false;
+                *self = *new_business;
+                // Saved roles and employees which needed fixing are saved again
+                update_fragment = self.repair_and_report(true);
+            },
+            BusinessEvents::Snapshot { business, open, close, block_size, schedule } => {
+                let mut new_business = *business;
+                new_business.init(open, close, block_size);
+                for (level, problem) in new_business.load_schedule(schedule) {
+                    self.notices.push(level, problem);
+                }
+                self.replace_document(new_business);
+            },
+            BusinessEvents::NewScenario { .. } | BusinessEvents::OpenScenario { .. } | BusinessEvents::PromoteScenario { .. }
+                | BusinessEvents::DeleteScenario { .. } | BusinessEvents::DismissAbsence | BusinessEvents::Restore { .. }
+                | BusinessEvents::Sync(_) => update_fragment = false,
+        }
+        update_fragment
+    }
+}
+
+impl Workspace {
+    /// Carry out the action, keeping whatever it leaves for this editor alone beside the business. Returns whether the
+    /// business saved in the page hash needs writing again.
+    pub fn apply(&mut self, action: BusinessEvents) -> bool {
+        match action {
+            BusinessEvents::MarkAbsent { employee } => {
+                match self.business.mark_absent(employee) {
+                    Ok(absence) => self.absence = Some(absence),
+                    Err(e) => self.report(e),
+                }
+                false
+            },
+            BusinessEvents::ApplyReplacement { employee, mut absence } => {
+                self.business.cover_absence(employee, &mut absence);
+                self.absence = Some(absence);
+                false
+            },
+            BusinessEvents::DismissAbsence => {
+                self.absence = None;
+                false
+            },
+            BusinessEvents::ScheduleRoles { settings } => {
+                self.role_trace = self.business.schedule_roles(&settings);
+                false
+            },
+
+            // Only the plan is saved, so only promoting a scenario changes what is
+            BusinessEvents::NewScenario { name } => {
+                self.new_scenario(name);
+                false
+            },
+            BusinessEvents::OpenScenario { scenario } => {
+                if let Err(e) = self.open_scenario(scenario) {
+                    self.report(e);
+                }
+                false
+            },
+            BusinessEvents::PromoteScenario { scenario } => match self.promote_scenario(scenario) {
+                Ok(_) => true,
+                Err(e) => {
+                    self.report(e);
+                    false
+                },
+            },
+            BusinessEvents::DeleteScenario { scenario } => {
+                if let Err(e) = self.delete_scenario(scenario) {
+                    self.report(e);
+                }
+                false
+            },
+
+            BusinessEvents::Restore { backup } => {
+                self.restore(*backup);
+                true
+            },
+            BusinessEvents::Sync(event) => {
+                self.receive_sync(event);
+                false
+            },
+            action => self.business.apply(action),
+        }
+    }
+}
+
+impl Reducible for Workspace {
+    type Action = BusinessEvents;
+
+    fn reduce(self: std::rc::Rc<Self>, action: Self::Action) -> std::rc::Rc<Self> {
+        let mut workspace = std::rc::Rc::unwrap_or_clone(self);
+        if let BusinessEvents::Sync(event) = action {
+            // Presence and this editor's own actions coming back change nothing here, so leave the role trace and
+            // anything still to be told alone
+            let (earlier, generation) = (std::mem::take(&mut workspace.notices.items), workspace.notices.generation);
+            let remote = workspace.receive_sync(event);
+            if workspace.notices.generation == generation {
+                workspace.notices.items = earlier;
+            }
+            // Another editor's action changes the plan as though it were done here
+            if let Some(remote) = remote {
+                workspace.role_trace.clear();
+                if remote.hours_changed {
+                    workspace.remote_hours += 1;
+                }
+                append_audit(audit_entry(Some(&remote.before), workspace.plan(), None, remote.action, remote.editor, Local::now().naive_local()));
+                write_business(workspace.plan());
             }
+            return workspace.into();
+        }
+        workspace.notices.items.clear();
+        workspace.index_cache = IndexCache::default();
+        // Anything done after filling in roles may make its trace wrong
+        workspace.role_trace.clear();
+        // Opening the saved business when the page loads isn't an edit
+        let audit = match action {
+            BusinessEvents::InitFromHash { .. } => None,
+            // Only the employees are kept, as they are all the entry compares
+            _ => Some((workspace.employees.clone(), workspace.editing, action.describe(&workspace, &workspace.scenarios))),
+        };
+        let shared = workspace.sync.as_ref().map(|_| (action.clone(), workspace.editing));
+        let update_fragment = workspace.apply(action);
+        let entry = audit.map(|(before, editing, description)| {
+            // Switching between the plan and a scenario changes neither
+            let before = (editing == workspace.editing).then_some(&before);
+            let scenario = workspace.editing.map(|index| workspace.scenarios[index].name.to_string());
+            audit_entry(before, &workspace, scenario, description, read_editor(), Local::now().naive_local())
+        });
+        if let Some((action, editing)) = shared {
+            workspace.share(action, editing, entry.as_ref());
+        }
+        if let Some(entry) = entry {
+            append_audit(entry);
         }
         if update_fragment {
-            write_business(&business);
+            write_business(workspace.plan());
         }
-        return business.into()
+        workspace.into()
     }
-}
\ No newline at end of file
+}
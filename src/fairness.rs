@@ -0,0 +1,80 @@
+use crate::data::{Business, Employee, Role, RoleTrait};
+
+/// How evenly the single roles are shared among the scheduled employees, see [`Business::fairness`]
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Fairness {
+    /// Single roles, in role order
+    pub roles: Vec<usize>,
+    /// Every scheduled employee, by id
+    pub employees: Vec<EmployeeFairness>,
+} impl Fairness {
+    /// Difference in minutes between whoever has the most and the least time on the role, among those who can work it
+    pub fn spread(&self, role: usize) -> i64 {
+        let position = match self.roles.iter().position(|id| *id == role) {
+            Some(position) => position,
+            None => return 0,
+        };
+        let minutes: Vec<i64> = self.employees.iter().filter_map(|emp| emp.minutes[position]).collect();
+        minutes.iter().max().unwrap_or(&0) - minutes.iter().min().unwrap_or(&0)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct EmployeeFairness {
+    pub employee: usize,
+    /// Minutes on each of [`Fairness::roles`], or None for roles they can't work
+    pub minutes: Vec<Option<i64>>,
+    /// See [`Employee::role_switches`]
+    pub switches: usize,
+}
+
+impl Employee {
+    /// Minutes worked on the role today, plus earlier in the week if asked for
+    pub fn role_minutes(&self, role: usize, week: bool) -> i64 {
+        let today: i64 = self.assignments.iter()
+            .filter(|assignment| assignment.role == role)
+            .map(|assignment| (assignment.end - assignment.start).num_minutes())
+            .sum();
+        match week {
+            true => today + self.week_minutes.get(&role).copied().unwrap_or(0),
+            false => today,
+        }
+    }
+    /// How many times the employee moves from one role to a different one during the day. Lunch, and free time
+    /// between two stretches of the same role, don't count.
+    pub fn role_switches(&self) -> usize {
+        let worked: Vec<usize> = self.assignments.iter().map(|assignment| assignment.role).filter(|role| *role > 2).collect();
+        worked.windows(2).filter(|pair| pair[0] != pair[1]).count()
+    }
+}
+
+impl Business {
+    /// Time on each single role for every scheduled employee, for today or the week so far including today
+    pub fn fairness(&self, week: bool) -> Fairness {
+        let mut roles: Vec<&Role> = self.roles.values().filter(|role| !role.is_multi()).collect();
+        roles.sort();
+        let roles: Vec<usize> = roles.into_iter().map(|role| role.id()).collect();
+        let mut employees: Vec<&Employee> = self.employees.values().filter(|emp| emp.scheduled).collect();
+        employees.sort_by_key(|emp| emp.id);
+        let employees = employees.into_iter().map(|emp| EmployeeFairness {
+            employee: emp.id,
+            minutes: roles.iter().map(|role| emp.roles.contains(role).then(|| emp.role_minutes(*role, week))).collect(),
+            switches: emp.role_switches(),
+        }).collect();
+        Fairness { roles, employees }
+    }
+    /// Add today's time on each role to every scheduled employee's week, for balancing across days
+    pub fn record_day(&mut self) {
+        for emp in self.employees.values_mut().filter(|emp| emp.scheduled) {
+            for assignment in emp.assignments.iter().filter(|assignment| assignment.role > 2) {
+                *emp.week_minutes.entry(assignment.role).or_default() += (assignment.end - assignment.start).num_minutes();
+            }
+        }
+        self.days_recorded += 1;
+    }
+    /// Forget the time recorded by [`Business::record_day`]
+    pub fn clear_week(&mut self) {
+        self.employees.values_mut().for_each(|emp| emp.week_minutes.clear());
+        self.days_recorded = 0;
+    }
+}
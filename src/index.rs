@@ -0,0 +1,91 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::data::{Business, Role, RoleAssigned, RoleTrait};
+
+/// The employees' schedules laid out on the business's block grid, and who is working each role at each block
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ScheduleIndex {
+    /// Each employee's schedule by block; see [`crate::data::Employee::assigned`]
+    by_employee: HashMap<usize, Vec<usize>>,
+    /// Employee ids, in ascending order, for each block of each role
+    by_role: HashMap<usize, Vec<Vec<usize>>>,
+    blocks: usize,
+} impl ScheduleIndex {
+    pub fn new(business: &Business) -> Self {
+        let grid = business.grid();
+        let mut by_employee = HashMap::new();
+        let mut by_role: HashMap<usize, Vec<Vec<usize>>> = HashMap::new();
+        let mut emp_ids: Vec<&usize> = business.employees.keys().collect();
+        emp_ids.sort();
+        for emp_id in emp_ids {
+            let assigned = business.employees[emp_id].assigned(&grid);
+            for (block, role) in assigned.iter().enumerate() {
+                if *role < 2 {
+                    continue;
+                }
+                let blocks = by_role.entry(*role).or_insert_with(|| vec![vec![]; business.blocks]);
+                if let Some(working) = blocks.get_mut(block) {
+                    working.push(*emp_id);
+                }
+            }
+            by_employee.insert(*emp_id, assigned);
+        }
+        ScheduleIndex { by_employee, by_role, blocks: business.blocks }
+    }
+
+    /// An employee's schedule by block, or nothing if they don't exist
+    pub fn assigned(&self, employee: usize) -> &[usize] {
+        match self.by_employee.get(&employee) {
+            Some(assigned) => assigned,
+            None => &[],
+        }
+    }
+
+    /// Employees working the role at the block
+    pub fn working(&self, role: usize, block: usize) -> &[usize] {
+        match self.by_role.get(&role).and_then(|blocks| blocks.get(block)) {
+            Some(working) => working,
+            None => &[],
+        }
+    }
+
+    /// The role's schedule in the shape of its capacity: a single employee id (0 for nobody) per block for single
+    /// roles, or every employee per block for multi roles
+    pub fn role_assigned(&self, role: &Role) -> RoleAssigned {
+        let blocks = (0..self.blocks).map(|block| self.working(role.id(), block));
+        match role.is_multi() {
+            true => RoleAssigned::MultiAssigned(blocks.map(|working| working.to_vec()).collect()),
+            false => RoleAssigned::SingleAssinged(blocks.map(|working| working.first().copied().unwrap_or(0)).collect()),
+        }
+    }
+}
+
+/// A [`ScheduleIndex`] built on first use. The reducer starts every action with an empty cache, so each business state
+/// builds its index at most once however many components read it. It is not part of the business's state and always
+/// compares equal.
+#[derive(Clone, Default)]
+pub struct IndexCache(RefCell<Option<Rc<ScheduleIndex>>>);
+
+impl PartialEq for IndexCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+impl std::fmt::Debug for IndexCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IndexCache")
+    }
+}
+
+impl Business {
+    /// The index of the current schedule, for displaying it. Methods that change the schedule read employees directly
+    /// instead, as the cache is only cleared between reducer actions.
+    pub fn index(&self) -> Rc<ScheduleIndex> {
+        if let Some(index) = self.index_cache.0.borrow().as_ref() {
+            return index.clone();
+        }
+        let index = Rc::new(ScheduleIndex::new(self));
+        *self.index_cache.0.borrow_mut() = Some(index.clone());
+        index
+    }
+}
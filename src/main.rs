@@ -5,27 +5,57 @@ use yew::prelude::*;
 use print::PrintTable;
 use business_tab::BusinessTab;
 
-use crate::{events::BusinessEvents, persistence::{read_settings, write_settings}, scheduler::{Controls, ScheduleCopy, Table}, settings::Settings};
+use crate::{events::BusinessEvents, notifications::{Level, NotificationContext, NotificationEvents, Notifications, Toasts}, persistence::{read_settings, read_shared_schedule, read_view_mode, write_settings}, scheduler::{scenarios::follow_hours, AbsenceAssistant, Controls, FairnessSummary, Scenarios, ScheduleCopy, ScheduleTotals, ShareLink, SyncPanel, Table}, settings::Settings, workspace::Workspace};
 
+mod absence;
+mod audit;
+mod audit_tab;
 mod automation;
+mod backup;
+mod bulk;
 mod business_tab;
+mod consistency;
 mod data;
 mod events;
+mod fairness;
+mod index;
+mod notifications;
 mod persistence;
 mod print;
+mod roster;
+mod scenarios;
 mod settings;
 mod scheduler;
+mod sync;
+mod totals;
+mod workspace;
 
-pub type BusinessContext = UseReducerHandle<Business>;
+#[cfg(test)]
+mod tests;
+
+pub type BusinessContext = UseReducerHandle<Workspace>;
 pub type SettingsContext = UseStateHandle<Settings>;
 pub type TabContext = UseStateHandle<Tabs>;
 pub type Sort = UseStateHandle<EmployeeSort>;
 
+/// Whether the page is the full editor, or the read-only viewer opened from a share link
+#[derive(Clone, PartialEq)]
+pub enum ViewMode {
+    Editor,
+    /// Only show the schedule, optionally limited to a single employee's row
+    ReadOnly { employee: Option<usize> },
+} impl ViewMode {
+    pub fn is_read_only(&self) -> bool {
+        matches!(self, ViewMode::ReadOnly { .. })
+    }
+}
+
 #[derive(PartialEq)]
 pub enum Tabs {
     Schedule,
     Business,
     Settings,
+    Log,
 } impl Tabs {
     pub fn curr_tab(&self, tab: Tabs) -> Option<AttrValue> {
         if tab.eq(self) {
@@ -39,31 +69,67 @@ pub enum Tabs {
 #[function_component]
 fn App() -> Html {
     let settings = use_state_eq(|| Settings::default());
-    let business = use_reducer_eq(|| Business::sample(settings.deref()));
+    let business = use_reducer_eq(|| Workspace::new(Business::sample(settings.deref())));
+    let view = use_state_eq(read_view_mode);
+    let notifications = use_reducer_eq(Notifications::default);
     
     {
-        let (business, settings) = (business.clone(), settings.clone());
+        let (business, settings, notifications) = (business.clone(), settings.clone(), notifications.clone());
         use_effect_with((), move |_| {
             let read = read_settings();
+            for problem in read.2 {
+                notifications.dispatch(NotificationEvents::Push { level: Level::Error, message: problem.into() });
+            }
+            let app = match &read.1 {
+                Some(s) => s.app.clone(),
+                None => settings.app.clone(),
+            };
             match read.1 {
                 Some(s) => settings.set(s),
                 None => (),
             }
             match read.0 {
                 Some(mut b) => {
-                    b.init(settings.app.open.clone(), settings.app.close.clone(), settings.app.block_size.clone());
-                    business.dispatch(BusinessEvents::InitFromHash { new_business: b });
+                    b.init(app.open, app.close, app.block_size);
+                    business.dispatch(BusinessEvents::InitFromHash { new_business: Box::new(b) });
                 },
                 None => (),
             }
+            if let Some(schedule) = read_shared_schedule() {
+                business.dispatch(BusinessEvents::LoadSchedule { schedule });
+            }
         });
     }
     
+    {
+        // Hand anything the last business action had to say over to the notification queue
+        let (notices, notifications) = (business.notices.clone(), notifications.clone());
+        use_effect_with(business.notices.generation, move |_| {
+            for (level, message) in notices.items {
+                notifications.dispatch(NotificationEvents::Push { level, message });
+            }
+        });
+    }
+
+    {
+        // Other editors can change the plan's business hours, which are kept in the settings. A scenario's hours stay
+        // until the plan is opened again.
+        let (business, settings) = (business.clone(), settings.clone());
+        use_effect_with(business.remote_hours, move |changes| {
+            if *changes > 0 && business.editing.is_none() {
+                follow_hours(&settings, business.plan());
+            }
+        });
+    }
+
     let tab = use_state_eq(|| Tabs::Schedule);
     let sort_table = use_state_eq(|| EmployeeSort::Name);
     let sort_settings = use_state_eq(|| EmployeeSort::Name);
 
-    write_settings(settings.deref());
+    // A scenario's business hours aren't saved over the plan's
+    if business.editing.is_none() {
+        write_settings(settings.deref());
+    }
     // let mut tab_styles = vec![None; 3];
     // match tab.deref() {
     //     Tabs::Schedule => tab_styles[0] = Some("mui--is-active"),
@@ -71,20 +137,52 @@ fn App() -> Html {
     //     Tabs::Settings => tab_styles[2] = Some("mui--is-active"),
     // }
 
+    if view.is_read_only() {
+        return html! {<ContextProvider<BusinessContext> context={business}>
+            <ContextProvider<SettingsContext> context={settings}>
+            <ContextProvider<ViewMode> context={view.deref().clone()}>
+            <ContextProvider<NotificationContext> context={notifications}>
+            <PrintTable />
+            <Toasts />
+            <ContextProvider<Sort> context={sort_table}>
+                <div class="viewer">
+                    <Table />
+                </div>
+            </ContextProvider<Sort>>
+            </ContextProvider<NotificationContext>>
+            </ContextProvider<ViewMode>>
+            </ContextProvider<SettingsContext>>
+        </ContextProvider<BusinessContext>>};
+    }
+
     html! {<ContextProvider<BusinessContext> context={business}> 
         <ContextProvider<SettingsContext> context={settings}>
+        <ContextProvider<ViewMode> context={view.deref().clone()}>
+        <ContextProvider<NotificationContext> context={notifications}>
         <PrintTable />
+        <Toasts />
         <ContextProvider<TabContext> context={tab.clone()}>
             <TabBar />
         </ContextProvider<TabContext>>
         <ContextProvider<Sort> context={sort_table}>
             <div class={classes!("mui-tabs__pane", tab.curr_tab(Tabs::Schedule))}>
                 <div class={"pane-content"}>
+                    <Scenarios />
                     <Table />
                     <br />
+                    <ScheduleTotals />
+                    <br />
+                    <FairnessSummary />
+                    <br />
                     <Controls />
                     <br />
+                    <AbsenceAssistant />
+                    <br />
                     <ScheduleCopy />
+                    <br />
+                    <ShareLink />
+                    <br />
+                    <SyncPanel />
                 </div>
             </div>
         </ContextProvider<Sort>>
@@ -100,6 +198,13 @@ fn App() -> Html {
                 <settings::SettingsTab />
             </div>
         </div>
+        <div class={classes!("mui-tabs__pane", tab.curr_tab(Tabs::Log))}>
+            <div class={"pane-content"}>
+                <audit_tab::AuditTab />
+            </div>
+        </div>
+        </ContextProvider<NotificationContext>>
+        </ContextProvider<ViewMode>>
         </ContextProvider<SettingsContext>>
     </ContextProvider<BusinessContext>>}
 }
@@ -110,6 +215,7 @@ fn TabBar() -> Html {
     let business_context = tab.clone();
     let schedule_context = tab.clone();
     let settings_context = tab.clone();
+    let log_context = tab.clone();
     // html!(<div><table><tr>
     //     <td onclick={move |_| {settings_context.set(Tabs::Settings);}}>
     //         {"Settings"}
@@ -128,6 +234,7 @@ fn TabBar() -> Html {
             <li class={tab.curr_tab(Tabs::Business)} onclick={move |_| {business_context.set(Tabs::Business);}}>{"Business"}</li>
             <li class={tab.curr_tab(Tabs::Schedule)} onclick={move |_| {schedule_context.set(Tabs::Schedule);}}>{"Schedule"}</li>
             <li class={tab.curr_tab(Tabs::Settings)} onclick={move |_| {settings_context.set(Tabs::Settings);}}>{"Settings"}</li>
+            <li class={tab.curr_tab(Tabs::Log)} onclick={move |_| {log_context.set(Tabs::Log);}}>{"Change Log"}</li>
         </ul>
     )
 }
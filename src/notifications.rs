@@ -0,0 +1,106 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+
+pub type NotificationContext = UseReducerHandle<Notifications>;
+
+/// Oldest notifications are dropped past this many
+const MAX_NOTIFICATIONS: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+} impl Level {
+    fn class(&self) -> &'static str {
+        match self {
+            Level::Info => "toast-info",
+            Level::Warning => "toast-warning",
+            Level::Error => "toast-error",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Notification {
+    pub id: usize,
+    pub level: Level,
+    pub message: AttrValue,
+}
+
+/// Queue of messages shown to the user until dismissed
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Notifications {
+    next_id: usize,
+    pub queue: Vec<Notification>,
+}
+
+pub enum NotificationEvents {
+    Push { level: Level, message: AttrValue },
+    Dismiss { id: usize },
+}
+
+impl Reducible for Notifications {
+    type Action = NotificationEvents;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut notifications = Rc::unwrap_or_clone(self);
+        match action {
+            NotificationEvents::Push { level, message } => {
+                match level {
+                    Level::Error => log::warn!("{}", message),
+                    _ => log::info!("{}", message),
+                }
+                // Repeating the newest message doesn't need another toast
+                if notifications.queue.last().is_some_and(|last| last.level == level && last.message == message) {
+                    return notifications.into();
+                }
+                notifications.queue.push(Notification { id: notifications.next_id, level, message });
+                notifications.next_id += 1;
+                if notifications.queue.len() > MAX_NOTIFICATIONS {
+                    notifications.queue.remove(0);
+                }
+            },
+            NotificationEvents::Dismiss { id } => notifications.queue.retain(|notification| notification.id != id),
+        }
+        notifications.into()
+    }
+}
+
+/// Messages raised while handling a [`crate::events::BusinessEvents`], picked up by the app after each action and moved
+/// into the [`Notifications`] queue. The generation changes whenever messages are added, so repeats are still seen.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Notices {
+    pub generation: usize,
+    pub items: Vec<(Level, AttrValue)>,
+} impl Notices {
+    pub fn push(&mut self, level: Level, message: impl Into<AttrValue>) {
+        self.items.push((level, message.into()));
+        self.generation += 1;
+    }
+}
+
+#[function_component]
+pub fn Toasts() -> Html {
+    let notifications = use_context::<NotificationContext>().expect("Notification context not found");
+
+    let mut toasts = vec![];
+    for notification in notifications.queue.iter() {
+        let onclick = {
+            let notifications = notifications.clone();
+            let id = notification.id;
+            Callback::from(move |_| notifications.dispatch(NotificationEvents::Dismiss { id }))
+        };
+        toasts.push(html!(
+            <div class={classes!("toast", notification.level.class())} key={notification.id}>
+                <span>{notification.message.clone()}</span>
+                <input type="button" value='\u{2715}' aria-label="Dismiss" onclick={onclick} />
+            </div>
+        ));
+    }
+
+    html!(<div class="toasts" role="status" aria-live="polite">
+        {toasts}
+    </div>)
+}
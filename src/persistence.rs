@@ -1,12 +1,15 @@
 use std::{collections::HashMap, io::{Read, Write}};
 
 use chrono::NaiveTime;
+use wasm_bindgen::{closure::Closure, JsCast};
 
-use crate::{data::{Business, RoleTrait}, settings::Settings, BusinessContext};
+use crate::{audit::{AuditEntry, AUDIT_LIMIT}, data::{Assignment, Business, Shift}, notifications::Level, settings::Settings, ViewMode};
 
 pub const SETTINGS_DELIMITER: char = '&';
 
-/// Write current business info (roles, employees, rarely changing info) to the page hash (url query section)
+/// Write current business info (roles, employees, rarely changing info) to the page hash (url query section). Only the
+/// plan is written, so this is given [`Workspace::plan`](crate::workspace::Workspace::plan) even while a scenario is
+/// being edited.
 pub fn write_business(business: &Business) {
     let serialized = match ron::to_string(business) {
         // Ok(s) => "business=".to_string() + &s,
@@ -17,157 +20,268 @@ pub fn write_business(business: &Business) {
     let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::best());
     encoder.write_all(serialized.as_bytes()).unwrap();
     let encoded = encoder.finish().unwrap();
-    
-    let location = web_sys::window()
-        .expect("Could not pull window")
-        .document()
-        .expect("Could not pull document")
-        .location()
-        .expect("Could not pull location");
-    let zip = "zip=".to_string() + &encoded_to_string(encoded);
 
-    let mut hash = location.hash().expect("Could not pull hash");
-    if hash.is_empty() {
-        let _ = location.set_hash(&zip);
-        return;
-    }
-    let mut split: Vec<&str> = hash.split(SETTINGS_DELIMITER).collect();
-    if split.len().eq(&1) {
-        if hash.contains("zip=") {
-            let _ = location.set_hash(&zip);
-            return;
-        } else {
-            hash.push(SETTINGS_DELIMITER);
-            let _ = location.set_hash(&(hash + &zip));
-            return;
-        }
-    } else {
-        for i in 0..split.len() {
-            if split[i].contains("zip=") {
-                split[i] = &zip;
-            }
-        }
-        let mut final_hash = split[0].to_string();
-        final_hash.push(SETTINGS_DELIMITER);
-        final_hash.push_str(split[1]);
-        let _ = location.set_hash(&final_hash);
-    }
+    let zip = "zip=".to_string() + &encoded_to_string(encoded);
+    set_hash_segment(|segment| segment.contains("zip="), &zip, false);
 }
 
 pub fn write_settings(settings: &Settings) {
-    let mut settings_query = settings.fragment_string();
+    let settings_query = settings.fragment_string();
 
-    if settings_query.len() == 0 {
+    if settings_query.is_empty() {
         return;
     }
 
-    let location = web_sys::window()
+    set_hash_segment(|segment| segment.contains("(|"), &settings_query, true);
+}
+
+fn location() -> web_sys::Location {
+    web_sys::window()
         .expect("Could not pull window")
         .document()
         .expect("Could not pull document")
         .location()
-        .expect("Could not pull location");
+        .expect("Could not pull location")
+}
 
+/// Split the page hash into its non-empty segments, without the leading '#'
+fn hash_segments(hash: &str) -> Vec<&str> {
+    hash.trim_start_matches('#')
+        .split(SETTINGS_DELIMITER)
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// Replace the hash segment matched by `is_segment`, or add it if none match. Every other segment (view flags, shared
+/// schedules, etc.) is left in place.
+fn set_hash_segment(is_segment: impl Fn(&str) -> bool, segment: &str, at_front: bool) {
+    let location = location();
     let hash = location.hash().expect("Could not pull hash");
-    if hash.is_empty() {
-        let _ = location.set_hash(&settings_query);
-        return;
-    }
-    let mut split: Vec<&str> = hash.split(SETTINGS_DELIMITER).collect();
-    if split.len().eq(&1) {
-        if hash.contains("zip=") {
-            settings_query.push(SETTINGS_DELIMITER);
-            let _ = location.set_hash(&(settings_query + &hash));
-            return;
-        } else {
-            let _ = location.set_hash(&settings_query);
-            return;
-        }
-    } else {
-        for i in 0..split.len() {
-            if split[i].contains("zip=") {
-                continue;
-            }
-            split[i] = &settings_query;
-        }
-        let mut final_hash = split[0].to_string();
-        final_hash.push(SETTINGS_DELIMITER);
-        final_hash.push_str(split[1]);
-        let _ = location.set_hash(&final_hash);
+    let mut split = hash_segments(&hash);
+    match split.iter().position(|s| is_segment(s)) {
+        Some(i) => split[i] = segment,
+        None if at_front => split.insert(0, segment),
+        None => split.push(segment),
     }
+    let _ = location.set_hash(&split.join(&SETTINGS_DELIMITER.to_string()));
 }
 
-/// Read business info from page hash
-pub fn read_settings() -> (Option<Business>, Option<Settings>) {
+/// Read business info from page hash, along with a description of anything in it which could not be read
+pub fn read_settings() -> (Option<Business>, Option<Settings>, Vec<String>) {
     let mut result = (None, None);
+    let mut problems = vec![];
 
-    let location = web_sys::window()
-        .expect("Could not pull window")
-        .document()
-        .expect("Could not pull document")
-        .location()
-        .expect("Could not pull location");
-
-    let hash = location.hash().expect("Could not pull hash");
-    let split: Vec<&str> = hash.split(SETTINGS_DELIMITER).collect();
+    let hash = location().hash().expect("Could not pull hash");
+    let split = hash_segments(&hash);
 
     'zip: {
-        let mut hash = match split.get(1) {
+        let mut hash = match split.iter().find(|segment| segment.contains("zip=")) {
             Some(s) => s.to_string(),
-            None => split[0].to_string(),
+            None => {
+                log::info!("No business information found in hash. Proceeding with sample.");
+                break 'zip;
+            },
         };
-        if hash.is_empty() {
-            log::info!("Hash found, but empty. Assuming no business information was provided and proceeding with sample.");
-            break 'zip;
-        }
         let data = hash.split_off(match hash.find("=") {
             Some(i) => i + 1,
-            None => {log::warn!("Hash found, but is not valid business information. Proceeding with sample."); break 'zip;},
+            None => {
+                log::warn!("Hash found, but is not valid business information. Proceeding with sample.");
+                problems.push(SAVED_BUSINESS_PROBLEM.to_string());
+                break 'zip;
+            },
         });
         match hash.as_str() {
-            "#zip=" => {
+            "zip=" => {
                 let from_string = match encoded_from_string(data) {
                     Ok(e) => e,
-                    Err(e) => {log::warn!("Failed to prep incoming zip for decoding; {}", e); break 'zip;},
+                    Err(e) => {
+                        log::warn!("Failed to prep incoming zip for decoding; {}", e);
+                        problems.push(format!("{} ({})", SAVED_BUSINESS_PROBLEM, e));
+                        break 'zip;
+                    },
                 };
                 let mut decoder = flate2::read::ZlibDecoder::new(from_string.as_slice());
                 let mut decoded = String::new();
                 if let Err(e) = decoder.read_to_string(&mut decoded) {
                     log::warn!("Failed to decode data; {}", e); 
+                    problems.push(format!("{} ({})", SAVED_BUSINESS_PROBLEM, e));
                     break 'zip;
                 }
                 match ron::from_str(&decoded) {
                     Ok(business) => result = (Some(business), result.1),
-                    Err(e) => {log::warn!("Failed to deserialize decoded data; {}\n{}", e, decoded); break 'zip;},
+                    Err(e) => {
+                        log::warn!("Failed to deserialize decoded data; {}\n{}", e, decoded);
+                        problems.push(format!("{} ({})", SAVED_BUSINESS_PROBLEM, e));
+                        break 'zip;
+                    },
                 }
             },
-            _ => {log::warn!("Hash found, but is not valid business information. Proceeding with sample."); break 'zip;}
+            _ => {log::info!("Hash found, but does not contain business information. Proceeding with sample."); break 'zip;}
         }
     }
 
     'settings: {
-        let hash = split[0].trim_start_matches("#").to_string();
-        if hash.is_empty() {
-            log::info!("Hash found, but empty. Assuming no settings information was provided and proceeding with default.");
-            break 'settings;
+        let hash = match split.iter().find(|segment| segment.contains("(|")) {
+            Some(s) => s.to_string(),
+            None => {
+                log::info!("No settings information found in hash. Proceeding with default.");
+                break 'settings;
+            }
+        };
+        match Settings::from_fragment(&hash) {
+            Ok(settings) => result = (result.0, Some(settings)),
+            Err(e) => {
+                log::warn!("Failed to read settings from hash; {}", e);
+                problems.push(format!("The settings saved in this link could not be read, so the defaults were used instead ({})", e));
+            }
         }
-        if !hash.contains("(|") {
-            log::warn!("Query found, but is not valid settings information. Proceeding with default.");
-            break 'settings;
+    }
+
+    (result.0, result.1, problems)
+}
+
+const SAVED_BUSINESS_PROBLEM: &str = "The roles and employees saved in this link could not be read, so the sample business was loaded instead";
+
+const VIEW_KEY: &str = "view";
+const SHARED_SCHEDULE_KEY: &str = "sched=";
+
+/// Read the viewer flag from the page hash. `#...&view` opens the whole schedule read-only, `#...&view=<employee id>`
+/// limits it to that employee's row.
+pub fn read_view_mode() -> ViewMode {
+    let hash = location().hash().expect("Could not pull hash");
+    for segment in hash_segments(&hash) {
+        if segment == VIEW_KEY {
+            return ViewMode::ReadOnly { employee: None };
+        }
+        if let Some(id) = segment.strip_prefix(VIEW_KEY).and_then(|rest| rest.strip_prefix("=")) {
+            match id.parse() {
+                Ok(id) => return ViewMode::ReadOnly { employee: Some(id) },
+                Err(e) => {
+                    log::warn!("Could not parse viewer employee id {}; showing full schedule. {}", id, e);
+                    return ViewMode::ReadOnly { employee: None };
+                }
+            }
         }
-        // let data = hash.split_off(match hash.find("=") {
-        //     Some(i) => i+1,
-        //     None => {log::warn!("Query found, but is not valid settings information. Proceeding with default."); break 'settings;},
-        // });
-        // match hash.as_str() {
-        //     "?settings=" => {
-                result = (result.0, Some(Settings::from_fragment(&hash)));
-        //     },
-        //     _ => {log::warn!("Query found, but is not valid settings information. Proceeding with default."); break 'settings;}
-        // }
     }
+    ViewMode::Editor
+}
 
-    result
+const AUDIT_KEY: &str = "audit";
+const EDITOR_KEY: &str = "editor";
+
+/// Storage kept by this browser, rather than in the link, for things which belong to this computer
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// The change log kept by this browser, oldest first
+pub fn read_audit() -> Vec<AuditEntry> {
+    let saved = match local_storage().and_then(|storage| storage.get_item(AUDIT_KEY).ok()?) {
+        Some(saved) => saved,
+        None => return vec![],
+    };
+    match ron::from_str(&saved) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::warn!("Failed to read the change log; starting a new one. {}", e);
+            vec![]
+        },
+    }
+}
+
+/// Add to the change log, dropping the oldest entries past [`AUDIT_LIMIT`]
+pub fn append_audit(entry: AuditEntry) {
+    let mut entries = read_audit();
+    entries.push(entry);
+    let excess = entries.len().saturating_sub(AUDIT_LIMIT);
+    entries.drain(..excess);
+    write_audit(&entries);
+}
+
+pub fn write_audit(entries: &[AuditEntry]) {
+    let storage = match local_storage() {
+        Some(storage) => storage,
+        None => {log::warn!("No local storage; the change log won't be kept"); return;},
+    };
+    match ron::to_string(entries) {
+        Ok(serialized) => if let Err(e) = storage.set_item(AUDIT_KEY, &serialized) {
+            log::error!("Failed to save the change log! {:?}", e);
+        },
+        Err(e) => log::error!("Failed to serialize the change log! {:#?}", e),
+    }
+}
+
+/// Name of whoever is making changes on this computer, for the change log
+pub fn read_editor() -> Option<String> {
+    local_storage()
+        .and_then(|storage| storage.get_item(EDITOR_KEY).ok()?)
+        .filter(|name| !name.is_empty())
+}
+
+pub fn write_editor(name: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.set_item(EDITOR_KEY, name.trim());
+    }
+}
+
+/// Read a schedule shared through a viewer link, in the same format as [`schedule_to_csv`]
+pub fn read_shared_schedule() -> Option<String> {
+    let hash = location().hash().expect("Could not pull hash");
+    hash_segments(&hash).iter()
+        .find_map(|segment| segment.strip_prefix(SHARED_SCHEDULE_KEY))
+        .map(|schedule| schedule.to_string())
+}
+
+/// Build a link to the current business and schedule which opens in the read-only viewer
+pub fn share_link(schedule: &str, employee: Option<usize>) -> String {
+    let location = location();
+    let hash = location.hash().expect("Could not pull hash");
+    let href = location.href().expect("Could not pull href");
+    let base = href.split('#').next().unwrap_or_default();
+
+    let mut split: Vec<String> = hash_segments(&hash).into_iter()
+        .filter(|segment| !segment.starts_with(VIEW_KEY) && !segment.starts_with(SHARED_SCHEDULE_KEY))
+        .map(|segment| segment.to_string())
+        .collect();
+    split.push(SHARED_SCHEDULE_KEY.to_string() + schedule);
+    split.push(match employee {
+        Some(id) => format!("{}={}", VIEW_KEY, id),
+        None => VIEW_KEY.to_string(),
+    });
+    format!("{}#{}", base, split.join(&SETTINGS_DELIMITER.to_string()))
+}
+
+/// Text percent-encoded for use in a link, such as a data: link to download a file
+pub fn percent_encode(text: &str) -> String {
+    text.bytes().map(|byte| match byte {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+        _ => format!("%{:02X}", byte),
+    }).collect()
+}
+
+/// Read the file chosen in the input as text, handing it to `loaded` once read. The input is cleared, so choosing the
+/// same file again reads it again.
+pub fn read_text_file(input: &web_sys::HtmlInputElement, loaded: impl FnOnce(String) + 'static) {
+    let file = match input.files().and_then(|files| files.get(0)) {
+        Some(file) => file,
+        None => return,
+    };
+    let reader = match web_sys::FileReader::new() {
+        Ok(reader) => reader,
+        Err(e) => {log::warn!("Could not read the file; {:?}", e); return;},
+    };
+    let result = reader.clone();
+    let onload = Closure::once_into_js(move || {
+        match result.result().ok().and_then(|text| text.as_string()) {
+            Some(text) => loaded(text),
+            None => log::warn!("The file read wasn't text"),
+        }
+    });
+    reader.set_onload(Some(onload.unchecked_ref()));
+    if let Err(e) = reader.read_as_text(&file) {
+        log::warn!("Could not read the file; {:?}", e);
+    }
+    input.set_value("");
 }
 
 fn encoded_to_string(bytes: Vec<u8>) -> String {
@@ -188,11 +302,18 @@ fn encoded_from_string(encoded: String) -> Result<Vec<u8>, std::num::ParseIntErr
 }
 
 // CSV header:
-// employee_id, clock_in, clock_out, assigned
+// employee_id, clock_in, clock_out, assignments as role@start-end
+//
+// Locked assignments end in !. Someone on a split shift has a clock_in, clock_out pair for each shift. Schedules saved before assignments were kept
+// as times have one role code per block in place of the assignments.
 
 pub enum ParseError {
     ParseIntError(std::num::ParseIntError),
-    ParseError(chrono::ParseError)
+    ParseError(chrono::ParseError),
+    /// A line ended before the named field
+    MissingField(&'static str),
+    /// An assignment wasn't in the form role@start-end, with an optional ! for locked
+    BadAssignment(String)
 } impl From<std::num::ParseIntError> for ParseError {
     fn from(value: std::num::ParseIntError) -> Self {
         Self::ParseIntError(value)
@@ -206,21 +327,29 @@ pub enum ParseError {
         match self {
             ParseError::ParseIntError(parse_int_error) => write!(f, "{}", parse_int_error),
             ParseError::ParseError(parse_error) => write!(f, "{}", parse_error),
+            ParseError::MissingField(field) => write!(f, "missing {}", field),
+            ParseError::BadAssignment(assignment) => write!(f, "unreadable assignment {}", assignment),
         }
     }
 }
 
 const SEPERATOR: &'static str = ",";
 const NEWLINE: &'static str = "--";
-pub fn schedule_to_csv(business: BusinessContext) -> String {
+pub fn schedule_to_csv(business: &Business) -> String {
     let mut result = String::new();
     for employee in business.employees.values() {
         result += &(employee.id.to_string() + SEPERATOR);
         if employee.scheduled {
-            result += &(employee.clock_in.to_string() + SEPERATOR);
-            result += &(employee.clock_out.to_string() + SEPERATOR);
-            for time_index in employee.assigned.iter() {
-                result += &(time_index.to_string() + SEPERATOR);
+            for shift in employee.shifts.iter() {
+                result += &(shift.clock_in.to_string() + SEPERATOR);
+                result += &(shift.clock_out.to_string() + SEPERATOR);
+            }
+            for assignment in employee.assignments.iter() {
+                let lock = match assignment.locked {
+                    true => "!",
+                    false => "",
+                };
+                result += &format!("{}@{}-{}{}{}", assignment.role, assignment.start, assignment.end, lock, SEPERATOR);
             }
         } else {
             result += &("false".to_string() + SEPERATOR)
@@ -231,19 +360,25 @@ pub fn schedule_to_csv(business: BusinessContext) -> String {
 }
 
 pub enum Schedule {
-    True((NaiveTime,NaiveTime,Vec<usize>)),
+    True((Vec<Shift>,Vec<Assignment>)),
+    /// Saved before assignments were kept as times, with a role code per block (see [`crate::data::Employee::assigned`])
+    Blocks((Vec<Shift>,Vec<usize>)),
     False
-} impl From<(NaiveTime,NaiveTime,Vec<usize>)> for Schedule {
-    fn from(value: (NaiveTime,NaiveTime,Vec<usize>)) -> Self {
+} impl From<(Vec<Shift>,Vec<Assignment>)> for Schedule {
+    fn from(value: (Vec<Shift>,Vec<Assignment>)) -> Self {
         Self::True(value)
     }
-} impl Schedule {
-    pub fn decompose(self) -> Option<(NaiveTime, NaiveTime, Vec<usize>)> {
-        match self {
-            Schedule::True(x) => Some(x),
-            Schedule::False => None,
-        }
-    }
+}
+
+fn parse_assignment(field: &str) -> core::result::Result<Assignment, ParseError> {
+    let bad = || ParseError::BadAssignment(field.to_string());
+    let (unlocked, locked) = match field.strip_suffix('!') {
+        Some(unlocked) => (unlocked, true),
+        None => (field, false),
+    };
+    let (role, times) = unlocked.split_once('@').ok_or_else(bad)?;
+    let (start, end) = times.split_once('-').ok_or_else(bad)?;
+    Ok(Assignment { role: role.parse()?, start: start.parse()?, end: end.parse()?, locked })
 }
 
 pub fn csv_to_schedule(csv: String) -> core::result::Result<HashMap<usize, Schedule>, ParseError> {
@@ -253,74 +388,118 @@ pub fn csv_to_schedule(csv: String) -> core::result::Result<HashMap<usize, Sched
             continue;
         }
         let mut parts = line.split(SEPERATOR);
-        let id: usize = parts.next().unwrap().parse()?;
-        let part2 = parts.next().unwrap();
+        let id: usize = parts.next().ok_or(ParseError::MissingField("employee id"))?.parse()?;
+        let part2 = parts.next().ok_or(ParseError::MissingField("clock-in"))?;
         if part2.eq("false") {
             result.insert(id, Schedule::False);
             continue;
         }
-        let clock_in: NaiveTime = part2.parse()?;
-        let clock_out: NaiveTime = parts.next().unwrap().parse()?;
-        let mut assigned = vec![];
-        for time in parts {
-            assigned.push(time.parse::<usize>()?);
+        let mut shifts = vec![];
+        let mut clock_in: NaiveTime = part2.parse()?;
+        let mut parts = parts.peekable();
+        loop {
+            let clock_out: NaiveTime = parts.next().ok_or(ParseError::MissingField("clock-out"))?.parse()?;
+            shifts.push(Shift { clock_in, clock_out });
+            // Another time means another shift
+            match parts.peek().and_then(|part| part.parse::<NaiveTime>().ok()) {
+                Some(time) => {
+                    clock_in = time;
+                    parts.next();
+                },
+                None => break,
+            }
+        }
+        let parts: Vec<&str> = parts.collect();
+        if parts.iter().any(|part| !part.contains('@')) {
+            let mut assigned = vec![];
+            for time in parts {
+                assigned.push(time.parse::<usize>()?);
+            }
+            result.insert(id, Schedule::Blocks((shifts, assigned)));
+            continue;
         }
-        result.insert(id, (clock_in, clock_out, assigned).into());
+        let mut assignments = vec![];
+        for part in parts {
+            assignments.push(parse_assignment(part)?);
+        }
+        result.insert(id, (shifts, assignments).into());
     }
     Ok(result)
 }
 
 impl Business {
-    pub fn load_schedule(&mut self, schedule: String) {
+    /// Replace the current schedule with one from [`schedule_to_csv`]. Returns anything the user should be told about,
+    /// such as rows which could not be loaded.
+    pub fn load_schedule(&mut self, schedule: String) -> Vec<(Level, String)> {
+        let mut problems = vec![];
         let result = csv_to_schedule(schedule);
         match result {
             Ok(schedule) => {
-                self.roles.values_mut().for_each(|role| role.blank_out(self.blocks));
-                self.employees.values_mut().for_each(|emp| emp.deschedule(self.blocks));
+                self.employees.values_mut().for_each(|emp| emp.deschedule());
                 for (emp_id, scheduled) in schedule {
+                    let name = self.employee_name(emp_id);
                     let emp_get = self.employees.get_mut(&emp_id);
                     let employee = match emp_get {
                         Some(e) => e,
-                        None => {log::warn!("Attempted to load schedule for invalid employee id: {}", emp_id); continue;},
+                        None => {
+                            log::warn!("Attempted to load schedule for invalid employee id: {}", emp_id);
+                            problems.push((Level::Warning, format!("Skipped the schedule for {}, who no longer exists", name)));
+                            continue;
+                        },
                     };
-                    let (clock_in, clock_out, assigned) = match scheduled.decompose() {
-                        Some(x) => x,
-                        None => continue,
+                    let (shifts, assigned) = match scheduled {
+                        Schedule::True((shifts, assignments)) => {
+                            employee.scheduled = true;
+                            if let Err(e) = self.update_employee_shifts(emp_id, shifts) {
+                                log::warn!("Failed to set shifts for employee {}; {}", emp_id, e);
+                                problems.push((Level::Warning, format!("Skipped the schedule for {}; {}", name, self.describe_error(&e))));
+                                continue;
+                            }
+                            for assignment in assignments {
+                                if let Err(e) = self.assign_span(emp_id, assignment.role, assignment.start, assignment.end) {
+                                    log::warn!("Failed to assign role {} for employee {}; {}", assignment.role, emp_id, e);
+                                    problems.push((Level::Warning, self.describe_error(&e)));
+                                    continue;
+                                }
+                                if assignment.locked {
+                                    if let Some(employee) = self.employees.get_mut(&emp_id) {
+                                        employee.set_locked(assignment.role, assignment.start, assignment.end, true);
+                                    }
+                                }
+                            }
+                            continue;
+                        },
+                        Schedule::Blocks(x) => x,
+                        Schedule::False => continue,
                     };
-                    if employee.assigned.len() != assigned.len() {
-                        log::warn!("Employee {} schedule is incorrect length; expected: {} recieved: {}", emp_id, employee.assigned.len(), assigned.len());
+                    if self.blocks != assigned.len() {
+                        log::warn!("Employee {} schedule is incorrect length; expected: {} recieved: {}", emp_id, self.blocks, assigned.len());
+                        problems.push((Level::Warning, format!("Skipped the schedule for {}; it was made for {} blocks, but the day has {}", name, assigned.len(), self.blocks)));
                         continue;
                     }
                     employee.scheduled = true;
-                    employee.clock_in = clock_in;
-                    employee.clock_out = clock_out;
-                    let mut new_roles: HashMap<usize, Vec<usize>> = HashMap::new();
-                    for i in 0..assigned.len() {
-                        let role_id = assigned[i];
-                        employee.assigned[i] = 1;
-                        let curr_role = new_roles.get_mut(&role_id);
-                        match curr_role {
-                            Some(role) => role.push(i),
-                            None => {new_roles.insert(role_id, vec![i]);}
-                        }
+                    if let Err(e) = self.update_employee_shifts(emp_id, shifts) {
+                        log::warn!("Failed to set shifts for employee {}; {}", emp_id, e);
+                        problems.push((Level::Warning, format!("Skipped the schedule for {}; {}", name, self.describe_error(&e))));
+                        continue;
                     }
-                    match new_roles.remove(&0) {
-                        Some(indexes) => {
-                            for index in indexes {
-                                employee.assigned[index] = 0;
-                            }
-                        },
-                        None => {},
+                    let mut new_roles: HashMap<usize, Vec<usize>> = HashMap::new();
+                    for (i, role_id) in assigned.into_iter().enumerate().filter(|(_, role_id)| *role_id >= 2) {
+                        new_roles.entry(role_id).or_default().push(i);
                     }
-                    let _ = new_roles.remove(&1);
                     for (role_id, new_blocks) in new_roles {
                         if let Err(e) = self.assign_block(emp_id, role_id, new_blocks) {
                             log::warn!("Failed to assign role {} for employee {}; {}", role_id, emp_id, e);
+                            problems.push((Level::Warning, self.describe_error(&e)));
                         }
                     }
                 }
             },
-            Err(e) => {log::warn!("Failed to parse schedule as csv; {}", e)}
+            Err(e) => {
+                log::warn!("Failed to parse schedule as csv; {}", e);
+                problems.push((Level::Error, format!("Could not read the schedule; {}", e)));
+            }
         }
+        problems
     }
 }
\ No newline at end of file
@@ -1,7 +1,7 @@
 use chrono::Timelike;
 use yew::prelude::*;
 
-use crate::{data::{Role, RoleTrait}, BusinessContext, SettingsContext};
+use crate::{data::{Employee, EmployeeSort, Role, RoleTrait}, BusinessContext, SettingsContext};
 
 #[function_component]
 pub fn PrintTable() -> Html {
@@ -21,7 +21,7 @@ pub fn PrintTable() -> Html {
         let mut i = 0;
         let mut curr_time = business.open.clone();
         let mut last_group = (0, business.open.clone());
-        let assigned: Vec<usize> = role.assigned().into();
+        let assigned: Vec<usize> = business.index().role_assigned(role).into();
         while curr_time < business.close {
             'block: {
                 if last_group.0 != assigned[i] {
@@ -74,12 +74,35 @@ pub fn PrintTable() -> Html {
             </div>
         ));
     }
-    let lunch = business.roles.get(&2).unwrap();
-    {
+    // Anyone coming back later in the day, so whoever is running the floor knows to expect them
+    let mut split: Vec<&Employee> = business.employees.values().filter(|emp| emp.scheduled && emp.shifts.len() > 1).collect();
+    split.sort_by(|a, b| a.cmp(b, EmployeeSort::ClockIn));
+    if !split.is_empty() {
+        let emps = split.into_iter().map(|employee| {
+            let shifts: Vec<String> = employee.shifts.iter()
+                .map(|shift| format!("{}-{}", time_string(&shift.clock_in), time_string(&shift.clock_out)))
+                .collect();
+            html!(
+                <li>
+                    {employee.name.clone()}
+                    <span>{format!(" {}", shifts.join(", "))}</span>
+                </li>
+            )
+        });
+        columns.push(html!(
+            <div class="print-column">
+                <h3>{"Split shifts"}</h3>
+                <ul class="no-bullets">
+                    {for emps}
+                </ul>
+            </div>
+        ));
+    }
+    if let Some(lunch) = business.roles.get(&2) {
         let mut emps = vec![];
         let mut i = 0;
         let mut curr_time = business.open.clone();
-        let assigned: Vec<Vec<usize>> = lunch.assigned().into();
+        let assigned: Vec<Vec<usize>> = business.index().role_assigned(lunch).into();
         let mut listed = vec![];
         while curr_time < business.close {
             let block = &assigned[i];
@@ -0,0 +1,73 @@
+// Keeps the planner working without a network. Trunk gives the app's files hashed names, so instead of a fixed list
+// the files to keep are read from the page itself: whenever the page is fetched, everything it links to is cached and
+// anything left from an older build is dropped. Schedules live in the page address and local storage, so once the
+// app is cached they need nothing else.
+
+const CACHE = 'daily-planner';
+// Kept alongside whatever the page links to
+const SHELL = ['manifest.webmanifest', 'icon.svg', 'icon-192.png', 'icon-512.png'];
+
+const scoped = path => new URL(path, self.registration.scope).href;
+const PAGE = scoped('./');
+
+/** Same-origin files the page links to or imports */
+function assetsOf(html) {
+    const assets = [];
+    for (const match of html.matchAll(/["']([^"'\s]+\.(?:js|wasm|css|png|svg|webmanifest))["']/g)) {
+        const url = new URL(match[1], PAGE);
+        if (url.origin === self.location.origin) {
+            assets.push(url.href);
+        }
+    }
+    return assets;
+}
+
+/** Keep the page and everything it needs, dropping the rest */
+async function cachePage(response) {
+    const cache = await caches.open(CACHE);
+    const html = await response.clone().text();
+    await cache.put(PAGE, response);
+    const keep = new Set([PAGE, ...SHELL.map(scoped), ...assetsOf(html)]);
+    for (const url of keep) {
+        if (!(await cache.match(url))) {
+            const asset = await fetch(url);
+            if (asset.ok) {
+                await cache.put(url, asset);
+            }
+        }
+    }
+    for (const request of await cache.keys()) {
+        if (!keep.has(request.url)) {
+            await cache.delete(request);
+        }
+    }
+}
+
+self.addEventListener('install', event => {
+    event.waitUntil(fetch(PAGE, { cache: 'reload' })
+        .then(cachePage)
+        .then(() => self.skipWaiting()));
+});
+
+self.addEventListener('activate', event => event.waitUntil(self.clients.claim()));
+
+self.addEventListener('fetch', event => {
+    const request = event.request;
+    if (request.method !== 'GET' || new URL(request.url).origin !== self.location.origin) {
+        return;
+    }
+    if (request.mode === 'navigate') {
+        // The newest page while online, so new builds arrive; the kept one otherwise
+        event.respondWith(fetch(request)
+            .then(response => {
+                if (response.ok) {
+                    event.waitUntil(cachePage(response.clone()));
+                }
+                return response;
+            })
+            .catch(() => caches.match(PAGE)));
+        return;
+    }
+    // Hashed names never change content, so anything kept can be used as is
+    event.respondWith(caches.match(request).then(cached => cached || fetch(request)));
+});
@@ -0,0 +1,291 @@
+use chrono::NaiveTime;
+
+use crate::{audit::csv_field, data::{Business, Employee, EmployeeSort, RoleTrait, Shift}, totals::{format_money, parse_money}};
+
+/// A roster column the planner reads, by header
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Column {
+    Name,
+    Roles,
+    Hours,
+    Lunch,
+    Wage,
+}
+
+fn column(header: &str) -> Option<Column> {
+    match header.trim().to_lowercase().as_str() {
+        "name" | "employee" => Some(Column::Name),
+        "roles" | "role" => Some(Column::Roles),
+        "hours" | "default hours" | "shift" | "shifts" => Some(Column::Hours),
+        "lunch" | "lunch minutes" => Some(Column::Lunch),
+        "wage" | "pay" | "hourly wage" => Some(Column::Wage),
+        _ => None,
+    }
+}
+
+/// One employee read from a roster, see [`Business::preview_roster`]. Blank cells leave what they'd set alone.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RosterRow {
+    /// Line of the file the row starts on, counting the header
+    pub line: usize,
+    pub name: String,
+    /// The employee of the same name the row updates, or None to add a new one
+    pub employee: Option<usize>,
+    /// Role names as written. The employee works exactly these once imported.
+    pub roles: Option<Vec<String>>,
+    pub shifts: Option<Vec<Shift>>,
+    /// In blocks
+    pub lunch: Option<usize>,
+    /// In cents
+    pub wage: Option<usize>,
+    /// Why the row can't be imported, if it can't
+    pub errors: Vec<String>,
+    /// Worth knowing, but not stopping the row
+    pub warnings: Vec<String>,
+} impl RosterRow {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// What importing a roster would do
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct RosterPreview {
+    pub rows: Vec<RosterRow>,
+    /// Roles named by rows which can be imported that don't exist yet, in the order first named
+    pub new_roles: Vec<String>,
+    /// Problems with the whole file, which keep anything from being imported
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+} impl RosterPreview {
+    /// How many rows would be imported, as (added, updated)
+    pub fn counts(&self) -> (usize, usize) {
+        let valid = || self.rows.iter().filter(|row| row.is_valid());
+        (valid().filter(|row| row.employee.is_none()).count(), valid().filter(|row| row.employee.is_some()).count())
+    }
+}
+
+/// Records of a CSV file with the line each starts on. Fields may be quoted, with "" for a quote. Text pasted from a
+/// spreadsheet is tab separated, so tabs are used instead of commas when the first line has one.
+pub fn parse_csv(text: &str) -> Vec<(usize, Vec<String>)> {
+    let delimiter = match text.lines().next().is_some_and(|line| line.contains('\t')) {
+        true => '\t',
+        false => ',',
+    };
+    let mut records = vec![];
+    let (mut record, mut field) = (vec![], String::new());
+    let (mut line, mut start, mut quoted) = (1, 1, false);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            },
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            ('\n', true) => {
+                field.push('\n');
+                line += 1;
+            },
+            ('\r', false) => {},
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut record)));
+                line += 1;
+                start = line;
+            },
+            (c, false) if c == delimiter => record.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    // Blank lines, including a spreadsheet's empty rows
+    records.retain(|(_, record)| record.iter().any(|field| !field.trim().is_empty()));
+    records
+}
+
+/// A clock time such as "9:00", "17:30", "5:30 PM" or "9"
+fn parse_clock(text: &str) -> Option<NaiveTime> {
+    let text = text.trim();
+    if let Ok(hour) = text.parse() {
+        return NaiveTime::from_hms_opt(hour, 0, 0);
+    }
+    ["%H:%M", "%H:%M:%S", "%I:%M %p", "%I:%M%p"].iter()
+        .find_map(|format| NaiveTime::parse_from_str(&text.to_uppercase(), format).ok())
+}
+
+/// Shifts such as "9:00-17:00", or "9:00-12:00; 13:00-17:00" for a split shift
+fn parse_shifts(text: &str) -> Result<Vec<Shift>, String> {
+    let mut shifts = vec![];
+    for part in text.split([';', ',']).filter(|part| !part.trim().is_empty()) {
+        let times = part.split_once(" to ").or_else(|| part.split_once(['-', '\u{2013}']));
+        let shift = times.and_then(|(clock_in, clock_out)| Some(Shift { clock_in: parse_clock(clock_in)?, clock_out: parse_clock(clock_out)? }));
+        match shift {
+            Some(shift) if shift.clock_in < shift.clock_out => shifts.push(shift),
+            Some(_) => return Err(format!("Hours \"{}\" end before they start", part.trim())),
+            None => return Err(format!("Hours \"{}\" aren't like 9:00-17:00", part.trim())),
+        }
+    }
+    Ok(shifts)
+}
+
+impl Business {
+    /// The role of that name, ignoring case
+    fn role_named(&self, name: &str) -> Option<usize> {
+        self.roles.values().find(|role| role.name().eq_ignore_ascii_case(name.trim())).map(|role| role.id())
+    }
+
+    /// Read a roster with a header row naming its columns: name, then any of roles (separated by semicolons), hours,
+    /// lunch (in minutes) and wage. Each row is checked, and matched to an existing employee by name. Unknown roles
+    /// are listed to be added when `create_roles` is set, or skipped otherwise.
+    pub fn preview_roster(&self, text: &str, create_roles: bool) -> RosterPreview {
+        let mut preview = RosterPreview::default();
+        let mut records = parse_csv(text).into_iter();
+        let header = match records.next() {
+            Some((_, header)) => header,
+            None => return preview,
+        };
+        let columns: Vec<Option<Column>> = header.iter().map(|header| column(header)).collect();
+        if !columns.contains(&Some(Column::Name)) {
+            preview.errors.push("The first row needs a \"name\" column".into());
+            return preview;
+        }
+        for (header, column) in header.iter().zip(columns.iter()) {
+            if column.is_none() && !header.trim().is_empty() {
+                preview.warnings.push(format!("The \"{}\" column isn't used", header.trim()));
+            }
+        }
+
+        for (line, record) in records {
+            let cell = |wanted: Column| columns.iter().position(|column| *column == Some(wanted))
+                .and_then(|index| record.get(index))
+                .map(|cell| cell.trim())
+                .filter(|cell| !cell.is_empty());
+            let name = cell(Column::Name).unwrap_or_default().to_string();
+            let mut row = RosterRow { line, name: name.clone(), employee: None, roles: None, shifts: None, lunch: None, wage: None, errors: vec![], warnings: vec![] };
+
+            let same_name: Vec<usize> = self.employees.values().filter(|emp| emp.name.trim().eq_ignore_ascii_case(&name)).map(|emp| emp.id).collect();
+            match same_name[..] {
+                _ if name.is_empty() => row.errors.push("No name".into()),
+                [] => {},
+                [id] => row.employee = Some(id),
+                _ => row.errors.push(format!("More than one employee is named {}", name)),
+            }
+            if let Some(earlier) = preview.rows.iter().find(|earlier| !name.is_empty() && earlier.name.eq_ignore_ascii_case(&name)) {
+                row.errors.push(format!("{} is already on line {}", name, earlier.line));
+            }
+
+            if let Some(roles) = cell(Column::Roles) {
+                let roles: Vec<String> = roles.split([';', ',', '|']).map(|role| role.trim().to_string()).filter(|role| !role.is_empty()).collect();
+                for role in roles.iter().filter(|role| self.role_named(role).is_none()) {
+                    match create_roles {
+                        true => row.warnings.push(format!("Adds the role {}", role)),
+                        false => row.warnings.push(format!("Skips {}, which isn't a role", role)),
+                    }
+                }
+                row.roles = Some(roles);
+            }
+            if let Some(hours) = cell(Column::Hours) {
+                match parse_shifts(hours) {
+                    Ok(shifts) => {
+                        if shifts.iter().any(|shift| shift.clock_in < self.open || shift.clock_out > self.close) {
+                            row.warnings.push("Works outside business hours".into());
+                        }
+                        row.shifts = Some(shifts).filter(|shifts| !shifts.is_empty());
+                    },
+                    Err(e) => row.errors.push(e),
+                }
+            }
+            if let Some(lunch) = cell(Column::Lunch) {
+                match lunch.parse::<i64>() {
+                    Ok(minutes) if minutes >= 0 => row.lunch = Some(self.lunch_blocks(minutes)),
+                    _ => row.errors.push(format!("Lunch \"{}\" isn't a number of minutes", lunch)),
+                }
+            }
+            if let Some(wage) = cell(Column::Wage) {
+                match parse_money(wage) {
+                    Some(cents) => row.wage = Some(cents),
+                    None => row.errors.push(format!("Wage \"{}\" isn't an amount", wage)),
+                }
+            }
+            preview.rows.push(row);
+        }
+
+        if create_roles {
+            for row in preview.rows.iter().filter(|row| row.is_valid()) {
+                for role in row.roles.iter().flatten() {
+                    if self.role_named(role).is_none() && !preview.new_roles.iter().any(|new| new.eq_ignore_ascii_case(role)) {
+                        preview.new_roles.push(role.clone());
+                    }
+                }
+            }
+        }
+        preview
+    }
+
+    /// Every employee as a roster [`Business::preview_roster`] reads, with a header row and sorted by name
+    pub fn roster_csv(&self) -> String {
+        let mut result = String::from("name,roles,hours,lunch,wage\n");
+        let mut employees: Vec<&Employee> = self.employees.values().collect();
+        employees.sort_by(|a, b| a.cmp(b, EmployeeSort::Name));
+        for emp in employees {
+            let mut roles: Vec<usize> = emp.roles.iter().copied().filter(|role| *role != 2).collect();
+            roles.sort_by_key(|role| self.roles.get(role).map(|role| role.sort()));
+            let roles: Vec<String> = roles.iter().map(|role| self.role_name(*role).to_string()).collect();
+            let hours: Vec<String> = emp.shifts.iter()
+                .map(|shift| format!("{}-{}", shift.clock_in.format("%H:%M"), shift.clock_out.format("%H:%M")))
+                .collect();
+            let lunch = self.block_size.num_minutes() * emp.lunch as i64;
+            let fields = [emp.name.to_string(), roles.join("; "), hours.join("; "), lunch.to_string(), format_money(emp.wage)];
+            let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            result += &(fields.join(",") + "\n");
+        }
+        result
+    }
+
+    /// Import every row of the roster which can be, see [`Business::preview_roster`]. Returns the preview it went by.
+    pub fn import_roster(&mut self, text: &str, create_roles: bool) -> RosterPreview {
+        let preview = self.preview_roster(text, create_roles);
+        if !preview.errors.is_empty() {
+            return preview;
+        }
+        for role in preview.new_roles.iter() {
+            self.new_role(role.clone().into());
+        }
+        for row in preview.rows.iter().filter(|row| row.is_valid()) {
+            let id = match row.employee {
+                Some(id) => id,
+                None => self.new_employee(row.name.clone().into()),
+            };
+            if let Some(roles) = &row.roles {
+                let wanted: Vec<usize> = roles.iter().filter_map(|role| self.role_named(role)).collect();
+                let current = self.employees.get(&id).map(|emp| emp.roles.clone()).unwrap_or_default();
+                // Everyone can take lunch
+                for role in current.into_iter().filter(|role| *role != 2 && !wanted.contains(role)) {
+                    if let Err(e) = self.restrict_role(id, role) {
+                        self.report(e);
+                    }
+                }
+                for role in wanted {
+                    if let Err(e) = self.assign_role(id, role) {
+                        self.report(e);
+                    }
+                }
+            }
+            if let Some(shifts) = &row.shifts {
+                if let Err(e) = self.update_employee_shifts(id, shifts.clone()) {
+                    self.report(e);
+                }
+            }
+            if let Some(emp) = self.employees.get_mut(&id) {
+                emp.lunch = row.lunch.unwrap_or(emp.lunch);
+                emp.wage = row.wage.unwrap_or(emp.wage);
+            }
+        }
+        preview
+    }
+}
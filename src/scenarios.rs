@@ -0,0 +1,175 @@
+use std::rc::Rc;
+
+use chrono::TimeDelta;
+use yew::AttrValue;
+
+use crate::{data::{Business, BusinessError, Grid, Result, RoleTrait}, workspace::Workspace};
+
+/// A named copy of the business, for trying out changes without touching the plan
+#[derive(Clone, Debug)]
+pub struct Scenario {
+    pub name: AttrValue,
+    /// The scenario, or the plan while this scenario is the one being edited. Shared between copies of the workspace
+    /// until one of them changes it.
+    pub business: Rc<Business>,
+} impl PartialEq for Scenario {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && Rc::ptr_eq(&self.business, &other.business)
+    }
+}
+
+/// One employee doing something different at one block. Roles are as in [`Employee::block_role`], so 0 is clocked out
+/// and 1 is available.
+///
+/// [`Employee::block_role`]: crate::data::Employee::block_role
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BlockChange {
+    pub employee: usize,
+    pub block: usize,
+    pub before: usize,
+    pub after: usize,
+}
+
+/// How a scenario differs from the plan
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScenarioDiff {
+    /// Blocks covering both days' hours, at the plan's block size
+    pub grid: Grid,
+    /// In employee then block order
+    pub changes: Vec<BlockChange>,
+    /// Each of these is the scenario's less the plan's
+    pub paid: TimeDelta,
+    /// In cents
+    pub cost: i64,
+    /// Time single roles are worked
+    pub coverage: TimeDelta,
+}
+
+/// Time single roles are worked, other than lunch
+fn coverage(business: &Business) -> TimeDelta {
+    business.totals().roles.iter()
+        .filter(|(role, _)| business.roles.get(role).is_some_and(|role| !role.is_multi()))
+        .map(|(_, time)| *time)
+        .sum()
+}
+
+impl Workspace {
+    /// The plan, whether or not a scenario is being edited in its place
+    pub fn plan(&self) -> &Business {
+        match self.editing {
+            Some(index) => &self.scenarios[index].business,
+            None => &self.business,
+        }
+    }
+    pub fn plan_mut(&mut self) -> &mut Business {
+        match self.editing {
+            Some(index) => Rc::make_mut(&mut self.scenarios[index].business),
+            None => &mut self.business,
+        }
+    }
+    /// The business as the scenario has it
+    pub fn scenario(&self, index: usize) -> Option<&Business> {
+        match self.editing == Some(index) {
+            true => Some(&self.business),
+            false => self.scenarios.get(index).map(|scenario| &*scenario.business),
+        }
+    }
+
+    /// Copy whatever is being edited into a new scenario, and start editing it
+    pub fn new_scenario(&mut self, name: AttrValue) {
+        let name = match name.trim() {
+            "" => format!("Scenario {}", self.scenarios.len() + 1).into(),
+            _ => name,
+        };
+        let business = Rc::new(self.business.document());
+        self.scenarios.push(Scenario { name, business });
+        // Safe, as the scenario was just added
+        let _ = self.open_scenario(Some(self.scenarios.len() - 1));
+    }
+    /// Edit the scenario, or the plan if None. Whatever was being edited is kept as it was, but any absence being
+    /// covered is dropped, as it was found in the business left.
+    pub fn open_scenario(&mut self, scenario: Option<usize>) -> Result<()> {
+        if scenario.is_some_and(|index| index >= self.scenarios.len()) {
+            return Err(BusinessError::ScenarioNotFound);
+        }
+        if self.editing == scenario {
+            return Ok(());
+        }
+        self.absence = None;
+        if let Some(open) = self.editing.take() {
+            self.swap_scenario(open);
+        }
+        if let Some(index) = scenario {
+            self.swap_scenario(index);
+            self.editing = Some(index);
+        }
+        Ok(())
+    }
+    /// Make the scenario the plan. The old plan is kept as a scenario in its place, so the change can be undone.
+    pub fn promote_scenario(&mut self, index: usize) -> Result<()> {
+        self.open_scenario(Some(index))?;
+        // The old plan is already where the scenario was kept
+        self.editing = None;
+        self.scenarios[index].name = format!("Plan before {}", self.scenarios[index].name).into();
+        Ok(())
+    }
+    /// Discard the scenario, going back to the plan if it was being edited
+    pub fn delete_scenario(&mut self, index: usize) -> Result<()> {
+        if index >= self.scenarios.len() {
+            return Err(BusinessError::ScenarioNotFound);
+        }
+        if self.editing == Some(index) {
+            self.open_scenario(None)?;
+        }
+        self.scenarios.remove(index);
+        if let Some(open) = self.editing.as_mut() {
+            if *open > index {
+                *open -= 1;
+            }
+        }
+        Ok(())
+    }
+    /// Exchange what is being edited with what is kept for the scenario. Any notices stay put.
+    fn swap_scenario(&mut self, index: usize) {
+        let kept = Rc::make_mut(&mut self.scenarios[index].business);
+        std::mem::swap(&mut self.business, kept);
+        std::mem::swap(&mut self.business.notices, &mut kept.notices);
+    }
+
+    /// How the scenario differs from the plan, block by block and in total
+    pub fn compare_scenario(&self, index: usize) -> Result<ScenarioDiff> {
+        let plan = self.plan();
+        let scenario = self.scenario(index).ok_or(BusinessError::ScenarioNotFound)?;
+        let (open, close) = (plan.open.min(scenario.open), plan.close.max(scenario.close));
+        let mut grid = Grid { open, close, block_size: plan.block_size, blocks: 0 };
+        while grid.block_time(grid.blocks) < close && grid.block_time(grid.blocks) >= open {
+            grid.blocks += 1;
+        }
+
+        let mut employees: Vec<usize> = plan.employees.keys().chain(scenario.employees.keys()).copied().collect();
+        employees.sort();
+        employees.dedup();
+        let mut changes = vec![];
+        for employee in employees {
+            let role_at = |business: &Business, block: usize| match business.employees.get(&employee) {
+                Some(emp) if emp.scheduled => emp.block_role(&grid, block),
+                _ => 0,
+            };
+            for block in 0..grid.blocks {
+                let (before, after) = (role_at(plan, block), role_at(scenario, block));
+                if before != after {
+                    changes.push(BlockChange { employee, block, before, after });
+                }
+            }
+        }
+
+        let (plan_totals, scenario_totals) = (plan.totals(), scenario.totals());
+        Ok(ScenarioDiff {
+            grid,
+            changes,
+            paid: scenario_totals.paid() - plan_totals.paid(),
+            cost: scenario_totals.cost() as i64 - plan_totals.cost() as i64,
+            coverage: coverage(scenario) - coverage(plan),
+        })
+    }
+}
@@ -0,0 +1,93 @@
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+use crate::{absence::block_runs, events::BusinessEvents, totals::format_duration, BusinessContext};
+
+/// Mark someone absent for the day, then see what they leave uncovered and who could take it over
+#[function_component]
+pub fn AbsenceAssistant() -> Html {
+    let business = use_context::<BusinessContext>().expect("No ctx found");
+    let emp_ref = use_node_ref();
+
+    let mut scheduled: Vec<_> = business.employees.values().filter(|emp| emp.scheduled).collect();
+    scheduled.sort_by(|a, b| a.name.cmp(&b.name));
+    let options: Vec<Html> = scheduled.iter().map(|emp| html!(
+        <option value={emp.id.to_string()}>{emp.name.clone()}</option>
+    )).collect();
+
+    let absent_cb = {
+        let (business, emp_ref) = (business.clone(), emp_ref.clone());
+        Callback::from(move |_| {
+            if let Ok(employee) = emp_ref.cast::<HtmlSelectElement>().unwrap().value().parse() {
+                business.dispatch(BusinessEvents::MarkAbsent { employee });
+            }
+        })
+    };
+
+    let details = match &business.absence {
+        None => html!(),
+        Some(absence) => {
+            let name = business.employee_name(absence.employee);
+            let dismiss_cb = {
+                let business = business.clone();
+                Callback::from(move |_| business.dispatch(BusinessEvents::DismissAbsence))
+            };
+            let vacancies: Vec<Html> = absence.vacancies.iter().flat_map(|vacancy| {
+                block_runs(&vacancy.blocks).into_iter().map(|(first, last)| html!(
+                    <li>{format!("{}, {} to {}", business.role_name(vacancy.role),
+                        business.block_time(first).format("%-I:%M"), business.block_time(last + 1).format("%-I:%M"))}</li>
+                )).collect::<Vec<Html>>()
+            }).collect();
+            let replacements: Vec<Html> = business.replacements(absence).into_iter().map(|replacement| {
+                let onclick = {
+                    let (business, absence) = (business.clone(), absence.clone());
+                    Callback::from(move |_| business.dispatch(BusinessEvents::ApplyReplacement { employee: replacement.employee, absence: absence.clone() }))
+                };
+                html!(<tr key={replacement.employee}>
+                    <td>{business.employee_name(replacement.employee)}</td>
+                    <td>{format_duration(business.block_size * replacement.blocks as i32)}</td>
+                    <td>{replacement.roles}</td>
+                    <td>{format_duration(replacement.hours)}</td>
+                    <td><input type="button" value="Cover" onclick={onclick} /></td>
+                </tr>)
+            }).collect();
+            let suggestions = match (absence.is_covered(), replacements.is_empty()) {
+                (true, _) => html!(<p>{"Everything they were assigned is covered."}</p>),
+                (false, true) => html!(<p>{"Nobody else is free and able to cover what's left."}</p>),
+                (false, false) => html!(<table class="mui-table mui-table--bordered">
+                    <thead>
+                        <tr>
+                            <th>{"Replacement"}</th>
+                            <th>{"Can Cover"}</th>
+                            <th>{"Roles They Can Work"}</th>
+                            <th>{"Hours Today"}</th>
+                            <th></th>
+                        </tr>
+                    </thead>
+                    <tbody>{replacements}</tbody>
+                </table>),
+            };
+            html!(<div class="absence">
+                <p>{format!("{} is absent.", name)}</p>
+                {match absence.is_covered() {
+                    true => html!(),
+                    false => html!(<>{"Left uncovered:"}<ul>{vacancies}</ul></>),
+                }}
+                {suggestions}
+                <input type="button" value="Done" onclick={dismiss_cb} />
+            </div>)
+        },
+    };
+
+    html!(<div class="absence-assistant">
+        <label for="absent_select">{"Call-out: "}</label>
+        <select name="absent" id="absent_select" ref={emp_ref}>
+            {options}
+        </select>
+        <div class="tooltip" style="border-bottom: none;">
+            <input type="button" value="Mark Absent" onclick={absent_cb} />
+            <span class="tooltiptext">{"Take the employee off today's schedule, freeing everything they were assigned, then suggest who could cover it. The best suggestions can take the most of it, can work the most of their roles and have the fewest hours so far."}</span>
+        </div>
+        {details}
+    </div>)
+}
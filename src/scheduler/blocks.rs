@@ -1,13 +1,14 @@
 use std::str::FromStr;
 
 use log::error;
+use serde::{Deserialize, Serialize};
 use yew::prelude::*;
 
-use crate::{events::BusinessEvents, BusinessContext};
+use crate::{data::RunEdge, events::BusinessEvents, BusinessContext};
 
 pub type HeldBlock = UseStateHandle<TimeBlock>;
 
-#[derive(Clone, PartialEq, Debug, Default, Properties)]
+#[derive(Clone, PartialEq, Debug, Default, Properties, Serialize, Deserialize)]
 pub struct TimeBlock {
     pub emp_id: usize,
     pub time_index: usize,
@@ -23,13 +24,15 @@ pub struct TimeBlock {
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         let set: Vec<&str> = s.split(",").collect();
+        // Missing fields parse as empty and fail rather than panicking on drag data from elsewhere
+        let field = |i: usize| set.get(i).copied().unwrap_or_default();
         
         Ok(TimeBlock { 
-            emp_id: set[0].parse()?, 
-            time_index: set[1].parse()?,
-            role: set[2].parse()?,
-            len: set[3].parse()?,
-            len_index: set[4].parse()?,
+            emp_id: field(0).parse()?, 
+            time_index: field(1).parse()?,
+            role: field(2).parse()?,
+            len: field(3).parse()?,
+            len_index: field(4).parse()?,
         })
     }
 } impl ToString for TimeBlock {
@@ -38,44 +41,77 @@ pub struct TimeBlock {
     }
 }
 
-pub fn static_block(block: TimeBlock, style: Option<String>, business: BusinessContext, held_block: HeldBlock) -> Html {
+/// Grid cell attributes for a single block: its id and screen reader label, and whether the keyboard cursor is on it or
+/// it is part of the selection. Blocks in the role key leave these at their defaults.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct BlockAttrs {
+    pub id: Option<AttrValue>,
+    pub label: Option<AttrValue>,
+    pub cursor: bool,
+    pub selected: bool,
+    /// Background for a block only partly spent on its role, replacing the role's color
+    pub fill: Option<String>,
+} impl BlockAttrs {
+    pub fn classes(&self) -> Classes {
+        classes!(self.cursor.then_some("cursor"), self.selected.then_some("selected"))
+    }
+    pub fn role(&self) -> Option<AttrValue> {
+        self.id.as_ref().map(|_| "gridcell".into())
+    }
+    pub fn aria_selected(&self) -> Option<AttrValue> {
+        self.id.as_ref().map(|_| self.selected.to_string().into())
+    }
+}
+
+pub fn static_block(block: TimeBlock, style: Option<String>, business: BusinessContext, held_block: HeldBlock, attrs: BlockAttrs) -> Html {
 
     let ondrop = drop_handler(block, business, held_block.clone());
 
     html!(
-        <div class="time-block" style={style} ondragover={drag_over_handler} ondrop={ondrop}>
+        <div class={classes!("time-block", attrs.classes())} style={attrs.fill.clone().or(style)} ondragover={drag_over_handler} ondrop={ondrop}
+            id={attrs.id.clone()} role={attrs.role()} aria-label={attrs.label.clone()} aria-selected={attrs.aria_selected()}>
             {"s"}
         </div>
     )
 }
 
-pub fn drag_block(block: TimeBlock, style: Option<String>, business: BusinessContext, held_block: HeldBlock) -> Html {
+pub fn drag_block(block: TimeBlock, style: Option<String>, business: BusinessContext, held_block: HeldBlock, attrs: BlockAttrs, locked: bool) -> Html {
 
     let drag_start_handler = drag_start_wrapper(block.clone());
+    let handles = resize_handles(&block);
+    let lock = lock_toggle(&block, locked, business.clone());
     let ondrop = drop_handler(block, business, held_block.clone());
 
     html!(
-        <div class="time-block" style={style} draggable="true" ondragstart={drag_start_handler} ondragover={drag_over_handler} ondrop={ondrop}>
+        <div class={classes!("time-block", "run", attrs.classes())} style={attrs.fill.clone().or(style)} draggable="true" ondragstart={drag_start_handler} ondragover={drag_over_handler} ondrop={ondrop}
+            id={attrs.id.clone()} role={attrs.role()} aria-label={attrs.label.clone()} aria-selected={attrs.aria_selected()}>
             {"d"}
+            {handles}
+            {lock}
         </div>
     )
 }
 
-pub fn multi_block(mut block: TimeBlock, style: Option<String>, business: BusinessContext, held_block: HeldBlock) -> Html {
+/// `attrs` holds one entry per block of the run; missing entries fall back to the defaults
+pub fn multi_block(mut block: TimeBlock, style: Option<String>, business: BusinessContext, held_block: HeldBlock, attrs: Vec<BlockAttrs>, locked: bool) -> Html {
 
     let ondragstart = drag_start_wrapper(block.clone());
+    let handles = resize_handles(&block);
+    let lock = lock_toggle(&block, locked, business.clone());
 
     let mut single_blocks: Vec<Html> = vec![];
     let blocks = block.len.clone();
-    for _ in 0..blocks {
+    for i in 0..blocks {
         let onclick;
         {
             let block = block.clone();
             let held_block = held_block.clone();
             onclick = move |_| held_block.set(block.clone());
         }
+        let attrs = attrs.get(i).cloned().unwrap_or_default();
         single_blocks.push(html!(
-            <div class="time-block multi-block"  style={style.clone()} ondragover={drag_over_handler} ondrop={drop_handler(block.clone(), business.clone(), held_block.clone())} onmousedown={onclick}>
+            <div class={classes!("time-block", "multi-block", attrs.classes())} style={attrs.fill.clone().or(style.clone())} ondragover={drag_over_handler} ondrop={drop_handler(block.clone(), business.clone(), held_block.clone())} onmousedown={onclick}
+                id={attrs.id.clone()} role={attrs.role()} aria-label={attrs.label.clone()} aria-selected={attrs.aria_selected()}>
                 {"m"}
             </div>
         ));
@@ -84,12 +120,67 @@ pub fn multi_block(mut block: TimeBlock, style: Option<String>, business: Busine
     }
 
     html!(
-        <div draggable="true" ondragstart={ondragstart}>
+        <div class="run" draggable="true" ondragstart={ondragstart}>
             {single_blocks}
+            {handles}
+            {lock}
+        </div>
+    )
+}
+
+/// Display-only block for the read-only viewer; carries no drag or drop handlers
+pub fn view_block(style: Option<String>, label: AttrValue) -> Html {
+    html!(
+        <div class="time-block view-block" style={style}>
+            {label}
         </div>
     )
 }
 
+/// Grips on either end of an employee's run, dragged onto another block of the same row to lengthen or shorten it
+fn resize_handles(block: &TimeBlock) -> Html {
+    if block.emp_id == 0 {
+        return html!();
+    }
+    html!(<>
+        <div class="resize-handle start" draggable="true" ondragstart={resize_start_wrapper(block.clone(), RunEdge::Start)} title="Drag to resize"></div>
+        <div class="resize-handle end" draggable="true" ondragstart={resize_start_wrapper(block.clone(), RunEdge::End)} title="Drag to resize"></div>
+    </>)
+}
+
+/// Padlock on an employee's run, shown while the run is locked or hovered. Clicking it locks or unlocks the run, which
+/// automations then leave alone.
+fn lock_toggle(block: &TimeBlock, locked: bool, business: BusinessContext) -> Html {
+    if block.emp_id == 0 {
+        return html!();
+    }
+    let (employee, time_index) = (block.emp_id, block.time_index);
+    let onclick = move |ev: MouseEvent| {
+        ev.stop_propagation();
+        business.dispatch(BusinessEvents::ToggleLock { employee, block: time_index });
+    };
+    // Keep a multi block from picking up the run as held
+    let onmousedown = |ev: MouseEvent| ev.stop_propagation();
+    let (icon, title) = match locked {
+        true => ("\u{1F512}", "Locked; automations leave this alone. Click to unlock"),
+        false => ("\u{1F513}", "Click to lock, so automations leave this alone"),
+    };
+    html!(
+        <span class={classes!("lock", locked.then_some("locked"))} title={title} onclick={onclick} onmousedown={onmousedown}>{icon}</span>
+    )
+}
+
+fn resize_start_wrapper(block: TimeBlock, edge: RunEdge) -> impl Fn(DragEvent) {
+    move |ev| {
+        // Keep the run's own drag handler from treating this as a move
+        ev.stop_propagation();
+        match ev.data_transfer().unwrap().set_data("Resize", &format!("{};{}", block.to_string(), edge)) {
+            Ok(_) => (),
+            Err(_) => error!("Failed to set drag data transfer!"),
+        }
+    }
+}
+
 fn drag_start_wrapper(block: TimeBlock) -> impl Fn(DragEvent) {
     move |ev| {
         match ev.data_transfer().unwrap().set_data("TimeBlock", &block.to_string()) {
@@ -105,6 +196,16 @@ fn drag_over_handler(ev: DragEvent) {
 
 fn drop_handler(target_block: TimeBlock, business: BusinessContext, held_block: HeldBlock) -> impl Fn(DragEvent) {
     move |ev: DragEvent| {
+        if let Ok(resize) = ev.data_transfer().unwrap().get_data("Resize") {
+            if !resize.is_empty() {
+                let parsed = resize.split_once(";").and_then(|(run, edge)| Some((TimeBlock::from_str(run).ok()?, RunEdge::from_str(edge).ok()?)));
+                match parsed {
+                    Some((run, edge)) => business.dispatch(BusinessEvents::ResizeBlock { run, edge, target_block: target_block.clone() }),
+                    None => error!("Failed to convert resize data transfer {}", resize),
+                }
+                return;
+            }
+        }
         let drag_block;
         match ev.data_transfer().unwrap().get_data("TimeBlock") {
             Ok(x) => {
@@ -115,6 +216,14 @@ fn drop_handler(target_block: TimeBlock, business: BusinessContext, held_block:
             },
             Err(e) => {error!("Failed to get drag data transfer {:#?}", e); return;},
         }
-        business.dispatch(BusinessEvents::DragAssignBlock { target_block: target_block.clone(), drag_block, held_block: held_block.clone() });
+        // Alt+drop trades the dragged run's time range with the employee it was dropped on
+        if ev.alt_key() {
+            if drag_block.emp_id != 0 && target_block.emp_id != 0 {
+                let blocks = (drag_block.time_index..drag_block.time_index + drag_block.len.max(1)).collect();
+                business.dispatch(BusinessEvents::SwapBlocks { first: drag_block.emp_id, second: target_block.emp_id, blocks });
+            }
+            return;
+        }
+        business.dispatch(BusinessEvents::DragAssignBlock { target_block: target_block.clone(), drag_block, held_block: (*held_block).clone() });
     }
 }
\ No newline at end of file
@@ -4,11 +4,12 @@ use chrono::NaiveTime;
 use yew::prelude::*;
 use web_sys::{HtmlInputElement, HtmlSelectElement};
 
-use crate::{data::RoleTrait, events::BusinessEvents, BusinessContext};
+use crate::{data::RoleTrait, events::BusinessEvents, notifications::{Level, NotificationContext, NotificationEvents}, BusinessContext};
 
 #[function_component]
 pub fn Controls() -> Html {
     let business = use_context::<BusinessContext>().expect("No ctx found");
+    let notifications = use_context::<NotificationContext>().expect("Notification context not found");
 
     let mut scheduled = vec![];
     for (id, emp) in business.employees.iter() {
@@ -41,30 +42,38 @@ pub fn Controls() -> Html {
     let submit;
     {
         let business = business.clone();
+        let notifications = notifications.clone();
         let (emp_ref, role_ref, time_ref, block_ref) = (emp_ref.clone(), role_ref.clone(), time_ref.clone(), block_ref.clone());
 
         submit = Callback::from(move |_| {
-            let time = NaiveTime::from_str(&time_ref.cast::<HtmlInputElement>().unwrap().value()).unwrap();
-            let blocks_input: usize = block_ref.cast::<HtmlInputElement>().unwrap().value().parse().unwrap();
-            let mut curr_time = business.open.clone();
-            let mut i = 0;
-            loop {
-                if curr_time >= time {
-                    break;
-                }
-                i += 1;
-                curr_time += business.block_size;
+            let reject = |message: &str| notifications.dispatch(NotificationEvents::Push { level: Level::Error, message: message.to_string().into() });
+            let time = match NaiveTime::from_str(&time_ref.cast::<HtmlInputElement>().unwrap().value()) {
+                Ok(time) => time,
+                Err(_) => return reject("Enter a start time to assign"),
+            };
+            let blocks_input: usize = match block_ref.cast::<HtmlInputElement>().unwrap().value().parse() {
+                Ok(blocks) => blocks,
+                Err(_) => return reject("Enter a whole number of blocks to assign"),
+            };
+            let (employee, role) = match (
+                emp_ref.cast::<HtmlSelectElement>().unwrap().value().parse(),
+                role_ref.cast::<HtmlSelectElement>().unwrap().value().parse()
+            ) {
+                (Ok(employee), Ok(role)) => (employee, role),
+                _ => return reject("Select an employee and a role to assign"),
+            };
+            if time < business.open || time >= business.close {
+                return reject("Start time is outside business hours");
             }
-            let mut blocks = vec![];
-            for _ in 0..blocks_input {
-                blocks.push(i.clone());
-                i += 1
-            }
-            business.dispatch(BusinessEvents::AssignBlock {
-                employee: emp_ref.cast::<HtmlSelectElement>().unwrap().value().parse().unwrap(),
-                role: role_ref.cast::<HtmlSelectElement>().unwrap().value().parse().unwrap(),
-                blocks
-            });
+            // Starts exactly at the given time, for as many blocks' worth of time as asked
+            let end = i32::try_from(blocks_input).ok()
+                .and_then(|blocks| business.block_size.checked_mul(blocks))
+                .and_then(|length| match time.overflowing_add_signed(length) {
+                    (end, 0) => Some(end.min(business.close)),
+                    _ => None,
+                })
+                .unwrap_or(business.close);
+            business.dispatch(BusinessEvents::AssignSpan { employee, role, start: time, end });
             let _ = emp_ref.cast::<HtmlSelectElement>().unwrap().focus();
         })
     }
@@ -81,7 +90,7 @@ pub fn Controls() -> Html {
         </select>
 
         <label for="time">{"Time:"}</label>
-        <input id="time" type="time" name="time" step="1800" min={business.open.format("%H:%M").to_string()} max={business.close.format("%H:%M").to_string()} value={business.open.format("%H:%M").to_string()} ref={time_ref}/>
+        <input id="time" type="time" name="time" step="60" min={business.open.format("%H:%M").to_string()} max={business.close.format("%H:%M").to_string()} value={business.open.format("%H:%M").to_string()} ref={time_ref}/>
 
         <label for="blocks">{"Blocks:"}</label>
         <input id="blocks" type="number" name="blocks" min="1" value="4" ref={block_ref} />
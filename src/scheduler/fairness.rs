@@ -0,0 +1,68 @@
+use chrono::TimeDelta;
+use yew::prelude::*;
+
+use crate::{events::BusinessEvents, totals::format_duration, BusinessContext, SettingsContext};
+
+/// How much time each scheduled employee has on each single role, and how often they switch roles, so uneven shares
+/// stand out after filling roles
+#[function_component]
+pub fn FairnessSummary() -> Html {
+    let business = use_context::<BusinessContext>().expect("No ctx found");
+    let settings = use_context::<SettingsContext>().expect("Settings context not found");
+    let week = settings.app.fairness_week;
+    let fairness = business.fairness(week);
+
+    let headers: Vec<Html> = fairness.roles.iter().map(|role| html!(<th>{business.role_name(*role)}</th>)).collect();
+    let rows: Vec<Html> = fairness.employees.iter().map(|emp| {
+        let cells: Vec<Html> = emp.minutes.iter().map(|minutes| match minutes {
+            Some(minutes) => html!(<td>{format_duration(TimeDelta::minutes(*minutes))}</td>),
+            None => html!(<td class="not-qualified">{"\u{2014}"}</td>),
+        }).collect();
+        let over = settings.app.max_role_switches > 0 && emp.switches > settings.app.max_role_switches;
+        html!(<tr key={emp.employee}>
+            <td>{business.employee_name(emp.employee)}</td>
+            {cells}
+            <td class={classes!(over.then_some("over-limit"))}>{emp.switches}</td>
+        </tr>)
+    }).collect();
+    let spreads: Vec<Html> = fairness.roles.iter().map(|role| html!(
+        <th>{format_duration(TimeDelta::minutes(fairness.spread(*role)))}</th>
+    )).collect();
+
+    let record_cb = {
+        let business = business.clone();
+        Callback::from(move |_| business.dispatch(BusinessEvents::RecordDay))
+    };
+    let clear_cb = {
+        let business = business.clone();
+        Callback::from(move |_| business.dispatch(BusinessEvents::ClearWeek))
+    };
+
+    html!(<div class="fairness">
+        <table class="mui-table mui-table--bordered">
+            <thead>
+                <tr>
+                    <th>{if week {"Employee (week)"} else {"Employee (today)"}}</th>
+                    {headers}
+                    <th>{"Role Switches"}</th>
+                </tr>
+            </thead>
+            <tbody>
+                {rows}
+                <tr>
+                    <th><div class="tooltip">
+                        {"Spread \u{24D8}"}
+                        <span class="tooltiptext">{"The difference between the most and least time anyone who can work the role has on it"}</span>
+                    </div></th>
+                    {spreads}
+                    <td></td>
+                </tr>
+            </tbody>
+        </table>
+        <p>
+            {format!("Days in the week so far: {} ", business.days_recorded)}
+            <input type="button" value="Add Today to the Week" onclick={record_cb} />
+            <input type="button" value="Start a New Week" onclick={clear_cb} />
+        </p>
+    </div>)
+}
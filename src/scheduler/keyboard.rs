@@ -0,0 +1,133 @@
+use std::ops::RangeInclusive;
+
+use yew::prelude::*;
+
+use crate::{bulk::GridSelection, data::{Role, RoleTrait}, events::BusinessEvents, scheduler::selection::Clipboard, BusinessContext};
+
+pub type CursorHandle = UseStateHandle<GridCursor>;
+
+/// Keyboard cursor over the schedule grid. Rows are indexes into the table's current (sorted) list of employees, and
+/// blocks are time indexes.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct GridCursor {
+    pub row: usize,
+    pub block: usize,
+    /// The opposite corner of the selection, set while extending with Shift
+    pub anchor: Option<(usize, usize)>,
+} impl GridCursor {
+    pub fn rows(&self) -> RangeInclusive<usize> {
+        let (anchor_row, _) = self.anchor.unwrap_or((self.row, self.block));
+        anchor_row.min(self.row)..=anchor_row.max(self.row)
+    }
+    pub fn blocks(&self) -> RangeInclusive<usize> {
+        let (_, anchor_block) = self.anchor.unwrap_or((self.row, self.block));
+        anchor_block.min(self.block)..=anchor_block.max(self.block)
+    }
+    pub fn contains(&self, row: usize, block: usize) -> bool {
+        self.rows().contains(&row) && self.blocks().contains(&block)
+    }
+    /// The selected employees and blocks, given the employee id of each row in display order
+    pub fn selection(&self, rows: &[usize]) -> GridSelection {
+        GridSelection {
+            employees: self.rows().filter_map(|row| rows.get(row).copied()).collect(),
+            blocks: self.blocks().collect(),
+        }
+    }
+    /// Top-left corner of the selection
+    pub fn start(&self) -> (usize, usize) {
+        (*self.rows().start(), *self.blocks().start())
+    }
+
+    /// Move the cursor by the given offsets, clamped to the grid. Extending keeps (or starts) a selection from the
+    /// current position; otherwise any selection is dropped.
+    pub fn moved(self, rows: isize, blocks: isize, extend: bool, row_count: usize, block_count: usize) -> Self {
+        let anchor = match extend {
+            true => Some(self.anchor.unwrap_or((self.row, self.block))),
+            false => None,
+        };
+        GridCursor {
+            row: self.row.saturating_add_signed(rows).min(row_count.saturating_sub(1)),
+            block: self.block.saturating_add_signed(blocks).min(block_count.saturating_sub(1)),
+            anchor,
+        }
+    }
+}
+
+/// Handle a key press on the schedule grid. `rows` is the employee id of each row, in display order.
+///
+/// - Arrows move the cursor, Shift+arrows extend the selection
+/// - Home/End jump to the first or last block of the day
+/// - 1-9 assign the Nth role of the role key to the selection
+/// - Delete/Backspace clear the selection
+/// - L locks or unlocks the run under the cursor
+/// - Ctrl+C copies the selection, Ctrl+V pastes it at the cursor
+/// - Escape drops the selection
+pub fn grid_keydown(e: KeyboardEvent, cursor: CursorHandle, clipboard: Clipboard, business: BusinessContext, rows: &[usize]) {
+    let curr = *cursor;
+    let extend = e.shift_key();
+    let (row_count, block_count) = (rows.len(), business.blocks);
+    let next = match e.key().as_str() {
+        "ArrowLeft" => curr.moved(0, -1, extend, row_count, block_count),
+        "ArrowRight" => curr.moved(0, 1, extend, row_count, block_count),
+        "ArrowUp" => curr.moved(-1, 0, extend, row_count, block_count),
+        "ArrowDown" => curr.moved(1, 0, extend, row_count, block_count),
+        "Home" => curr.moved(0, -(curr.block as isize), extend, row_count, block_count),
+        "End" => curr.moved(0, block_count as isize, extend, row_count, block_count),
+        "Escape" => GridCursor { anchor: None, ..curr },
+        "Delete" | "Backspace" => {
+            business.dispatch(BusinessEvents::BulkClear { selection: curr.selection(rows) });
+            curr
+        },
+        "l" | "L" if !e.ctrl_key() && !e.meta_key() => {
+            if let Some(employee) = rows.get(curr.row) {
+                business.dispatch(BusinessEvents::ToggleLock { employee: *employee, block: curr.block });
+            }
+            curr
+        },
+        "c" if e.ctrl_key() || e.meta_key() => {
+            clipboard.set(Some(business.copy_blocks(&curr.selection(rows))));
+            curr
+        },
+        "v" if e.ctrl_key() || e.meta_key() => {
+            if let Some(pattern) = clipboard.as_ref() {
+                let (row, start) = curr.start();
+                business.dispatch(BusinessEvents::PasteBlocks { employees: rows[row.min(rows.len())..].to_vec(), start, pattern: pattern.clone() });
+            }
+            curr
+        },
+        key => {
+            let role = match key.parse::<usize>() {
+                Ok(n @ 1..=9) => key_roles(&business).get(n - 1).map(|role| role.id()),
+                _ => None,
+            };
+            match role {
+                Some(role) => {
+                    business.dispatch(BusinessEvents::BulkAssign { selection: curr.selection(rows), role });
+                    curr
+                },
+                // Leave every other key (Tab, shortcuts, etc.) to the browser
+                None => return,
+            }
+        },
+    };
+    e.prevent_default();
+    cursor.set(next);
+}
+
+/// Roles in the order they appear in the role key, which is the order used by the number keys
+pub fn key_roles(business: &BusinessContext) -> Vec<&Role> {
+    let mut roles: Vec<&Role> = business.roles.values().collect();
+    roles.sort();
+    roles
+}
+
+/// DOM id of a grid cell, used as the grid's `aria-activedescendant`
+pub fn cell_id(emp_id: usize, block: usize) -> AttrValue {
+    format!("cell-{}-{}", emp_id, block).into()
+}
+
+/// Reverse of [`cell_id`], giving (employee id, block)
+pub fn parse_cell_id(id: &str) -> Option<(usize, usize)> {
+    let (emp_id, block) = id.strip_prefix("cell-")?.split_once("-")?;
+    Some((emp_id.parse().ok()?, block.parse().ok()?))
+}
@@ -1,7 +1,22 @@
+pub mod absence;
 pub mod blocks;
 pub mod controls;
+pub mod fairness;
+pub mod keyboard;
+pub mod scenarios;
+pub mod selection;
+pub mod share;
+pub mod sync;
 pub mod table;
+pub mod totals;
+pub mod trace;
 
+pub use absence::AbsenceAssistant;
 pub use blocks::TimeBlock;
 pub use controls::Controls;
-pub use table::{Table, ScheduleCopy};
\ No newline at end of file
+pub use fairness::FairnessSummary;
+pub use scenarios::Scenarios;
+pub use share::ShareLink;
+pub use sync::SyncPanel;
+pub use table::{Table, ScheduleCopy};
+pub use totals::ScheduleTotals;
\ No newline at end of file
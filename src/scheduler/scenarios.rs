@@ -0,0 +1,183 @@
+use std::ops::Deref;
+
+use chrono::TimeDelta;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::{data::Business, events::BusinessEvents, scenarios::ScenarioDiff, totals::{format_duration, format_money}, workspace::Workspace, BusinessContext, SettingsContext};
+
+/// Business hours are kept in the settings, so they follow whichever version of the business is being edited
+pub fn follow_hours(settings: &SettingsContext, target: &Business) {
+    let mut new_settings = settings.deref().clone();
+    new_settings.app.business_set(target.open, target.close, target.block_size);
+    settings.set(new_settings);
+}
+
+/// What an employee is doing at a block, as given by [`crate::data::Employee::block_role`]
+fn describe_activity(business: &Business, role: usize) -> String {
+    match role {
+        0 => "out".into(),
+        1 => "available".into(),
+        role => business.role_name(role),
+    }
+}
+
+/// A difference with its sign, e.g. "+1:30" or "-0:30"
+fn signed_duration(delta: TimeDelta) -> String {
+    match delta < TimeDelta::zero() {
+        true => format!("-{}", format_duration(-delta)),
+        false => format!("+{}", format_duration(delta)),
+    }
+}
+
+fn signed_money(cents: i64) -> String {
+    match cents < 0 {
+        true => format!("-{}", format_money(cents.unsigned_abs() as usize)),
+        false => format!("+{}", format_money(cents as usize)),
+    }
+}
+
+/// Named copies of the business for trying changes without losing the plan, with a comparison of any one of them
+/// against the plan
+#[function_component]
+pub fn Scenarios() -> Html {
+    let business = use_context::<BusinessContext>().expect("No ctx found");
+    let settings = use_context::<SettingsContext>().expect("Settings context not found");
+    let compared = use_state_eq(|| None::<usize>);
+    let name_ref = use_node_ref();
+
+    let new_cb = {
+        let (business, name_ref) = (business.clone(), name_ref.clone());
+        Callback::from(move |_| {
+            let input = name_ref.cast::<HtmlInputElement>().unwrap();
+            business.dispatch(BusinessEvents::NewScenario { name: input.value().into() });
+            input.set_value("");
+        })
+    };
+    let plan_cb = {
+        let (business, settings) = (business.clone(), settings.clone());
+        Callback::from(move |_| {
+            follow_hours(&settings, business.plan());
+            business.dispatch(BusinessEvents::OpenScenario { scenario: None });
+        })
+    };
+
+    let rows: Vec<Html> = business.scenarios.iter().enumerate().map(|(index, scenario)| {
+        let editing = business.editing == Some(index);
+        let open_cb = {
+            let (business, settings) = (business.clone(), settings.clone());
+            Callback::from(move |_| {
+                if let Some(target) = business.scenario(index) {
+                    follow_hours(&settings, target);
+                }
+                business.dispatch(BusinessEvents::OpenScenario { scenario: Some(index) });
+            })
+        };
+        let compare_cb = {
+            let compared = compared.clone();
+            Callback::from(move |_| compared.set(match *compared == Some(index) {
+                true => None,
+                false => Some(index),
+            }))
+        };
+        let promote_cb = {
+            let (business, settings) = (business.clone(), settings.clone());
+            Callback::from(move |_| {
+                if let Some(target) = business.scenario(index) {
+                    follow_hours(&settings, target);
+                }
+                business.dispatch(BusinessEvents::PromoteScenario { scenario: index });
+            })
+        };
+        let delete_cb = {
+            let (business, settings, compared) = (business.clone(), settings.clone(), compared.clone());
+            Callback::from(move |_| {
+                if business.editing == Some(index) {
+                    follow_hours(&settings, business.plan());
+                }
+                compared.set(None);
+                business.dispatch(BusinessEvents::DeleteScenario { scenario: index });
+            })
+        };
+        html!(<tr key={index} class={classes!(editing.then_some("editing"))}>
+            <td>{scenario.name.clone()}{if editing {" (editing)"} else {""}}</td>
+            <td>
+                <input type="button" value="Edit" disabled={editing} onclick={open_cb} />
+                <input type="button" value={if *compared == Some(index) {"Hide Changes"} else {"Compare"}} onclick={compare_cb} />
+                <input type="button" value="Make Plan" onclick={promote_cb} />
+                <input type="button" value="Delete" onclick={delete_cb} />
+            </td>
+        </tr>)
+    }).collect();
+
+    let editing = match business.editing {
+        Some(index) => html!(<p>
+            {format!("Editing scenario \"{}\"; the plan is kept as it was. ", business.scenarios[index].name)}
+            <input type="button" value="Back to Plan" onclick={plan_cb} />
+        </p>),
+        None => html!(<p>{"Editing the plan"}</p>),
+    };
+    let diff = match (*compared).map(|index| business.compare_scenario(index)) {
+        Some(Ok(diff)) => scenario_diff(&business, (*compared).unwrap(), &diff),
+        _ => html!(),
+    };
+
+    html!(<div class="scenarios">
+        {editing}
+        <table class="mui-table mui-table--bordered">
+            <tbody>
+                {rows}
+                <tr>
+                    <td><input type="text" placeholder="e.g. Employee 4 calls out" ref={name_ref} /></td>
+                    <td><div class="tooltip" style="border-bottom: none;">
+                        <input type="button" value="New Scenario" onclick={new_cb} />
+                        <span class="tooltiptext">{"Copy what you are editing into a new scenario and switch to it. Changes to a scenario don't touch the plan until it is made the plan; the old plan is then kept as a scenario."}</span>
+                    </div></td>
+                </tr>
+            </tbody>
+        </table>
+        {diff}
+    </div>)
+}
+
+/// Totals, then a grid of the blocks where anyone is doing something different in the scenario than in the plan
+fn scenario_diff(workspace: &Workspace, index: usize, diff: &ScenarioDiff) -> Html {
+    let (plan, scenario) = (workspace.plan(), workspace.scenario(index).unwrap_or(workspace));
+    let summary = format!("Compared with the plan: paid hours {}, cost {}, time roles are covered {}",
+        signed_duration(diff.paid), signed_money(diff.cost), signed_duration(diff.coverage));
+    if diff.changes.is_empty() {
+        return html!(<div class="scenario-diff"><p>{summary}</p><p>{"Nobody's blocks are different."}</p></div>);
+    }
+
+    let headers: Vec<Html> = (0..diff.grid.blocks).map(|block| html!(
+        <th>{diff.grid.block_time(block).format("%-I:%M").to_string()}</th>
+    )).collect();
+    let mut employees: Vec<usize> = diff.changes.iter().map(|change| change.employee).collect();
+    employees.dedup();
+    let rows: Vec<Html> = employees.into_iter().map(|employee| {
+        let name = match scenario.employees.contains_key(&employee) {
+            true => scenario.employee_name(employee),
+            false => plan.employee_name(employee),
+        };
+        let cells: Vec<Html> = (0..diff.grid.blocks).map(|block| {
+            match diff.changes.iter().find(|change| change.employee == employee && change.block == block) {
+                Some(change) => {
+                    let title = format!("{}: {} in the plan, {} in the scenario", diff.grid.block_time(block).format("%-I:%M"),
+                        describe_activity(plan, change.before), describe_activity(scenario, change.after));
+                    let color = scenario.role_colors.get(&change.after).map(|color| format!("background-color: {};", color));
+                    html!(<td class="diff-changed" title={title} style={color}></td>)
+                },
+                None => html!(<td></td>),
+            }
+        }).collect();
+        html!(<tr key={employee}><td>{name}</td>{cells}</tr>)
+    }).collect();
+
+    html!(<div class="scenario-diff">
+        <p>{summary}</p>
+        <table class="mui-table mui-table--bordered">
+            <thead><tr><th>{"Employee"}</th>{headers}</tr></thead>
+            <tbody>{rows}</tbody>
+        </table>
+    </div>)
+}
@@ -0,0 +1,124 @@
+use web_sys::{Element, HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::{bulk::BlockPattern, data::{RoleTrait, LUNCH_ROLE}, events::BusinessEvents, scheduler::keyboard::*, BusinessContext};
+
+/// Blocks copied from the grid, waiting to be pasted
+pub type Clipboard = UseStateHandle<Option<BlockPattern>>;
+
+/// Grid cell (row, block) under the mouse, if any
+fn target_cell(e: &MouseEvent, rows: &[usize]) -> Option<(usize, usize)> {
+    let (emp_id, block) = parse_cell_id(&e.target_dyn_into::<Element>()?.id())?;
+    let row = rows.iter().position(|id| *id == emp_id)?;
+    Some((row, block))
+}
+
+/// Pressing on a cell moves the cursor there; Shift+click extends the selection to it instead
+pub fn grid_mousedown(e: MouseEvent, cursor: CursorHandle, rows: &[usize]) {
+    let (row, block) = match target_cell(&e, rows) {
+        Some(cell) => cell,
+        None => return,
+    };
+    let anchor = match e.shift_key() {
+        true => Some(cursor.anchor.unwrap_or((cursor.row, cursor.block))),
+        false => None,
+    };
+    cursor.set(GridCursor { row, block, anchor });
+}
+
+/// Dragging across cells with the button held stretches a rubber-band selection from where it was pressed
+pub fn grid_mouseover(e: MouseEvent, cursor: CursorHandle, rows: &[usize]) {
+    if e.buttons() & 1 == 0 {
+        return;
+    }
+    let (row, block) = match target_cell(&e, rows) {
+        Some(cell) => cell,
+        None => return,
+    };
+    let anchor = Some(cursor.anchor.unwrap_or((cursor.row, cursor.block)));
+    cursor.set(GridCursor { row, block, anchor });
+}
+
+/// Bulk actions on the current grid selection. Each button dispatches a single event, so the whole selection changes
+/// together.
+pub fn selection_toolbar(business: BusinessContext, cursor: CursorHandle, clipboard: Clipboard, rows: &[usize], role_ref: NodeRef, shift_ref: NodeRef) -> Html {
+    let selection = cursor.selection(rows);
+
+    let mut role_options = vec![];
+    for role in key_roles(&business) {
+        role_options.push(html!(
+            <option value={role.id().to_string()}>{role.name()}</option>
+        ));
+    }
+
+    let assign_cb = {
+        let (business, selection, role_ref) = (business.clone(), selection.clone(), role_ref.clone());
+        Callback::from(move |_| {
+            let role = match role_ref.cast::<HtmlSelectElement>().unwrap().value().parse() {
+                Ok(role) => role,
+                Err(_) => return,
+            };
+            business.dispatch(BusinessEvents::BulkAssign { selection: selection.clone(), role });
+        })
+    };
+    let lunch_cb = {
+        let (business, selection) = (business.clone(), selection.clone());
+        Callback::from(move |_| business.dispatch(BusinessEvents::BulkAssign { selection: selection.clone(), role: LUNCH_ROLE }))
+    };
+    let clear_cb = {
+        let (business, selection) = (business.clone(), selection.clone());
+        Callback::from(move |_| business.dispatch(BusinessEvents::BulkClear { selection: selection.clone() }))
+    };
+    let copy_cb = {
+        let (business, selection, clipboard) = (business.clone(), selection.clone(), clipboard.clone());
+        Callback::from(move |_| clipboard.set(Some(business.copy_blocks(&selection))))
+    };
+    let paste_cb = {
+        let (business, clipboard) = (business.clone(), clipboard.clone());
+        let (row, start) = cursor.start();
+        let employees = rows[row.min(rows.len())..].to_vec();
+        Callback::from(move |_| {
+            if let Some(pattern) = clipboard.as_ref() {
+                business.dispatch(BusinessEvents::PasteBlocks { employees: employees.clone(), start, pattern: pattern.clone() });
+            }
+        })
+    };
+    let shift_cb = |direction: isize| {
+        let (business, selection, shift_ref) = (business.clone(), selection.clone(), shift_ref.clone());
+        Callback::from(move |_| {
+            let blocks: isize = match shift_ref.cast::<HtmlInputElement>().unwrap().value().parse() {
+                Ok(blocks) => blocks,
+                Err(_) => return,
+            };
+            business.dispatch(BusinessEvents::ShiftBlocks { selection: selection.clone(), offset: blocks * direction });
+        })
+    };
+
+    let (shift_left_cb, shift_right_cb) = (shift_cb(-1), shift_cb(1));
+    let swap_cb = {
+        let (business, selection) = (business.clone(), selection.clone());
+        Callback::from(move |_| {
+            if let [first, second] = selection.employees[..] {
+                business.dispatch(BusinessEvents::SwapBlocks { first, second, blocks: selection.blocks.clone() });
+            }
+        })
+    };
+
+    let summary = format!("{} employee(s) \u{00D7} {} block(s) selected", selection.employees.len(), selection.blocks.len());
+
+    html!(<div class="controls selection-controls">
+        <p>{summary}</p>
+        <select name="bulk_role" aria-label="Role to assign" ref={role_ref}>
+            {role_options}
+        </select>
+        <input type="button" value="Assign" onclick={assign_cb} />
+        <input type="button" value="Lunch" onclick={lunch_cb} />
+        <input type="button" value="Clear" onclick={clear_cb} />
+        <input type="button" value="Copy" onclick={copy_cb} />
+        <input type="button" value="Paste" onclick={paste_cb} disabled={clipboard.is_none()} />
+        <input type="button" value="Swap" title="Trade assignments between the two selected employees" onclick={swap_cb} disabled={selection.employees.len() != 2} />
+        <input type="button" value="\u{25C0} Shift" onclick={shift_left_cb} />
+        <input type="number" name="shift_blocks" aria-label="Blocks to shift by" min="1" value="1" ref={shift_ref} />
+        <input type="button" value="Shift \u{25B6}" onclick={shift_right_cb} />
+    </div>)
+}
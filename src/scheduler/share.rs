@@ -0,0 +1,50 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::{persistence::{schedule_to_csv, share_link}, BusinessContext};
+
+/// Generates a link to the current schedule which opens in the read-only viewer, for sending to staff
+#[function_component]
+pub fn ShareLink() -> Html {
+    let business = use_context::<BusinessContext>().expect("No ctx found");
+    let link = use_state_eq(String::new);
+
+    let mut scheduled = vec![];
+    for (id, emp) in business.employees.iter() {
+        if emp.scheduled {
+            scheduled.push((*id, emp.name.clone()));
+        }
+    }
+    scheduled.sort_by(|a, b| a.1.cmp(&b.1));
+    let mut emp_options = vec![html!(<option value="">{"Everyone"}</option>)];
+    for (id, name) in scheduled {
+        emp_options.push(html!(
+            <option value={id.to_string()}>{name}</option>
+        ));
+    }
+
+    let emp_ref = use_node_ref();
+    let onclick = {
+        let business = business.clone();
+        let link = link.clone();
+        let emp_ref = emp_ref.clone();
+        Callback::from(move |_| {
+            let employee = emp_ref.cast::<HtmlSelectElement>().unwrap().value().parse().ok();
+            link.set(share_link(&schedule_to_csv(&business), employee));
+        })
+    };
+    let select_all = Callback::from(|e: FocusEvent| {
+        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+            input.select();
+        }
+    });
+
+    html!(<div>
+        <label for="share_select">{"Share read-only schedule for:"}</label>
+        <select name="share" id="share_select" ref={emp_ref}>
+            {emp_options}
+        </select>
+        <input type="button" value="Create Link" onclick={onclick} />
+        <input readonly=true value={(*link).clone()} onfocus={select_all} />
+    </div>)
+}
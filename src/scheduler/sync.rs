@@ -0,0 +1,68 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::{events::BusinessEvents, notifications::{Level, NotificationContext, NotificationEvents}, persistence::read_editor, sync::{connect, disconnect, SyncEvent, DEFAULT_RELAY}, BusinessContext};
+
+/// Connect to a relay server to edit the plan live with others, and see who else is editing
+#[function_component]
+pub fn SyncPanel() -> Html {
+    let business = use_context::<BusinessContext>().expect("No ctx found");
+    let notifications = use_context::<NotificationContext>().expect("Notification context not found");
+    let url_ref = use_node_ref();
+
+    let sync = match &business.sync {
+        Some(sync) => sync,
+        None => {
+            let connect_cb = {
+                let (business, url_ref) = (business.clone(), url_ref.clone());
+                Callback::from(move |_| {
+                    let url = url_ref.cast::<HtmlInputElement>().unwrap().value().trim().to_string();
+                    match connect(&url, business.clone()) {
+                        Ok(()) => business.dispatch(BusinessEvents::Sync(SyncEvent::Connecting { url, name: read_editor().unwrap_or_default() })),
+                        Err(e) => notifications.dispatch(NotificationEvents::Push { level: Level::Error, message: format!("Couldn't connect to {}; {}", url, e).into() }),
+                    }
+                })
+            };
+            return html!(<div class="sync">
+                <label for="sync_url">{"Edit live with others through: "}</label>
+                <input type="text" id="sync_url" value={DEFAULT_RELAY} ref={url_ref} />
+                <div class="tooltip" style="border-bottom: none;">
+                    <input type="button" value="Connect" onclick={connect_cb} />
+                    <span class="tooltiptext">{"Share every change to the plan with everyone connected to the same address, through a relay server (run the daily-planner-relay program). The path picks the room, e.g. ws://localhost:9031/store-12. Whoever starts a room shares their plan with everyone who joins."}</span>
+                </div>
+            </div>);
+        },
+    };
+
+    let disconnect_cb = {
+        let business = business.clone();
+        Callback::from(move |_| {
+            disconnect();
+            business.dispatch(BusinessEvents::Sync(SyncEvent::Stop));
+        })
+    };
+    let status = match sync.client {
+        None => format!("Connecting to {}...", sync.url),
+        Some(_) if sync.pending.is_empty() => format!("Editing live through {}.", sync.url),
+        Some(_) => format!("Editing live through {}; {} change(s) waiting to be confirmed.", sync.url, sync.pending.len()),
+    };
+    let peers: Vec<Html> = sync.peers.iter().map(|(client, presence)| {
+        let employee = presence.employee.map(|employee| format!(" ({})", business.employee_name(employee)));
+        html!(<li key={*client}><b>{sync.peer_name(*client)}</b>{": "}{presence.action.clone()}{employee}</li>)
+    }).collect();
+
+    html!(<div class="sync">
+        <p>
+            {status}{" "}
+            <input type="button" value="Disconnect" onclick={disconnect_cb} />
+        </p>
+        {match business.editing {
+            Some(_) => html!(<p>{"Changes to a scenario aren't shared until it is made the plan."}</p>),
+            None => html!(),
+        }}
+        {match peers.is_empty() {
+            true => html!(<p>{"Nobody else is editing."}</p>),
+            false => html!(<>{"Also editing:"}<ul>{peers}</ul></>),
+        }}
+    </div>)
+}
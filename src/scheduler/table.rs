@@ -1,12 +1,12 @@
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
-use crate::{data::*, events::BusinessEvents, persistence::schedule_to_csv, scheduler::blocks::*, BusinessContext, SettingsContext, Sort};
+use crate::{data::*, events::BusinessEvents, persistence::schedule_to_csv, scheduler::{blocks::*, keyboard::*, selection::*, trace::*}, BusinessContext, SettingsContext, Sort, ViewMode};
 
-fn table_header(business: UseReducerHandle<Business>) -> Html {
+fn table_header(business: BusinessContext) -> Html {
     let mut table_header = vec![];
     let mut curr_time = business.open.clone();
     table_header.push(html!(
-        <th>
+        <th role="columnheader">
         </th>
     ));
     loop {
@@ -14,14 +14,14 @@ fn table_header(business: UseReducerHandle<Business>) -> Html {
             break;
         }
         table_header.push(html!(
-            <th>
+            <th role="columnheader">
                 {curr_time.format("%-I:%M").to_string()}
             </th>
         ));
         curr_time += business.block_size;
     }
     html!(
-        <tr>
+        <tr role="row">
             {table_header}
         </tr>
     )
@@ -32,21 +32,36 @@ pub fn Table() -> Html {
     let business = use_context::<BusinessContext>().expect("No ctx found");
     let settings = use_context::<SettingsContext>().expect("No settings context found");
     let sort = use_context::<Sort>().expect("Sort context not found");
+    let view = use_context::<ViewMode>().expect("View mode context not found");
     let held_block = use_state_eq(|| TimeBlock::default());
+    let cursor_handle = use_state_eq(GridCursor::default);
+    let clipboard = use_state_eq(|| None);
+    let (bulk_role_ref, shift_ref) = (use_node_ref(), use_node_ref());
+    let hovered = use_state_eq(|| None::<TraceTarget>);
 
     let table_header = table_header(business.clone());
 
-    let mut emp_rows = vec![];
-    for (_id,employee) in business.employees.iter() {
+    let mut employees = vec![];
+    for (id,employee) in business.employees.iter() {
         if !employee.scheduled {
             continue;
         }
-        emp_rows.push((employee, employee.make_row(business.clone(), held_block.clone())));
+        if let ViewMode::ReadOnly { employee: Some(only) } = view {
+            if only != *id {
+                continue;
+            }
+        }
+        employees.push(employee);
     }
-    emp_rows.sort_by(|a, b| a.0.cmp(&b.0, *sort));
+    employees.sort_by(|a, b| a.cmp(b, *sort));
+    let rows: Vec<usize> = employees.iter().map(|emp| emp.id).collect();
+    let cursor = match view.is_read_only() {
+        true => None,
+        false => Some(*cursor_handle),
+    };
     let mut emp_table = vec![];
-    for (_, row) in emp_rows {
-        emp_table.push(row);
+    for (row, employee) in employees.iter().enumerate() {
+        emp_table.push(employee.make_row(business.clone(), held_block.clone(), view.is_read_only(), row, cursor));
     }
     if emp_table.is_empty() {
         emp_table.push(html!(<tr>
@@ -54,6 +69,48 @@ pub fn Table() -> Html {
         </tr>));
     }
 
+    if view.is_read_only() {
+        return html!(<>
+            {table_legend(business.clone())}
+            <div class="table-scroll">
+                <table class={"mui-table mui-table--bordered"}>
+                    {table_header}
+                    {emp_table}
+                </table>
+            </div>
+        </>);
+    }
+
+    let active_cell = rows.get(cursor_handle.row).map(|emp_id| cell_id(*emp_id, cursor_handle.block));
+    let toolbar = selection_toolbar(business.clone(), cursor_handle.clone(), clipboard.clone(), &rows, bulk_role_ref, shift_ref);
+    let onkeydown = {
+        let (business, cursor_handle, rows) = (business.clone(), cursor_handle.clone(), rows.clone());
+        Callback::from(move |e: KeyboardEvent| grid_keydown(e, cursor_handle.clone(), clipboard.clone(), business.clone(), &rows))
+    };
+    let onmousedown = {
+        let (cursor_handle, rows) = (cursor_handle.clone(), rows.clone());
+        Callback::from(move |e: MouseEvent| grid_mousedown(e, cursor_handle.clone(), &rows))
+    };
+    let tracing = !business.role_trace.is_empty();
+    let onmouseover = {
+        let (cursor_handle, hovered) = (cursor_handle.clone(), hovered.clone());
+        Callback::from(move |e: MouseEvent| {
+            if tracing {
+                hovered.set(trace_target(&e));
+            }
+            grid_mouseover(e, cursor_handle.clone(), &rows)
+        })
+    };
+    let onmouseleave = {
+        let hovered = hovered.clone();
+        Callback::from(move |_| hovered.set(None))
+    };
+    let (coverage, trace_panel) = match (tracing, *hovered) {
+        (false, _) => (html!(), html!()),
+        (true, None) => (coverage_rows(&business), html!()),
+        (true, Some(target)) => (coverage_rows(&business), html!(<TracePanel target={target} />)),
+    };
+
     html!(<>
         // <table class={"mui-table mui-table--bordered"}>
         //     {table_header.clone()}
@@ -63,12 +120,19 @@ pub fn Table() -> Html {
         <br />
         {extra_controls(sort, business, settings)}
         <br />
-        <table class={"mui-table mui-table--bordered"}>
+        {toolbar}
+        <table class={"mui-table mui-table--bordered schedule-grid"} tabindex="0" role="grid" aria-label="Schedule" aria-multiselectable="true"
+            aria-describedby="grid-help" aria-activedescendant={active_cell} onkeydown={onkeydown} onmousedown={onmousedown} onmouseover={onmouseover} onmouseleave={onmouseleave}>
             // <thead class="time">
                 {table_header}
             // </thead>
             {emp_table}
+            {coverage}
         </table>
+        {trace_panel}
+        <p id="grid-help" class="grid-help">
+            {"Keyboard: arrow keys move, Shift+arrows select, 1-9 assign the role in that position of the role key, Delete clears the selection, L locks or unlocks the run under the cursor, Ctrl+C/Ctrl+V copy and paste. Mouse: click or drag across open blocks to select, Shift+click to extend, hold Alt while dropping a block to swap it with that employee"}
+        </p>
     </>)
 }
 
@@ -102,8 +166,8 @@ fn table_key(business: BusinessContext, settings: SettingsContext, held_block: H
                 {role.name()}
                 <input type="button" value='\u{21C5}' onclick={onclick} style="float: right; margin-right: 4px;"/>
             </div>
-            {drag_block(block_single, style.clone(), business.clone(), held_block.clone())}
-            {multi_block(block_multi, style, business.clone(), held_block.clone())}
+            {drag_block(block_single, style.clone(), business.clone(), held_block.clone(), BlockAttrs::default(), false)}
+            {multi_block(block_multi, style, business.clone(), held_block.clone(), vec![], false)}
         </div>));
     }
 
@@ -112,6 +176,28 @@ fn table_key(business: BusinessContext, settings: SettingsContext, held_block: H
     </div>)
 }
 
+/// Role colors for the read-only viewer, in place of the draggable role palette
+fn table_legend(business: BusinessContext) -> Html {
+    let colors = &business.role_colors;
+
+    let mut roles: Vec<&Role> = business.roles.values().collect();
+    roles.sort();
+    let mut items = vec![];
+    for role in roles {
+        let mut style = None;
+        if colors.contains_key(&role.id()) {
+            style = Some("background-color: ".to_string() + &colors[&role.id()] + ";")
+        }
+        items.push(html!(<div class="table-key-item">
+            {view_block(style, role.name())}
+        </div>));
+    }
+
+    html!(<div class="table-key legend">
+        {items}
+    </div>)
+}
+
 fn extra_controls(sort: Sort, business: BusinessContext, settings: SettingsContext) -> Html {
 
     let (snc, scic, scoc);
@@ -130,11 +216,13 @@ fn extra_controls(sort: Sort, business: BusinessContext, settings: SettingsConte
 
     let lunch_callback;
     let schedule_callback;
+    let repair_callback;
     {
-        let (b1, b2) = (business.clone(), business.clone());
-        let settings = settings.clone();
-        lunch_callback = Callback::from(move |_| b1.dispatch(BusinessEvents::ScheduleLunch));
-        schedule_callback = Callback::from(move |_| b2.dispatch(BusinessEvents::ScheduleRoles { settings: settings.clone() }));
+        let (b1, b2, b3) = (business.clone(), business.clone(), business.clone());
+        let (s1, settings) = (settings.clone(), settings.clone());
+        lunch_callback = Callback::from(move |_| b1.dispatch(BusinessEvents::ScheduleLunch { settings: s1.app.clone() }));
+        schedule_callback = Callback::from(move |_| b2.dispatch(BusinessEvents::ScheduleRoles { settings: settings.app.clone() }));
+        repair_callback = Callback::from(move |_| b3.dispatch(BusinessEvents::RepairSchedule));
     }
 
     html!(<div class="controls">
@@ -144,27 +232,55 @@ fn extra_controls(sort: Sort, business: BusinessContext, settings: SettingsConte
 
         <div class="tooltip" style="border-bottom: none;">
             <input type="button" value="Guess Lunches" onclick={lunch_callback} />
-            <span class="tooltiptext">{"Clear the current schedule and fill in a lunch block in the middle of every employee's day, appropriately size for that employee"}</span>
+            <span class="tooltiptext">{"Fill in a lunch for every employee near the middle of their day, staggered within the lunch window set in settings. Locked runs stay where they are."}</span>
         </div>
         <div class="tooltip" style="border-bottom: none;">
             <input type="button" value="Fill in Roles" onclick={schedule_callback} />
-            <span class="tooltiptext">{"For each role, in order of sort, find an available employee and attempt to assign up to 4 blocks to them"}</span>
+            <span class="tooltiptext">{"Block by block, fill each role, in order of sort, with the available employee who has spent the least time on it, assigning up to a shift's worth of blocks at a time. Role switches are kept within the limit set in settings where possible. Afterwards, hover over a block to see why it was filled the way it was, and check the fairness summary below."}</span>
+        </div>
+        <div class="tooltip" style="border-bottom: none;">
+            <input type="button" value="Check Schedule" onclick={repair_callback} />
+            <span class="tooltiptext">{"Look for assignments which overlap or fall outside an employee's hours, roles which have been deleted or which the employee isn't allowed to work, and roles with more employees at once than they allow, and fix them"}</span>
         </div>
     </div>)
 }
 
 impl Employee {
-    pub fn make_row(&self, business: BusinessContext, held_block: UseStateHandle<TimeBlock>, ) -> Html {
+    /// `row_index` is this employee's position in the table, used to match against the keyboard cursor
+    pub fn make_row(&self, business: BusinessContext, held_block: UseStateHandle<TimeBlock>, read_only: bool, row_index: usize, cursor: Option<GridCursor>) -> Html {
         let colors = &business.role_colors;
+        let grid = business.grid();
+        let fill = |first: usize, last: usize| partial_fill(&business, &self.segments(grid.block_span(first).0, grid.block_span(last).1));
+        let block_attrs = |block: usize| match cursor {
+            None => BlockAttrs { fill: fill(block, block), ..BlockAttrs::default() },
+            Some(cursor) => BlockAttrs {
+                id: Some(cell_id(self.id, block)),
+                label: Some(self.block_label(&business, block)),
+                cursor: cursor.row == row_index && cursor.block == block,
+                selected: cursor.contains(row_index, block),
+                fill: fill(block, block),
+            },
+        };
+        // Other editors whose last change was to this employee
+        let editors: Vec<Html> = business.sync.iter()
+            .flat_map(|sync| sync.peers.iter().map(move |(client, presence)| (sync.peer_name(*client), presence)))
+            .filter(|(_, presence)| presence.employee == Some(self.id))
+            .map(|(name, presence)| html!(
+                <span class="peer" title={format!("{}: {}", name, presence.action)}>{name.chars().next().unwrap_or('?').to_string()}</span>
+            ))
+            .collect();
         let mut row = vec![];
         row.push(html!(
-            <td>
+            <td role="rowheader">
                 {self.name.clone()}
+                {editors}
             </td>
         ));
+        let index = business.index();
+        let assigned = index.assigned(self.id);
         let mut prev_role = 0;
-        for i in 0..self.assigned.len() {
-            let role = self.assigned[i].clone();
+        for i in 0..assigned.len() {
+            let role = assigned[i];
 
             if prev_role != 0 && prev_role == role {
                 continue;
@@ -179,12 +295,29 @@ impl Employee {
 
             if role == 0 {
                 // If not at work, give empty block
-                row.push(html!(<td class="empty-block"></td>));
+                let attrs = block_attrs(i);
+                row.push(html!(<td class={classes!("empty-block", attrs.classes())} id={attrs.id.clone()} role={attrs.role()} aria-label={attrs.label.clone()} aria-selected={attrs.aria_selected()}></td>));
+            } else if read_only {
+                // If viewing, give a labelled block spanning the whole run
+                let mut role_len = 1;
+                while assigned.get(i + role_len).is_some_and(|next_role| role.eq(next_role)) {
+                    role_len += 1;
+                }
+                prev_role = role;
+                let label = match business.roles.get(&role) {
+                    Some(r) if role != 1 => r.name(),
+                    _ => AttrValue::default(),
+                };
+                row.push(html!(
+                    <td colspan={role_len.to_string()}>
+                        {view_block(fill(i, i + role_len - 1).or(style), label)}
+                    </td>
+                ));
             } else if role == 1 {
                 // If unassigned, give non-draggable block
                 row.push(html!(
                     <td>
-                        {static_block(TimeBlock::new_simple(self.id.clone(), i, role), style, business.clone(), held_block.clone())}
+                        {static_block(TimeBlock::new_simple(self.id, i, role), style, business.clone(), held_block.clone(), block_attrs(i))}
                     </td>
                 ));
             } else {
@@ -192,7 +325,7 @@ impl Employee {
                 let mut role_len = 1;
                 let mut role_i = i+1;
                 loop {
-                    if let Some(next_role) = self.assigned.get(role_i) {
+                    if let Some(next_role) = assigned.get(role_i) {
                         if role.eq(next_role) {
                             role_len += 1;
                             role_i += 1;
@@ -208,7 +341,7 @@ impl Employee {
                 if role_len == 1 {
                     row.push(html!(
                         <td>
-                            {drag_block(TimeBlock::new_simple(self.id.clone(), i, role), style, business.clone(), held_block.clone())}
+                            {drag_block(TimeBlock::new_simple(self.id, i, role), style, business.clone(), held_block.clone(), block_attrs(i), self.locked_at(&grid, i))}
                         </td>
                     ));
                     continue;
@@ -216,18 +349,56 @@ impl Employee {
                 prev_role = role.clone();
                 row.push(html!(
                     <td colspan={role_len.to_string()}>
-                        {multi_block(TimeBlock { emp_id: self.id.clone(), time_index: i, role, len: role_len, len_index: 0 }, style, business.clone(), held_block.clone())}
+                        {multi_block(TimeBlock { emp_id: self.id, time_index: i, role, len: role_len, len_index: 0 }, style, business.clone(), held_block.clone(), (i..i + role_len).map(block_attrs).collect(), self.locked_at(&grid, i))}
                     </td>
                 ));
 
             }
         }
         html!(
-            <tr key={self.id}>
+            <tr key={self.id} role="row">
                 {row}
             </tr>
         )
     }
+
+    /// Screen reader label for one of this employee's blocks, e.g. "Employee 1, 9:30, Role 2"
+    fn block_label(&self, business: &Business, block: usize) -> AttrValue {
+        let time = business.block_time(block);
+        let activity = match business.index().assigned(self.id).get(block) {
+            None | Some(0) => "not clocked in".into(),
+            Some(1) => "available".into(),
+            Some(role) => match business.roles.get(role) {
+                Some(role) => role.name(),
+                None => "unknown role".into(),
+            },
+        };
+        let locked = match self.locked_at(&business.grid(), block) {
+            true => ", locked",
+            false => "",
+        };
+        format!("{}, {}, {}{}", self.name, time.format("%-I:%M"), activity, locked).into()
+    }
+}
+
+/// Background showing each part of a stretch of time in proportion, when it isn't all clocked out, available or the
+/// same role. Clocked out parts take the empty block color and available parts are left clear.
+fn partial_fill(business: &Business, segments: &[Assignment]) -> Option<String> {
+    let (first, last) = match segments {
+        [] | [_] => return None,
+        [first, .., last] => (first, last),
+    };
+    let total = (last.end - first.start).num_seconds() as f64;
+    let percent = |time: chrono::NaiveTime| (time - first.start).num_seconds() as f64 * 100.0 / total;
+    let stops: Vec<String> = segments.iter().map(|segment| {
+        let color = match segment.role {
+            0 => "var(--empty-block-color)".to_string(),
+            1 => "transparent".to_string(),
+            role => business.role_colors.get(&role).map(|color| color.to_string()).unwrap_or("transparent".to_string()),
+        };
+        format!("{} {:.2}% {:.2}%", color, percent(segment.start), percent(segment.end))
+    }).collect();
+    Some(format!("background: linear-gradient(to right, {});", stops.join(", ")))
 }
 
 #[function_component]
@@ -235,7 +406,7 @@ pub fn ScheduleCopy() -> Html {
     let business = use_context::<BusinessContext>().expect("No ctx found");
 
     let input_ref = use_node_ref();
-    let schedule = schedule_to_csv(business.clone());
+    let schedule = schedule_to_csv(&business);
 
     let onclick;
     {
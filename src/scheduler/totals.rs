@@ -0,0 +1,51 @@
+use yew::prelude::*;
+
+use crate::{totals::{format_duration, format_money}, BusinessContext};
+
+/// Hours and cost of each scheduled employee, with the time worked on each role
+#[function_component]
+pub fn ScheduleTotals() -> Html {
+    let business = use_context::<BusinessContext>().expect("No ctx found");
+    let totals = business.totals();
+
+    let rows: Vec<Html> = totals.employees.iter().map(|emp| html!(
+        <tr key={emp.employee}>
+            <td>{business.employee_name(emp.employee)}</td>
+            <td>{format_duration(emp.clocked)}</td>
+            <td>{format_duration(emp.lunch)}</td>
+            <td>{format_duration(emp.paid())}</td>
+            <td>{format_money(emp.wage)}</td>
+            <td>{format_money(emp.cost())}</td>
+        </tr>
+    )).collect();
+    let roles: Vec<String> = totals.roles.iter()
+        .map(|(role, time)| format!("{} {}", business.role_name(*role), format_duration(*time)))
+        .collect();
+
+    html!(<div class="totals">
+        <table class="mui-table mui-table--bordered">
+            <thead>
+                <tr>
+                    <th>{"Employee"}</th>
+                    <th>{"Hours"}</th>
+                    <th>{"Lunch"}</th>
+                    <th>{"Paid"}</th>
+                    <th>{"Wage"}</th>
+                    <th>{"Cost"}</th>
+                </tr>
+            </thead>
+            <tbody>
+                {rows}
+                <tr>
+                    <th>{"Total"}</th>
+                    <td></td>
+                    <td></td>
+                    <th>{format_duration(totals.paid())}</th>
+                    <td></td>
+                    <th>{format_money(totals.cost())}</th>
+                </tr>
+            </tbody>
+        </table>
+        <p>{"Time on each role: "}{roles.join(", ")}</p>
+    </div>)
+}
@@ -0,0 +1,108 @@
+use web_sys::Element;
+use yew::prelude::*;
+
+use crate::{automation::{Decision, Outcome, Rejection}, data::{Business, RoleTrait}, scheduler::keyboard::parse_cell_id, BusinessContext};
+
+/// What the mouse is over while there is a trace from filling in roles: an employee's block, or a role's slot in the
+/// coverage rows
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TraceTarget {
+    Employee { employee: usize, block: usize },
+    Slot { role: usize, block: usize },
+}
+
+/// DOM id of a role's slot in the coverage rows
+pub fn slot_id(role: usize, block: usize) -> AttrValue {
+    format!("slot-{}-{}", role, block).into()
+}
+
+/// The employee block or role slot under the mouse, if any
+pub fn trace_target(e: &MouseEvent) -> Option<TraceTarget> {
+    let id = e.target_dyn_into::<Element>()?.closest("[id]").ok()??.id();
+    if let Some((employee, block)) = parse_cell_id(&id) {
+        return Some(TraceTarget::Employee { employee, block });
+    }
+    let (role, block) = id.strip_prefix("slot-")?.split_once("-")?;
+    Some(TraceTarget::Slot { role: role.parse().ok()?, block: block.parse().ok()? })
+}
+
+/// Who works each single role at each block, below the employees, so empty slots can be hovered to see why
+pub fn coverage_rows(business: &Business) -> Html {
+    let mut roles: Vec<_> = business.roles.values().filter(|role| !role.is_multi()).collect();
+    roles.sort();
+    let index = business.index();
+    let rows = roles.into_iter().map(|role| {
+        let assigned: Vec<usize> = index.role_assigned(role).into();
+        let cells = assigned.into_iter().enumerate().map(|(block, employee)| match employee {
+            0 => html!(<td class="empty-slot" id={slot_id(role.id(), block)} title="Nobody"></td>),
+            employee => {
+                let style = format!("background-color: {};", role.color());
+                html!(<td class="slot" id={slot_id(role.id(), block)} style={style} title={business.employee_name(employee)}></td>)
+            },
+        });
+        html!(<tr class="coverage-row">
+            <td>{role.name()}</td>
+            {for cells}
+        </tr>)
+    });
+    html!({for rows})
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TracePanelProps {
+    pub target: TraceTarget,
+}
+
+/// Side panel explaining what filling in roles decided for the hovered block, and why
+#[function_component]
+pub fn TracePanel(props: &TracePanelProps) -> Html {
+    let business = use_context::<BusinessContext>().expect("No ctx found");
+    let (block, employee, role) = match props.target {
+        TraceTarget::Employee { employee, block } => (block, Some(employee), None),
+        TraceTarget::Slot { role, block } => (block, None, Some(role)),
+    };
+    let decisions: Vec<&Decision> = business.role_trace.iter()
+        .filter(|decision| decision.block == block && role.is_none_or(|role| decision.role == role))
+        .collect();
+    let title = format!("Fill in Roles at {}", business.block_time(block).format("%-I:%M"));
+    let sections = decisions.into_iter().map(|decision| {
+        let candidates = decision.candidates.iter().map(|(candidate, rejection)| {
+            let reason = match rejection {
+                Some(rejection) => describe_rejection(&business, rejection),
+                None => "chosen".to_string(),
+            };
+            let class = classes!(rejection.is_none().then_some("trace-chosen"), (Some(*candidate) == employee).then_some("trace-self"));
+            html!(<li class={class}>{format!("{}: {}", business.employee_name(*candidate), reason)}</li>)
+        });
+        html!(<div class="trace-decision">
+            <h4>{business.role_name(decision.role)}</h4>
+            <p>{describe_outcome(&business, &decision.outcome)}</p>
+            <ul>{for candidates}</ul>
+        </div>)
+    });
+    html!(<aside class="trace-panel" aria-live="polite">
+        <h3>{title}</h3>
+        {for sections}
+    </aside>)
+}
+
+fn describe_outcome(business: &Business, outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::AlreadyCovered { employee } => format!("Already worked by {}", business.employee_name(*employee)),
+        Outcome::Continued { employee, from } => format!("Part of the run given to {} at {}", business.employee_name(*employee), business.block_time(*from).format("%-I:%M")),
+        Outcome::Chosen { employee } => format!("Given to {}", business.employee_name(*employee)),
+        Outcome::Empty => "Left empty; nobody could work it".to_string(),
+    }
+}
+
+fn describe_rejection(business: &Business, rejection: &Rejection) -> String {
+    match rejection {
+        Rejection::NotScheduled => "not scheduled today".to_string(),
+        Rejection::NotAllowed => "can't work this role".to_string(),
+        Rejection::NotClockedIn => "not clocked in".to_string(),
+        Rejection::Busy { role: 2 } => "at lunch".to_string(),
+        Rejection::Busy { role } => format!("already working {}", business.role_name(*role)),
+        Rejection::Outranked => "free, but someone with less time on the role was chosen".to_string(),
+        Rejection::TooManySwitches => "free, but it would switch their role too often".to_string(),
+    }
+}
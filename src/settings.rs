@@ -1,12 +1,26 @@
 use std::{collections::HashMap, ops::Deref};
 
 use chrono::{NaiveTime, TimeDelta};
+use serde::{Deserialize, Serialize};
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
-use crate::{events::BusinessEvents, print::PrintTable, BusinessContext, SettingsContext};
+use crate::{backup::{read_backup, Backup}, data::LostTime, events::BusinessEvents, notifications::{Level, NotificationContext, NotificationEvents}, persistence::{percent_encode, read_editor, read_text_file, write_editor}, print::PrintTable, BusinessContext, SettingsContext};
 
-#[derive(Debug, PartialEq, Clone, Default)]
+#[derive(Debug)]
+pub enum FragmentError {
+    /// A settings group without its opening parenthesis, e.g. `app|shift_length:4|)`
+    MissingGroupStart { group: String }
+} impl std::fmt::Display for FragmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FragmentError::MissingGroupStart { group } => write!(f, "settings group \"{}\" is missing its \"(\"", group),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     pub app: AppSettings,
     pub print: PrintSettings,
@@ -17,7 +31,7 @@ pub struct Settings {
         result = self.print.fragment_string(result);
         result
     }
-    pub fn from_fragment(data: &str) -> Settings {
+    pub fn from_fragment(data: &str) -> Result<Settings, FragmentError> {
         let mut data_map = HashMap::new();
         // let decoded = decode_query(data.to_string(), &[':', '(', ')']);
         let settings_groups: Vec<&str> = data.split(",").collect();
@@ -25,7 +39,11 @@ pub struct Settings {
             if group.is_empty() {
                 continue;
             }
-            let (name, all_raw_values) = group.split_at(group.find("(").unwrap());
+            let group_start = match group.find("(") {
+                Some(i) => i,
+                None => return Err(FragmentError::MissingGroupStart { group: group.to_string() }),
+            };
+            let (name, all_raw_values) = group.split_at(group_start);
             let mut values = HashMap::new();
             for name_value_vec in all_raw_values.split("|").map(|x| x.split(":").collect::<Vec<&str>>()) {
                 if name_value_vec.len() != 2 {
@@ -35,10 +53,10 @@ pub struct Settings {
             }
             data_map.insert(name, values);
         }
-        Settings { 
+        Ok(Settings { 
             app: AppSettings::from_data_map(&data_map), 
             print: PrintSettings::from_data_map(&data_map)
-        }
+        })
     }
 
     pub fn app_set(mut self, app: AppSettings) -> Self {
@@ -57,6 +75,7 @@ pub fn SettingsTab() -> Html {
     html!(<>
         <AppSettingsSection />
         <PrintSettingsSection />
+        <BackupSection />
         <h1 style="text-align: center;">{"Print Preview"}</h1>
         <div class="print-preview">
             <PrintTable />
@@ -65,17 +84,46 @@ pub fn SettingsTab() -> Html {
 }
 
 const APP_SETTINGS_KEY: &'static str = "app";
-#[derive(Debug, PartialEq, Clone)]
+/// Whether blocks of this many minutes fit a day evenly, the one rule for block sizes wherever they are entered or
+/// read. Zero and negative sizes would also leave [`crate::data::Business::update_business_hours`] unable to finish.
+pub fn valid_block_size(minutes: i64) -> bool {
+    (1..=24 * 60).contains(&minutes) && (24 * 60) % minutes == 0
+}
+const FRAGMENT_TIME: &str = "%H%M";
+/// Settings missing from an older backup are left at their defaults
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppSettings {
     pub shift_length: usize,
     pub lunch_duration: usize,
+    /// Guessed lunches start no earlier than this and end by lunch_latest where possible
+    pub lunch_earliest: NaiveTime,
+    pub lunch_latest: NaiveTime,
+    /// The most employees to have at lunch at the same time
+    pub lunch_overlap: usize,
+    /// Whether guessing lunches keeps the roles already assigned, rather than starting from a clear schedule
+    pub lunch_keep_roles: bool,
+    /// Whether filling roles balances time on each role over the recorded week, rather than just today
+    pub fairness_week: bool,
+    /// The most times filling roles has anyone switch from one role to another, where someone else can cover; 0 for no
+    /// limit
+    pub max_role_switches: usize,
 
     pub open: NaiveTime,
     pub close: NaiveTime,
     pub block_size: TimeDelta,
 } impl Default for AppSettings {
     fn default() -> Self {
-        Self { shift_length: 4, lunch_duration: 2, block_size: TimeDelta::minutes(30), open: NaiveTime::from_hms_opt(9, 0, 0).unwrap(), close: NaiveTime::from_hms_opt(19, 0, 0).unwrap() }
+        Self {
+            shift_length: 4,
+            lunch_duration: 2,
+            lunch_earliest: NaiveTime::from_hms_opt(11, 0, 0).unwrap(),
+            lunch_latest: NaiveTime::from_hms_opt(15, 0, 0).unwrap(),
+            lunch_overlap: 2,
+            lunch_keep_roles: true,
+            fairness_week: false,
+            max_role_switches: 3,
+            block_size: TimeDelta::minutes(30), open: NaiveTime::from_hms_opt(9, 0, 0).unwrap(), close: NaiveTime::from_hms_opt(19, 0, 0).unwrap() }
     }
 } impl AppSettings {
     fn fragment_string(&self, mut string: String) -> String {
@@ -93,6 +141,25 @@ pub struct AppSettings {
         if self.block_size != default.block_size {
             string += &format!("block_size:{}|", self.block_size.num_minutes());
         }
+        // Times without the colon, which separates names from values
+        if self.lunch_earliest != default.lunch_earliest {
+            string += &format!("lunch_earliest:{}|", self.lunch_earliest.format(FRAGMENT_TIME));
+        }
+        if self.lunch_latest != default.lunch_latest {
+            string += &format!("lunch_latest:{}|", self.lunch_latest.format(FRAGMENT_TIME));
+        }
+        if self.lunch_overlap != default.lunch_overlap {
+            string += &format!("lunch_overlap:{}|", self.lunch_overlap);
+        }
+        if self.lunch_keep_roles != default.lunch_keep_roles {
+            string += &format!("lunch_keep_roles:{}|", self.lunch_keep_roles);
+        }
+        if self.fairness_week != default.fairness_week {
+            string += &format!("fairness_week:{}|", self.fairness_week);
+        }
+        if self.max_role_switches != default.max_role_switches {
+            string += &format!("max_role_switches:{}|", self.max_role_switches);
+        }
         string += "),";
         string
     }
@@ -117,13 +184,37 @@ pub struct AppSettings {
                         Some(x) => {
                             let mins = x.parse();
                             match mins {
-                                Ok(x) => TimeDelta::minutes(x),
-                                Err(_) => default.block_size,
+                                Ok(x) if valid_block_size(x) => TimeDelta::minutes(x),
+                                _ => default.block_size,
                             }
                         }, 
                         None => default.block_size
                     }
                 }, 
+                lunch_earliest: match data.get("lunch_earliest") {
+                    Some(x) => NaiveTime::parse_from_str(x, FRAGMENT_TIME).unwrap_or(default.lunch_earliest),
+                    None => default.lunch_earliest
+                },
+                lunch_latest: match data.get("lunch_latest") {
+                    Some(x) => NaiveTime::parse_from_str(x, FRAGMENT_TIME).unwrap_or(default.lunch_latest),
+                    None => default.lunch_latest
+                },
+                lunch_overlap: match data.get("lunch_overlap") {
+                    Some(x) => x.parse().unwrap_or(default.lunch_overlap),
+                    None => default.lunch_overlap
+                },
+                lunch_keep_roles: match data.get("lunch_keep_roles") {
+                    Some(x) => x.parse().unwrap_or(default.lunch_keep_roles),
+                    None => default.lunch_keep_roles
+                },
+                fairness_week: match data.get("fairness_week") {
+                    Some(x) => x.parse().unwrap_or(default.fairness_week),
+                    None => default.fairness_week
+                },
+                max_role_switches: match data.get("max_role_switches") {
+                    Some(x) => x.parse().unwrap_or(default.max_role_switches),
+                    None => default.max_role_switches
+                },
                 open: default.open,
                 close: default.close
             },
@@ -131,17 +222,34 @@ pub struct AppSettings {
         }
     }
 
-    fn business_set(&mut self, open: NaiveTime, close: NaiveTime, block_size: TimeDelta) {
+    pub fn business_set(&mut self, open: NaiveTime, close: NaiveTime, block_size: TimeDelta) {
         self.open = open;
         self.close = close;
         self.block_size = block_size;
     }
 }
 
+/// New business hours waiting on the user to accept what they would remove from the schedule
+#[derive(Clone, PartialEq)]
+struct PendingHours {
+    open: NaiveTime,
+    close: NaiveTime,
+    block_size: TimeDelta,
+    lost: Vec<LostTime>,
+}
+
+fn apply_business_hours(business: &BusinessContext, settings: &SettingsContext, open: NaiveTime, close: NaiveTime, block_size: TimeDelta) {
+    business.dispatch(BusinessEvents::UpdateBusinessHours { open, close, block_size });
+    let mut new_settings = settings.deref().clone();
+    new_settings.app.business_set(open, close, block_size);
+    settings.set(new_settings);
+}
+
 #[function_component]
 fn AppSettingsSection() -> Html {
     let business = use_context::<BusinessContext>().expect("No ctx found");
     let settings = use_context::<SettingsContext>().expect("Settings context not found");
+    let notifications = use_context::<NotificationContext>().expect("Notification context not found");
     let app = &settings.app;
 
     let (
@@ -149,8 +257,16 @@ fn AppSettingsSection() -> Html {
         close_ref,
         block_ref,
         shift_ref,
-        lunch_ref
+        lunch_ref,
+        lunch_earliest_ref,
+        lunch_latest_ref,
+        lunch_overlap_ref,
+        switches_ref
     ) = (
+        use_node_ref(),
+        use_node_ref(),
+        use_node_ref(),
+        use_node_ref(),
         use_node_ref(),
         use_node_ref(),
         use_node_ref(),
@@ -158,47 +274,169 @@ fn AppSettingsSection() -> Html {
         use_node_ref()
     );
 
+    let pending = use_state_eq(|| None::<PendingHours>);
+
     let business_time_change_cb = {
         let b = business.clone();
-        let settings = settings.clone();
+        let (settings, notifications, pending) = (settings.clone(), notifications.clone(), pending.clone());
         let (open_ref, close_ref, block_ref) = (open_ref.clone(), close_ref.clone(), block_ref.clone());
         Callback::from(move |_| {
-            let (open, close, block_size) = (
-                open_ref.cast::<HtmlInputElement>().unwrap().value().parse::<NaiveTime>().unwrap(),
-                close_ref.cast::<HtmlInputElement>().unwrap().value().parse::<NaiveTime>().unwrap(),
-                TimeDelta::minutes(block_ref.cast::<HtmlInputElement>().unwrap().value().parse().unwrap())
-            );
-            b.dispatch(BusinessEvents::UpdateBusinessHours { 
-                open: open.clone(), 
-                close: close.clone(), 
-                block_size: block_size.clone()
-            });
-            let mut new_settings = settings.deref().clone();
-            new_settings.app.business_set(open, close, block_size);
-            settings.set(new_settings);
+            let (open, close, block_size) = match (
+                open_ref.cast::<HtmlInputElement>().unwrap().value().parse::<NaiveTime>(),
+                close_ref.cast::<HtmlInputElement>().unwrap().value().parse::<NaiveTime>(),
+                block_ref.cast::<HtmlInputElement>().unwrap().value().parse::<i64>()
+            ) {
+                (Ok(open), Ok(close), Ok(minutes)) if open < close && valid_block_size(minutes) => (open, close, TimeDelta::minutes(minutes)),
+                _ => return notifications.dispatch(NotificationEvents::Push { 
+                    level: Level::Error, 
+                    message: "Business hours need an open time before the close time and a block size which divides a day evenly, e.g. 15 or 30 minutes".into() 
+                }),
+            };
+            // Anything which would be lost from the schedule is shown first, to be applied or cancelled
+            let lost = b.preview_business_hours(open, close, block_size);
+            if lost.is_empty() {
+                pending.set(None);
+                apply_business_hours(&b, &settings, open, close, block_size);
+            } else {
+                pending.set(Some(PendingHours { open, close, block_size, lost }));
+            }
         })
     };
 
+    let pending_row = match (*pending).clone() {
+        None => html!(),
+        Some(PendingHours { open, close, block_size, lost }) => {
+            let apply_cb = {
+                let (b, settings, pending) = (business.clone(), settings.clone(), pending.clone());
+                Callback::from(move |_| {
+                    pending.set(None);
+                    apply_business_hours(&b, &settings, open, close, block_size);
+                })
+            };
+            let cancel_cb = {
+                let pending = pending.clone();
+                let (open_ref, close_ref, block_ref) = (open_ref.clone(), close_ref.clone(), block_ref.clone());
+                let (open, close, block_size) = (app.open, app.close, app.block_size);
+                Callback::from(move |_| {
+                    pending.set(None);
+                    // The inputs aren't redrawn when the settings don't change, so put back what they showed
+                    open_ref.cast::<HtmlInputElement>().unwrap().set_value(&open.format("%H:%M").to_string());
+                    close_ref.cast::<HtmlInputElement>().unwrap().set_value(&close.format("%H:%M").to_string());
+                    block_ref.cast::<HtmlInputElement>().unwrap().set_value(&block_size.num_minutes().to_string());
+                })
+            };
+            let items: Vec<Html> = lost.iter().map(|lost| html!(
+                <li>{format!("{}: {} from {} to {}", business.employee_name(lost.employee), business.role_name(lost.role), lost.start.format("%-I:%M"), lost.end.format("%-I:%M"))}</li>
+            )).collect();
+            html!(<tr class="pending-change">
+                <td colspan="2">
+                    <p>{format!("These hours would remove {} part(s) of the schedule:", lost.len())}</p>
+                    <ul>{items}</ul>
+                    <input type="button" value="Apply" onclick={apply_cb} />
+                    <input type="button" value="Cancel" onclick={cancel_cb} />
+                </td>
+            </tr>)
+        },
+    };
+
     let shift_cb = {
-        let settings = settings.clone();
+        let (settings, notifications) = (settings.clone(), notifications.clone());
         let shift_ref = shift_ref.clone();
         Callback::from(move |_| {
             let mut new = settings.deref().clone();
-            new.app.shift_length = shift_ref.cast::<HtmlInputElement>().unwrap().value().parse().unwrap();
+            new.app.shift_length = match shift_ref.cast::<HtmlInputElement>().unwrap().value().parse() {
+                Ok(length) => length,
+                Err(_) => return notifications.dispatch(NotificationEvents::Push { level: Level::Error, message: "Shift length must be a whole number of hours".into() }),
+            };
             settings.set(new);
         })
     };
 
     let lunch_cb = {
-        let settings = settings.clone();
+        let (settings, notifications) = (settings.clone(), notifications.clone());
         let lunch_ref = lunch_ref.clone();
         Callback::from(move |_| {
             let mut new_settings = settings.deref().clone();
-            new_settings.app.lunch_duration = lunch_ref.cast::<HtmlInputElement>().unwrap().value().parse().unwrap();
+            new_settings.app.lunch_duration = match lunch_ref.cast::<HtmlInputElement>().unwrap().value().parse() {
+                Ok(duration) => duration,
+                Err(_) => return notifications.dispatch(NotificationEvents::Push { level: Level::Error, message: "Lunch duration must be a whole number of blocks".into() }),
+            };
+            settings.set(new_settings);
+        })
+    };
+
+    let lunch_window_cb = {
+        let (settings, notifications) = (settings.clone(), notifications.clone());
+        let (earliest_ref, latest_ref) = (lunch_earliest_ref.clone(), lunch_latest_ref.clone());
+        Callback::from(move |_| {
+            let mut new_settings = settings.deref().clone();
+            (new_settings.app.lunch_earliest, new_settings.app.lunch_latest) = match (
+                earliest_ref.cast::<HtmlInputElement>().unwrap().value().parse::<NaiveTime>(),
+                latest_ref.cast::<HtmlInputElement>().unwrap().value().parse::<NaiveTime>()
+            ) {
+                (Ok(earliest), Ok(latest)) if earliest < latest => (earliest, latest),
+                _ => return notifications.dispatch(NotificationEvents::Push { level: Level::Error, message: "The lunch window needs a start before its end".into() }),
+            };
+            settings.set(new_settings);
+        })
+    };
+
+    let lunch_overlap_cb = {
+        let (settings, notifications) = (settings.clone(), notifications.clone());
+        let lunch_overlap_ref = lunch_overlap_ref.clone();
+        Callback::from(move |_| {
+            let mut new_settings = settings.deref().clone();
+            new_settings.app.lunch_overlap = match lunch_overlap_ref.cast::<HtmlInputElement>().unwrap().value().parse() {
+                Ok(overlap) if overlap > 0 => overlap,
+                _ => return notifications.dispatch(NotificationEvents::Push { level: Level::Error, message: "At least one person needs to be able to go to lunch at a time".into() }),
+            };
+            settings.set(new_settings);
+        })
+    };
+
+    let keep_roles_cb = {
+        let settings = settings.clone();
+        Callback::from(move |_| {
+            let mut new_settings = settings.deref().clone();
+            new_settings.app.lunch_keep_roles = !new_settings.app.lunch_keep_roles;
+            settings.set(new_settings);
+        })
+    };
+
+    let fairness_week_cb = {
+        let settings = settings.clone();
+        Callback::from(move |_| {
+            let mut new_settings = settings.deref().clone();
+            new_settings.app.fairness_week = !new_settings.app.fairness_week;
+            settings.set(new_settings);
+        })
+    };
+
+    let switches_cb = {
+        let (settings, notifications) = (settings.clone(), notifications.clone());
+        let switches_ref = switches_ref.clone();
+        Callback::from(move |_| {
+            let mut new_settings = settings.deref().clone();
+            new_settings.app.max_role_switches = match switches_ref.cast::<HtmlInputElement>().unwrap().value().parse() {
+                Ok(switches) => switches,
+                Err(_) => return notifications.dispatch(NotificationEvents::Push { level: Level::Error, message: "Role switches must be a whole number".into() }),
+            };
             settings.set(new_settings);
         })
     };
 
+    // Kept by this browser rather than in the link, so each computer records its own editor
+    let editor = use_state(|| read_editor().unwrap_or_default());
+    let editor_cb = {
+        let editor = editor.clone();
+        Callback::from(move |e: FocusEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                write_editor(&input.value());
+                editor.set(input.value().trim().to_string());
+            }
+        })
+    };
+
     let step_size: AttrValue = app.block_size.num_seconds().to_string().into();
 
     html!(<table class="mui-table mui-table--bordered">
@@ -221,12 +459,13 @@ fn AppSettingsSection() -> Html {
             <tr>
                 <td><div class="tooltip">
                     {"Time Block Size: \u{24D8}"}
-                    <span class="tooltiptext">{"The amount of time, in minutes, you wish each block to be. For example, the default value of 30 will split a 10 hour day into 20 blocks."}</span>
+                    <span class="tooltiptext">{"The amount of time, in minutes, you wish each block to be. For example, the default value of 30 will split a 10 hour day into 20 blocks. Changing it keeps the schedule, moving each assignment to the nearest block."}</span>
                 </div></td>
                 <td>
-                    <input id="blocks" type="number" name="blocks" min={0} value={app.block_size.num_minutes().to_string()} onblur={business_time_change_cb} ref={block_ref} />
+                    <input id="blocks" type="number" name="blocks" min={1} value={app.block_size.num_minutes().to_string()} onblur={business_time_change_cb} ref={block_ref} />
                 </td>
             </tr>
+            {pending_row}
             <tr>
                 <td><div class="tooltip">
                     {"Preferred Shift Length: \u{24D8}"}
@@ -245,11 +484,67 @@ fn AppSettingsSection() -> Html {
                     <input id="lunch" type="number" name="lunch" min={1} value={app.lunch_duration.to_string()} onblur={lunch_cb} ref={lunch_ref} />
                 </td>
             </tr>
+            <tr>
+                <td><div class="tooltip">
+                    {"Lunch Window: \u{24D8}"}
+                    <span class="tooltiptext">{"When guessing lunches, each lunch starts and ends within this window, unless an employee isn't working then"}</span>
+                </div></td>
+                <td>
+                    <input id="lunch_earliest" type="time" name="lunch_earliest" step="60" value={app.lunch_earliest.format("%H:%M").to_string()} onblur={lunch_window_cb.clone()} ref={lunch_earliest_ref} />
+                    {" to "}
+                    <input id="lunch_latest" type="time" name="lunch_latest" step="60" value={app.lunch_latest.format("%H:%M").to_string()} onblur={lunch_window_cb} ref={lunch_latest_ref} />
+                </td>
+            </tr>
+            <tr>
+                <td><div class="tooltip">
+                    {"Most at Lunch at Once: \u{24D8}"}
+                    <span class="tooltiptext">{"When guessing lunches, they are staggered so no more than this many employees are at lunch at the same time, where possible"}</span>
+                </div></td>
+                <td>
+                    <input id="lunch_overlap" type="number" name="lunch_overlap" min={1} value={app.lunch_overlap.to_string()} onblur={lunch_overlap_cb} ref={lunch_overlap_ref} />
+                </td>
+            </tr>
+            <tr>
+                <td><div class="tooltip">
+                    {"Keep Roles When Guessing Lunches: \u{24D8}"}
+                    <span class="tooltiptext">{"Leave assigned roles in place when guessing lunches, having someone available cover for anyone going to lunch. Otherwise the schedule is cleared first."}</span>
+                </div></td>
+                <td>
+                    <input id="lunch_keep_roles" type="checkbox" name="lunch_keep_roles" checked={app.lunch_keep_roles} onchange={keep_roles_cb} />
+                </td>
+            </tr>
+            <tr>
+                <td><div class="tooltip">
+                    {"Balance Roles Over the Week: \u{24D8}"}
+                    <span class="tooltiptext">{"When filling roles, count the time each employee spent on each role on the days added to the week, not just today"}</span>
+                </div></td>
+                <td>
+                    <input id="fairness_week" type="checkbox" name="fairness_week" checked={app.fairness_week} onchange={fairness_week_cb} />
+                </td>
+            </tr>
+            <tr>
+                <td><div class="tooltip">
+                    {"Most Role Switches: \u{24D8}"}
+                    <span class="tooltiptext">{"When filling roles, nobody is moved from one role to another more than this many times in a day unless nobody else can cover. 0 for no limit."}</span>
+                </div></td>
+                <td>
+                    <input id="max_role_switches" type="number" name="max_role_switches" min={0} value={app.max_role_switches.to_string()} onblur={switches_cb} ref={switches_ref} />
+                </td>
+            </tr>
+            <tr>
+                <td><div class="tooltip">
+                    {"Your Name: \u{24D8}"}
+                    <span class="tooltiptext">{"Recorded with each change you make in the change log. It's kept on this computer only, so change it when someone else takes over."}</span>
+                </div></td>
+                <td>
+                    <input id="editor" type="text" name="editor" placeholder="Optional" value={(*editor).clone()} onblur={editor_cb} />
+                </td>
+            </tr>
         </tbody>
     </table>)
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 /// Whole number, hundredths
 pub struct Size(usize, usize);
 impl std::fmt::Display for Size {
@@ -273,7 +568,7 @@ impl std::fmt::Display for Size {
         Ok(Size(whole, decimal))
     }
 }
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum PrintStyle {
     None,
     Table,
@@ -294,7 +589,8 @@ pub enum PrintStyle {
     }
 }
 const PRINT_SETTINGS_KEY: &'static str = "print";
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PrintSettings {
     pub style: PrintStyle,
     pub width: Size,
@@ -465,4 +761,83 @@ fn PrintSettingsSection() -> Html {
             </tbody>
         </table>
     </>)
-}
\ No newline at end of file
+}
+
+/// Download everything to a file, or restore it from one after checking it and showing what it holds
+#[function_component]
+fn BackupSection() -> Html {
+    let business = use_context::<BusinessContext>().expect("No ctx found");
+    let settings = use_context::<SettingsContext>().expect("Settings context not found");
+    let notifications = use_context::<NotificationContext>().expect("Notification context not found");
+    let pending = use_state_eq(|| None::<Backup>);
+
+    let download = match Backup::new(&business, &settings).to_file() {
+        Ok(file) => html!(<a class="mui-btn" href={format!("data:text/plain;charset=utf-8,{}", percent_encode(&file))} download="daily-planner-backup.ron">{"Download Backup"}</a>),
+        Err(e) => {
+            log::error!("Failed to serialize backup! {:#?}", e);
+            html!({"A backup can't be made right now"})
+        },
+    };
+    let open_cb = {
+        let (notifications, pending) = (notifications.clone(), pending.clone());
+        Callback::from(move |e: Event| {
+            let (notifications, pending) = (notifications.clone(), pending.clone());
+            read_text_file(&e.target_unchecked_into::<HtmlInputElement>(), move |text| match read_backup(&text) {
+                Ok(backup) => pending.set(Some(backup)),
+                Err(e) => {
+                    pending.set(None);
+                    notifications.dispatch(NotificationEvents::Push { level: Level::Error, message: format!("Could not restore the backup; {}", e).into() });
+                },
+            });
+        })
+    };
+
+    let pending_row = match (*pending).clone() {
+        None => html!(),
+        Some(backup) => {
+            let restore_cb = {
+                let (b, settings, pending) = (business.clone(), settings.clone(), pending.clone());
+                let backup = backup.clone();
+                Callback::from(move |_| {
+                    pending.set(None);
+                    settings.set(backup.settings.clone());
+                    b.dispatch(BusinessEvents::Restore { backup: Box::new(backup.clone()) });
+                })
+            };
+            let cancel_cb = {
+                let pending = pending.clone();
+                Callback::from(move |_| pending.set(None))
+            };
+            let app = &backup.settings.app;
+            html!(<tr class="pending-change">
+                <td colspan="2">
+                    <p>{format!("Replace everything with the backup's {} role(s), {} employee(s) and {} scenario(s), open {} to {}?",
+                        // Lunch is always there
+                        backup.business.roles.len() - 1, backup.business.employees.len(), backup.scenarios.len(),
+                        app.open.format("%-I:%M %p"), app.close.format("%-I:%M %p"))}</p>
+                    <input type="button" value="Restore" onclick={restore_cb} />
+                    <input type="button" value="Cancel" onclick={cancel_cb} />
+                </td>
+            </tr>)
+        },
+    };
+
+    html!(<>
+        <table class="mui-table mui-table--bordered">
+            <thead>
+                <tr><th colspan="2">{"Backup"}</th></tr>
+            </thead>
+            <tbody>
+                <tr>
+                    <td>{"Save roles, employees, settings, the schedule and scenarios to a file:"}</td>
+                    <td>{download}</td>
+                </tr>
+                <tr>
+                    <td><label for="restore">{"Restore from a backup file:"}</label></td>
+                    <td><input id="restore" type="file" accept=".ron,.txt,text/plain" onchange={open_cb} /></td>
+                </tr>
+                {pending_row}
+            </tbody>
+        </table>
+    </>)
+}
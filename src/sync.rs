@@ -0,0 +1,289 @@
+//! Live editing with others through the relay server in `relay/`. The relay puts every shared action into one order
+//! and sends it to everyone, the sender included. Each editor applies its own actions straight away and keeps them
+//! pending until they come back; when someone else's action arrives first, it is applied to the last confirmed
+//! business and the pending actions are replayed on top, so every editor ends up with the relay's order.
+//!
+//! Messages are a letter, a space, then the rest:
+//! - to the relay: `M <action>`, `S <snapshot action>` (which also starts the room's history over) and
+//!   `P <presence>`
+//! - from the relay: `W <client> <history>` on joining, `M <seq> <client> <action>` for each action in order,
+//!   `P <client> <presence>`, `L <client>` when someone leaves and `R` when the room's history has grown long enough
+//!   to be replaced with a snapshot
+//!
+//! Actions and presence are written as RON.
+
+use std::{cell::RefCell, collections::{HashMap, VecDeque}};
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{MessageEvent, WebSocket};
+
+use crate::{audit::AuditEntry, data::{Business, Employee}, events::BusinessEvents, index::IndexCache, notifications::Level, persistence::schedule_to_csv, workspace::Workspace, BusinessContext};
+
+/// Where the relay listens when run with no arguments
+pub const DEFAULT_RELAY: &str = "ws://localhost:9031/";
+
+thread_local! {
+    static SOCKET: RefCell<Option<WebSocket>> = const { RefCell::new(None) };
+}
+
+/// What another editor is up to
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Presence {
+    pub name: String,
+    /// The employee their last action changed, if any
+    pub employee: Option<usize>,
+    /// Their last action, as given by [`BusinessEvents::describe`]
+    pub action: String,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum SyncEvent {
+    /// The socket to the relay is being opened, with this editor's name for others to see
+    Connecting { url: String, name: String },
+    /// The relay has taken us into the room. `history` actions already taken in the room follow; if there are none,
+    /// this editor's business starts the room off.
+    Welcome { client: u64, history: u64 },
+    Received { seq: u64, client: u64, action: Box<BusinessEvents> },
+    /// Another editor's latest presence, or None once they have left
+    Presence { client: u64, presence: Option<Presence> },
+    /// The relay wants the room's history started over from this editor's plan
+    SnapshotWanted,
+    /// The user stopped syncing
+    Stop,
+    /// The socket closed on its own
+    Closed,
+}
+
+/// An action from another editor, for the change log
+pub struct RemoteEdit {
+    /// The plan's employees before the action
+    pub before: HashMap<usize, Employee>,
+    pub action: String,
+    pub editor: Option<String>,
+    /// Whether it changed the plan's business hours, which the settings keep too
+    pub hours_changed: bool,
+}
+
+/// Where this editor is in the room's history, see [`crate::sync`]
+#[derive(Clone, PartialEq, Debug)]
+pub struct SyncState {
+    pub url: String,
+    pub name: String,
+    /// Given by the relay on joining
+    pub client: Option<u64>,
+    /// The last action received
+    pub seq: u64,
+    /// The plan with every received action applied and none of ours still pending
+    pub confirmed: Business,
+    /// Our actions sent but not yet received back, oldest first
+    pub pending: VecDeque<BusinessEvents>,
+    /// Everyone else in the room who has said what they are doing, by client
+    pub peers: Vec<(u64, Presence)>,
+} impl SyncState {
+    /// The name to show for a client
+    pub fn peer_name(&self, client: u64) -> String {
+        match self.peers.iter().find(|(id, _)| *id == client) {
+            Some((_, presence)) if !presence.name.is_empty() => presence.name.clone(),
+            _ => format!("Editor {}", client),
+        }
+    }
+}
+
+/// A message from the relay, or None if it can't be read
+pub fn parse_message(message: &str) -> Option<SyncEvent> {
+    let (kind, rest) = message.split_once(' ').unwrap_or((message, ""));
+    match kind {
+        "W" => {
+            let (client, history) = rest.split_once(' ')?;
+            Some(SyncEvent::Welcome { client: client.parse().ok()?, history: history.parse().ok()? })
+        },
+        "M" => {
+            let (seq, rest) = rest.split_once(' ')?;
+            let (client, action) = rest.split_once(' ')?;
+            Some(SyncEvent::Received { seq: seq.parse().ok()?, client: client.parse().ok()?, action: Box::new(ron::from_str(action).ok()?) })
+        },
+        "P" => {
+            let (client, presence) = rest.split_once(' ')?;
+            Some(SyncEvent::Presence { client: client.parse().ok()?, presence: Some(ron::from_str(presence).ok()?) })
+        },
+        "L" => Some(SyncEvent::Presence { client: rest.parse().ok()?, presence: None }),
+        "R" => Some(SyncEvent::SnapshotWanted),
+        _ => None,
+    }
+}
+
+/// Send a message to the relay, if connected
+fn send(kind: char, payload: &impl Serialize) {
+    let payload = match ron::to_string(payload) {
+        Ok(payload) => payload,
+        Err(e) => {log::error!("Failed to serialize sync message! {:#?}", e); return;},
+    };
+    SOCKET.with(|socket| {
+        if let Some(socket) = socket.borrow().as_ref() {
+            if let Err(e) = socket.send_with_str(&format!("{} {}", kind, payload)) {
+                log::warn!("Failed to send to the sync server; {:?}", e);
+            }
+        }
+    });
+}
+
+/// Open a socket to the relay, passing everything it says to the business. [`SyncEvent::Connecting`] should be
+/// dispatched alongside.
+pub fn connect(url: &str, business: BusinessContext) -> Result<(), String> {
+    disconnect();
+    let socket = WebSocket::new(url).map_err(|e| e.as_string().unwrap_or(format!("{:?}", e)))?;
+    let onmessage = {
+        let business = business.clone();
+        Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
+            match e.data().as_string().as_deref().and_then(parse_message) {
+                Some(event) => business.dispatch(BusinessEvents::Sync(event)),
+                None => log::warn!("Unreadable message from the sync server"),
+            }
+        })
+    };
+    let onclose = Closure::<dyn FnMut()>::new(move || business.dispatch(BusinessEvents::Sync(SyncEvent::Closed)));
+    socket.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    socket.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+    // The socket owns its handlers from here on
+    onmessage.forget();
+    onclose.forget();
+    SOCKET.with(|cell| *cell.borrow_mut() = Some(socket));
+    Ok(())
+}
+
+/// Close the socket to the relay. [`SyncEvent::Stop`] should be dispatched alongside.
+pub fn disconnect() {
+    if let Some(socket) = SOCKET.with(|cell| cell.borrow_mut().take()) {
+        socket.set_onclose(None);
+        let _ = socket.close();
+    }
+}
+
+impl Business {
+    /// A copy of the business without the notices for this editor
+    pub fn document(&self) -> Business {
+        let mut document = self.clone();
+        document.notices = Default::default();
+        document
+    }
+
+    /// Take on what another version of the business shares with other editors, keeping this editor's notices
+    pub fn replace_document(&mut self, other: Business) {
+        self.roles = other.roles;
+        self.employees = other.employees;
+        self.days_recorded = other.days_recorded;
+        self.role_colors = other.role_colors;
+        self.blocks = other.blocks;
+        self.open = other.open;
+        self.close = other.close;
+        self.block_size = other.block_size;
+        self.index_cache = IndexCache::default();
+    }
+
+    /// The whole business as one action, for starting a room off or replacing what it has
+    pub fn snapshot(&self) -> BusinessEvents {
+        BusinessEvents::Snapshot {
+            business: Box::new(self.document()),
+            open: self.open,
+            close: self.close,
+            block_size: self.block_size,
+            schedule: schedule_to_csv(self),
+        }
+    }
+}
+
+impl Workspace {
+    /// Send an action just taken here to the other editors. `editing` is the scenario it was taken in, if any; only
+    /// making a scenario the plan leaves one, and then the whole new plan is sent, as it is after restoring a backup.
+    pub fn share(&mut self, action: BusinessEvents, editing: Option<usize>, entry: Option<&AuditEntry>) {
+        let (kind, action) = match action {
+            BusinessEvents::PromoteScenario { .. } | BusinessEvents::Restore { .. } => ('S', self.plan().snapshot()),
+            action if editing.is_none() && action.is_shared() => ('M', action),
+            _ => return,
+        };
+        let sync = match self.sync.as_mut() {
+            Some(sync) if sync.client.is_some() => sync,
+            _ => return,
+        };
+        send(kind, &action);
+        sync.pending.push_back(action);
+        if let Some(entry) = entry {
+            send('P', &Presence { name: sync.name.clone(), employee: entry.employees.first().copied(), action: entry.action.clone() });
+        }
+    }
+
+    /// Apply news from the relay. Returns another editor's action, if it changed the plan.
+    pub fn receive_sync(&mut self, event: SyncEvent) -> Option<RemoteEdit> {
+        match event {
+            SyncEvent::Connecting { url, name } => {
+                self.sync = Some(Box::new(SyncState {
+                    url, name, client: None, seq: 0, confirmed: self.plan().document(), pending: VecDeque::new(), peers: vec![],
+                }));
+            },
+            SyncEvent::Welcome { client, history } => {
+                let document = self.plan().document();
+                let snapshot = self.plan().snapshot();
+                let sync = self.sync.as_mut()?;
+                sync.client = Some(client);
+                sync.confirmed = document;
+                sync.pending.clear();
+                send('P', &Presence { name: sync.name.clone(), employee: None, action: "Joined".into() });
+                match history {
+                    0 => {
+                        send('S', &snapshot);
+                        sync.pending.push_back(snapshot);
+                    },
+                    _ => {
+                        let message = format!("Joined {}; catching up on {} change(s)", sync.url, history);
+                        self.notices.push(Level::Info, message);
+                    },
+                }
+            },
+            SyncEvent::Received { seq, client, action } => {
+                let sync = self.sync.as_mut()?;
+                if seq <= sync.seq {
+                    return None;
+                }
+                sync.seq = seq;
+                sync.confirmed.apply(*action.clone());
+                sync.confirmed.notices = Default::default();
+                if sync.client == Some(client) {
+                    sync.pending.pop_front();
+                    return None;
+                }
+                let mut rebased = sync.confirmed.clone();
+                for pending in sync.pending.iter() {
+                    rebased.apply(pending.clone());
+                }
+                let editor = Some(sync.peer_name(client));
+                let before = self.plan().employees.clone();
+                let description = action.describe(self.plan(), &[]);
+                let hours = |business: &Business| (business.open, business.close, business.block_size);
+                let hours_changed = hours(self.plan()) != hours(&rebased);
+                self.plan_mut().replace_document(rebased);
+                return Some(RemoteEdit { before, action: description, editor, hours_changed });
+            },
+            SyncEvent::Presence { client, presence } => {
+                let sync = self.sync.as_mut()?;
+                sync.peers.retain(|(id, _)| *id != client);
+                if let Some(presence) = presence {
+                    sync.peers.push((client, presence));
+                    sync.peers.sort_by_key(|(id, _)| *id);
+                }
+            },
+            SyncEvent::SnapshotWanted => {
+                let snapshot = self.plan().snapshot();
+                let sync = self.sync.as_mut().filter(|sync| sync.client.is_some())?;
+                send('S', &snapshot);
+                sync.pending.push_back(snapshot);
+            },
+            SyncEvent::Stop => self.sync = None,
+            SyncEvent::Closed => {
+                let sync = self.sync.take()?;
+                self.notices.push(Level::Warning, format!("Lost the connection to {}; changes are no longer shared", sync.url));
+            },
+        }
+        None
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{absence::{block_runs, Vacancy}, events::BusinessEvents};
+
+use super::{assert_consistent, assigned, sample, workspace};
+
+#[test]
+fn marking_absent_frees_their_roles() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0, 1, 2]).unwrap();
+    business.assign_block(1, 2, vec![3, 4]).unwrap();
+    business.assign_block(1, 4, vec![5, 6]).unwrap();
+    business.toggle_lock(1, 5).unwrap();
+    let absence = business.mark_absent(1).unwrap();
+
+    assert_eq!(absence.employee, 1);
+    // Lunch isn't listed
+    assert_eq!(absence.vacancies, vec![
+        Vacancy { role: 3, blocks: vec![0, 1, 2] },
+        Vacancy { role: 4, blocks: vec![5, 6] },
+    ]);
+    assert!(!business.employees[&1].scheduled);
+    assert!(business.employees[&1].assignments.is_empty());
+    assert_consistent(&business);
+    assert!(business.mark_absent(9).is_err());
+}
+
+#[test]
+fn replacements_are_ranked() {
+    let mut business = sample();
+    business.assign_block(1, 3, (0..8).collect()).unwrap();
+    // Employee 2 is busy for some of it, and employee 4 isn't in until 11
+    business.assign_block(2, 4, vec![0, 1]).unwrap();
+    let absence = business.mark_absent(1).unwrap();
+
+    let ranked: Vec<(usize, usize)> = business.replacements(&absence).iter().map(|replacement| (replacement.employee, replacement.blocks)).collect();
+    assert_eq!(ranked, vec![(3, 8), (2, 6), (4, 4)]);
+    assert!(business.replacements(&absence).iter().all(|replacement| replacement.roles == 1));
+}
+
+#[test]
+fn fewer_hours_ranks_first() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![10, 11]).unwrap();
+    business.restrict_role(3, 3).unwrap();
+    business.update_employee_hours(2, business.open, chrono::NaiveTime::from_hms_opt(18, 0, 0).unwrap()).unwrap();
+    let absence = business.mark_absent(1).unwrap();
+    let ranked: Vec<usize> = business.replacements(&absence).iter().map(|replacement| replacement.employee).collect();
+    // Both are free for all of it; employee 4 works 8 hours to employee 2's 9
+    assert_eq!(ranked, vec![4, 2]);
+}
+
+#[test]
+fn applying_a_replacement() {
+    let mut business = sample();
+    business.assign_block(1, 3, (0..8).collect()).unwrap();
+    business.assign_block(2, 4, vec![0, 1]).unwrap();
+    let mut absence = business.mark_absent(1).unwrap();
+
+    assert_eq!(business.apply_replacement(2, &mut absence).unwrap(), 6);
+    assert_eq!(&assigned(&business, 2)[0..8], &[4, 4, 3, 3, 3, 3, 3, 3]);
+    assert_eq!(absence.vacancies, vec![Vacancy { role: 3, blocks: vec![0, 1] }]);
+    // Taking the rest leaves nothing to cover
+    assert_eq!(business.apply_replacement(3, &mut absence).unwrap(), 2);
+    assert!(absence.is_covered());
+    assert!(business.replacements(&absence).is_empty());
+    assert_eq!(business.apply_replacement(4, &mut absence).unwrap(), 0);
+    assert_consistent(&business);
+}
+
+#[test]
+fn the_assistant_is_kept_beside_the_business() {
+    let mut business = workspace();
+    business.assign_block(1, 3, (0..8).collect()).unwrap();
+    business.apply(BusinessEvents::MarkAbsent { employee: 1 });
+    let absence = business.absence.clone().unwrap();
+    business.apply(BusinessEvents::ApplyReplacement { employee: 3, absence });
+    assert!(business.absence.as_ref().unwrap().is_covered());
+    assert_eq!(&assigned(&business, 3)[0..8], &[3; 8]);
+
+    // It was found in the plan, so doesn't follow into a scenario
+    business.apply(BusinessEvents::NewScenario { name: "Short staffed".into() });
+    assert_eq!(business.absence, None);
+}
+
+#[test]
+fn runs_of_blocks() {
+    assert_eq!(block_runs(&[0, 1, 2, 5, 7, 8]), vec![(0, 2), (5, 5), (7, 8)]);
+    assert_eq!(block_runs(&[]), vec![]);
+}
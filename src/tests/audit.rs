@@ -0,0 +1,71 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::{audit::{audit_entry, audit_to_csv, filter_entries, AffectedBlocks}, events::BusinessEvents};
+
+use super::sample;
+
+fn noon() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 3, 4).unwrap().and_hms_opt(12, 0, 0).unwrap()
+}
+
+fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+#[test]
+fn entries_list_the_blocks_changed() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0, 1, 2, 3]).unwrap();
+    let before = business.employees.clone();
+    let action = BusinessEvents::SwapBlocks { first: 1, second: 2, blocks: vec![2, 3] };
+    let description = action.describe(&business, &[]);
+    business.swap_blocks(1, 2, vec![2, 3]).unwrap();
+
+    let entry = audit_entry(Some(&before), &business, None, description, Some("Sam".into()), noon());
+    assert_eq!(entry.action, "Swapped Employee 1 and Employee 2 at 10:00 to 11:00");
+    assert_eq!(entry.editor.as_deref(), Some("Sam"));
+    assert_eq!(entry.scenario, None);
+    assert_eq!(entry.employees, vec![1, 2]);
+    assert_eq!(entry.blocks, vec![
+        AffectedBlocks { employee: 1, name: "Employee 1".into(), spans: vec![(time(10, 0), time(11, 0))] },
+        AffectedBlocks { employee: 2, name: "Employee 2".into(), spans: vec![(time(10, 0), time(11, 0))] },
+    ]);
+    assert_eq!(entry.blocks[0].describe(), "Employee 1 10:00-11:00");
+}
+
+#[test]
+fn entries_without_blocks() {
+    let mut business = sample();
+    let before = business.employees.clone();
+    business.update_employee_wage(3, 1600).unwrap();
+    let entry = audit_entry(Some(&before), &business, None, "Set wage".into(), None, noon());
+    assert_eq!(entry.employees, vec![3]);
+    assert!(entry.blocks.is_empty());
+
+    // Switching to a scenario isn't a change to anyone
+    business.assign_block(1, 4, vec![0]).unwrap();
+    let entry = audit_entry(None, &business, Some("Quiet day".into()), "Started scenario".into(), None, noon());
+    assert!(entry.employees.is_empty());
+    assert_eq!(entry.scenario.as_deref(), Some("Quiet day"));
+}
+
+#[test]
+fn filtering_and_exporting() {
+    let mut business = sample();
+    let mut entries = vec![];
+    for (employee, block) in [(1, 0), (2, 1), (1, 2)] {
+        let before = business.employees.clone();
+        business.assign_block(employee, 3, vec![block]).unwrap();
+        entries.push(audit_entry(Some(&before), &business, None, format!("Assigned, \"{}\"", employee), None, noon()));
+    }
+    let shown: Vec<&str> = filter_entries(&entries, Some(1)).iter().map(|entry| entry.action.as_str()).collect();
+    assert_eq!(shown, vec!["Assigned, \"1\"", "Assigned, \"1\""]);
+    assert_eq!(filter_entries(&entries, Some(2)).len(), 1);
+    assert_eq!(filter_entries(&entries, None).len(), 3);
+
+    let csv = audit_to_csv(&entries.iter().collect::<Vec<_>>());
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "time,editor,scenario,action,affected blocks");
+    assert_eq!(lines[1], "2025-03-04 12:00:00,,,\"Assigned, \"\"1\"\"\",Employee 1 9:00-9:30");
+    assert_eq!(lines.len(), 4);
+}
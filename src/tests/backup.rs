@@ -0,0 +1,85 @@
+use chrono::{NaiveTime, TimeDelta};
+
+use crate::{backup::{read_backup, Backup, BackupError, BACKUP_VERSION}, settings::{PrintStyle, Settings}};
+
+use crate::workspace::Workspace;
+
+use super::{assert_consistent, assigned, sample, workspace};
+
+#[test]
+fn backups_restore_everything() {
+    let mut settings = Settings::default();
+    settings.app.lunch_overlap = 1;
+    settings.print.style = PrintStyle::None;
+    let mut business = workspace();
+    business.assign_block(1, 3, vec![0, 1]).unwrap();
+    business.toggle_lock(1, 0).unwrap();
+    business.new_scenario("Short day".into());
+    let close = business.close;
+    business.update_business_hours(NaiveTime::from_hms_opt(10, 0, 0).unwrap(), close, TimeDelta::minutes(15));
+    business.assign_block(2, 4, vec![4]).unwrap();
+
+    let file = Backup::new(&business, &settings).to_file().unwrap();
+    assert!(file.starts_with(&format!("daily-planner backup {}\n", BACKUP_VERSION)));
+    let backup = read_backup(&file).unwrap();
+    assert_eq!(backup.settings, settings);
+
+    let mut restored = workspace();
+    restored.new_scenario("Gone".into());
+    restored.restore(backup);
+    assert_eq!(restored.editing, None);
+    assert_eq!(restored.employees, business.plan().employees);
+    assert!(restored.employees[&1].assignments[0].locked);
+    assert_eq!(restored.scenarios.len(), 1);
+    assert_eq!(restored.scenarios[0].name.as_str(), "Short day");
+    let scenario = &restored.scenarios[0].business;
+    assert_eq!((scenario.open, scenario.block_size), (business.open, business.block_size));
+    assert_eq!(assigned(scenario, 2), assigned(&business, 2));
+    assert_consistent(&restored);
+    assert_consistent(scenario);
+}
+
+#[test]
+fn older_backups_are_migrated() {
+    let mut business = sample();
+    business.update_employee_wage(3, 1800).unwrap();
+
+    // Version 0 is the business as the page hash keeps it, without a header
+    let backup = read_backup(&ron::to_string(&business).unwrap()).unwrap();
+    assert_eq!(backup.settings, Settings::default());
+    assert_eq!(backup.business.employees[&3].wage, 1800);
+    assert!(backup.scenarios.is_empty());
+
+    // Settings added since a backup was made are left at their defaults
+    let file = Backup::new(&Workspace::new(business), &Settings::default()).to_file().unwrap();
+    let older = file.replace("max_role_switches:", "unknown_setting:");
+    assert_ne!(older, file);
+    assert_eq!(read_backup(&older).unwrap().settings, Settings::default());
+}
+
+#[test]
+fn bad_backups_are_refused() {
+    let file = Backup::new(&workspace(), &Settings::default()).to_file().unwrap();
+    let body = file.split_once('\n').unwrap().1;
+
+    assert_eq!(read_backup(&format!("daily-planner backup 99\n{}", body)), Err(BackupError::NewerVersion { version: 99 }));
+    assert!(matches!(read_backup(&format!("daily-planner backup one\n{}", body)), Err(BackupError::BadHeader { .. })));
+    assert!(matches!(read_backup("name,roles\nSam,Register"), Err(BackupError::Unreadable(_))));
+
+    let mut backup = read_backup(&file).unwrap();
+    backup.settings.app.close = backup.settings.app.open;
+    assert!(matches!(read_backup(&backup.to_file().unwrap()), Err(BackupError::Invalid(_))));
+
+    // The link would drop a block size which doesn't divide the day, so a backup can't bring one back
+    let mut backup = read_backup(&file).unwrap();
+    backup.settings.app.block_size = TimeDelta::minutes(7);
+    assert!(matches!(read_backup(&backup.to_file().unwrap()), Err(BackupError::Invalid(_))));
+
+    let mut backup = read_backup(&file).unwrap();
+    backup.business.roles.remove(&2);
+    assert!(matches!(read_backup(&backup.to_file().unwrap()), Err(BackupError::Invalid(_))));
+
+    let mut backup = read_backup(&file).unwrap();
+    backup.schedule = "1,9:00,x,--".into();
+    assert!(matches!(read_backup(&backup.to_file().unwrap()), Err(BackupError::Invalid(_))));
+}
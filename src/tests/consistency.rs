@@ -0,0 +1,114 @@
+use proptest::prelude::*;
+
+use crate::{consistency::Inconsistency, data::{Assignment, Business}};
+
+use super::{assert_consistent, assigned, random::{apply, op}, sample};
+
+#[test]
+fn sample_is_consistent() {
+    assert_eq!(sample().check_consistency(), vec![]);
+}
+
+#[test]
+fn delete_employee_leaves_multi_roles() {
+    let mut business = sample();
+    business.assign_block(1, 2, vec![4, 5]).unwrap();
+    business.assign_block(2, 2, vec![5]).unwrap();
+    business.delete_employee(1);
+    assert_eq!(business.check_consistency(), vec![]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn update_employee_hours_clears_multi_roles() {
+    let mut business = sample();
+    business.assign_block(1, 2, vec![4, 5]).unwrap();
+    business.update_employee_hours(1, business.block_time(0), business.block_time(10)).unwrap();
+    assert_eq!(business.check_consistency(), vec![]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn delete_role_forgets_allowed_role() {
+    let mut business = sample();
+    business.delete_role(4);
+    assert!(business.employees.values().all(|emp| !emp.roles.contains(&4)));
+    // A new role reusing the id isn't given to everyone who could work the old one
+    business.new_role("Register".into());
+    assert!(business.roles.contains_key(&4));
+    assert!(business.employees.values().all(|emp| !emp.roles.contains(&4)));
+}
+
+/// Give an employee a role over a block without any of the usual checks
+fn force_block(business: &mut Business, employee: usize, block: usize, role: usize) {
+    let (start, end) = business.grid().block_span(block);
+    business.employees.get_mut(&employee).unwrap().assignments.push(Assignment { role, start, end, locked: false });
+}
+
+#[test]
+fn finds_and_repairs_problems() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0, 1]).unwrap();
+    business.assign_block(2, 4, vec![2]).unwrap();
+    // Assigned a role that doesn't exist or isn't allowed
+    force_block(&mut business, 4, 8, 7);
+    force_block(&mut business, 1, 9, 5);
+    // Allowed a role that doesn't exist
+    business.employees.get_mut(&2).unwrap().roles.push(8);
+    // Two employees on a single role
+    force_block(&mut business, 3, 1, 3);
+
+    let found = business.check_consistency();
+    assert_eq!(found, vec![
+        Inconsistency::NotAllowed { employee: 1, role: 5, start: business.block_time(9) },
+        Inconsistency::UnknownAllowedRole { employee: 2, role: 8 },
+        Inconsistency::UnknownRole { employee: 4, role: 7, start: business.block_time(8) },
+        Inconsistency::OverCapacity { role: 3, start: business.block_time(1), employees: vec![1, 3] },
+    ]);
+
+    assert_eq!(business.repair(), found);
+    assert_consistent(&business);
+    // Employee schedules are kept where they could be
+    assert_eq!(assigned(&business, 1)[0..2], [3, 3]);
+    assert_eq!(assigned(&business, 2)[2], 4);
+    assert_eq!(assigned(&business, 3)[1], 1);
+    assert_eq!(assigned(&business, 4)[8], 1);
+    assert_eq!(assigned(&business, 1)[9], 1);
+    assert!(!business.employees[&2].roles.contains(&8));
+}
+
+#[test]
+fn repairs_misplaced_assignments() {
+    let mut business = sample();
+    business.assign_block(2, 3, vec![4, 5]).unwrap();
+    // Overlapping the existing run, and past clock-out for employee 3
+    force_block(&mut business, 2, 5, 4);
+    force_block(&mut business, 3, 18, 3);
+    let found = business.repair();
+    assert_eq!(found, vec![Inconsistency::Misplaced { employee: 2 }, Inconsistency::Misplaced { employee: 3 }]);
+    assert_consistent(&business);
+    assert_eq!(assigned(&business, 2)[4..7], [3, 3, 1]);
+    assert!(business.employees[&3].assignments.is_empty());
+}
+
+proptest! {
+    #[test]
+    fn operations_stay_consistent(ops in prop::collection::vec(op(), 1..60)) {
+        let mut business = sample();
+        for op in ops {
+            apply(&mut business, op);
+            prop_assert_eq!(business.check_consistency(), vec![]);
+        }
+    }
+
+    #[test]
+    fn repair_keeps_consistent_schedules(ops in prop::collection::vec(op(), 1..40)) {
+        let mut business = sample();
+        for op in ops {
+            apply(&mut business, op);
+        }
+        let before = business.employees.clone();
+        prop_assert_eq!(business.repair(), vec![]);
+        prop_assert_eq!(business.employees, before);
+    }
+}
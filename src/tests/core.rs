@@ -0,0 +1,387 @@
+use chrono::TimeDelta;
+
+use crate::{bulk::GridSelection, data::{BusinessError, EmployeeError, LostTime, RoleTrait, Shift}, index::ScheduleIndex, scheduler::TimeBlock};
+
+use super::{assert_consistent, assigned, drag_run, sample};
+
+fn role_assigned(business: &crate::data::Business, role: usize) -> Vec<Vec<usize>> {
+    ScheduleIndex::new(business).role_assigned(&business.roles[&role]).into()
+}
+
+#[test]
+fn assign_block_shows_in_role_view() {
+    let mut business = sample();
+    assert_eq!(business.assign_block(1, 3, vec![0, 1, 2]).unwrap(), 3);
+    assert_eq!(assigned(&business, 1)[0..4], [3, 3, 3, 1]);
+    assert_eq!(role_assigned(&business, 3)[1], vec![1]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn assign_block_displaces_single_role_holder() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0, 1, 2]).unwrap();
+    business.assign_block(2, 3, vec![2, 3]).unwrap();
+    assert_eq!(assigned(&business, 1)[0..4], [3, 3, 1, 1]);
+    assert_eq!(assigned(&business, 2)[0..4], [1, 1, 3, 3]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn assign_block_replaces_previous_role() {
+    let mut business = sample();
+    business.assign_block(2, 3, vec![0, 1]).unwrap();
+    business.assign_block(2, 4, vec![1]).unwrap();
+    assert_eq!(role_assigned(&business, 3)[1], vec![0]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn assign_block_over_lunch_releases_lunch() {
+    let mut business = sample();
+    business.assign_block(1, 2, vec![5, 6]).unwrap();
+    business.assign_block(1, 3, vec![5]).unwrap();
+    assert!(!role_assigned(&business, 2)[5].contains(&1));
+    assert!(role_assigned(&business, 2)[6].contains(&1));
+    assert_consistent(&business);
+}
+
+#[test]
+fn assign_block_skips_clocked_out_and_out_of_range() {
+    let mut business = sample();
+    // Employee 4 clocks in at 11:00, the fifth block
+    assert_eq!(business.assign_block(4, 3, vec![2, 3, 4, 40]).unwrap(), 1);
+    assert_eq!(assigned(&business, 4)[2..5], [0, 0, 3]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn assign_block_rejects_disallowed_role() {
+    let mut business = sample();
+    match business.assign_block(1, 5, vec![0]) {
+        Err(BusinessError::EmployeeError(EmployeeError::NotAssignedRole { employee: 1, failed: 5, .. })) => {},
+        other => panic!("expected NotAssignedRole, got {:?}", other),
+    }
+    assert!(matches!(business.assign_block(9, 3, vec![0]), Err(BusinessError::EmployeeNotFound)));
+    assert!(matches!(business.assign_block(1, 9, vec![0]), Err(BusinessError::RoleNotFound)));
+    assert_consistent(&business);
+}
+
+#[test]
+fn remove_block_frees_role() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0, 1, 2]).unwrap();
+    business.assign_block(2, 2, vec![0, 1]).unwrap();
+    business.remove_block(1, vec![1, 2, 30]).unwrap();
+    business.remove_block(2, vec![0]).unwrap();
+    assert_eq!(assigned(&business, 1)[0..3], [3, 1, 1]);
+    assert_eq!(role_assigned(&business, 3)[1], vec![0]);
+    assert!(!role_assigned(&business, 2)[0].contains(&2));
+    assert!(role_assigned(&business, 2)[1].contains(&2));
+    assert_consistent(&business);
+}
+
+#[test]
+fn remove_block_keeps_clocked_out() {
+    let mut business = sample();
+    business.remove_block(4, vec![0]).unwrap();
+    assert_eq!(assigned(&business, 4)[0], 0);
+}
+
+#[test]
+fn delete_role_frees_employees() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0, 1]).unwrap();
+    business.assign_block(2, 3, vec![5]).unwrap();
+    business.assign_block(2, 2, vec![6, 7]).unwrap();
+    business.delete_role(3);
+    business.delete_role(2);
+    assert!(!business.roles.contains_key(&3));
+    assert_eq!(assigned(&business, 1)[0..2], [1, 1]);
+    assert_eq!(assigned(&business, 2)[5..8], [1, 1, 1]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn toggle_role_multi_keeps_assignments() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0, 1]).unwrap();
+    business.assign_block(2, 3, vec![2]).unwrap();
+    let sort = business.roles[&3].sort();
+    business.toggle_role_multi(3);
+    assert!(business.roles[&3].is_multi());
+    assert_eq!(business.roles[&3].sort(), sort);
+    assert_consistent(&business);
+
+    // Several employees may now share a block
+    business.assign_block(2, 3, vec![0]).unwrap();
+    assert_eq!(assigned(&business, 1)[0], 3);
+    assert_consistent(&business);
+
+    // Going back to single keeps one of them
+    business.toggle_role_multi(3);
+    assert!(!business.roles[&3].is_multi());
+    assert_eq!(role_assigned(&business, 3)[0].len(), 1);
+    assert_eq!(assigned(&business, 1)[1], 3);
+    assert_consistent(&business);
+}
+
+#[test]
+fn index_is_built_once_per_state() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0]).unwrap();
+    let index = business.index();
+    assert!(std::rc::Rc::ptr_eq(&index, &business.index()));
+    assert_eq!(index.working(3, 0), [1]);
+
+    // The reducer clears the cache before each action
+    business.index_cache = Default::default();
+    business.assign_block(2, 3, vec![0]).unwrap();
+    assert_eq!(business.index().working(3, 0), [2]);
+    assert_eq!(index.working(3, 0), [1]);
+}
+
+#[test]
+fn drag_run_moves_relative_to_held_block() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![2, 3, 4]).unwrap();
+    // Picked up by its middle block and dropped on block 8, so it now covers 7-9
+    drag_run(&mut business, 1, 3, 1, 8).unwrap();
+    assert_eq!(assigned(&business, 1)[1..11], [1, 1, 1, 1, 1, 1, 3, 3, 3, 1]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn drag_run_overlapping_itself() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![2, 3, 4]).unwrap();
+    drag_run(&mut business, 1, 2, 1, 3).unwrap();
+    assert_eq!(assigned(&business, 1)[1..7], [1, 1, 3, 3, 3, 1]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn drag_run_to_other_employee() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![2, 3, 4]).unwrap();
+    drag_run(&mut business, 1, 4, 2, 4).unwrap();
+    assert_eq!(assigned(&business, 1)[2..5], [1, 1, 1]);
+    assert_eq!(assigned(&business, 2)[2..5], [3, 3, 3]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn drag_run_past_open_drops_blocks() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![2, 3, 4]).unwrap();
+    // Held by the last block and dropped on the first, so two blocks would land before open
+    drag_run(&mut business, 1, 4, 1, 0).unwrap();
+    assert_eq!(assigned(&business, 1)[0..5], [3, 1, 1, 1, 1]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn drag_run_past_close_drops_blocks() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![2, 3, 4]).unwrap();
+    drag_run(&mut business, 1, 2, 1, 19).unwrap();
+    assert_eq!(assigned(&business, 1)[17..20], [1, 1, 3]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn drag_run_to_disallowed_employee_changes_nothing() {
+    let mut business = sample();
+    business.assign_block(2, 5, vec![2, 3]).unwrap();
+    assert!(drag_run(&mut business, 2, 2, 1, 2).is_err());
+    assert_eq!(assigned(&business, 2)[2..4], [5, 5]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn drag_run_to_role_key_clears() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![2, 3, 4]).unwrap();
+    drag_run(&mut business, 1, 3, 0, 0).unwrap();
+    assert_eq!(assigned(&business, 1)[2..5], [1, 1, 1]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn drag_from_role_key_assigns() {
+    let mut business = sample();
+    let key_block = TimeBlock::new_simple(0, 0, 4);
+    business.drag_assign_block(&TimeBlock::new_simple(2, 6, 1), &key_block, &key_block).unwrap();
+    assert_eq!(assigned(&business, 2)[6], 4);
+    assert_consistent(&business);
+}
+
+#[test]
+fn block_size_change_keeps_schedule() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![2, 3]).unwrap();
+    business.assign_block(2, 2, vec![6]).unwrap();
+    let before = business.employees.clone();
+    business.update_business_hours(business.open, business.close, TimeDelta::minutes(15));
+    assert_eq!(business.blocks, 40);
+    assert_eq!(assigned(&business, 1)[4..8], [3, 3, 3, 3]);
+    assert_eq!(assigned(&business, 2)[11..15], [1, 2, 2, 1]);
+    assert_consistent(&business);
+
+    // Going back shows the same schedule, as nothing was rounded
+    business.update_business_hours(business.open, business.close, TimeDelta::minutes(30));
+    assert_eq!(business.employees, before);
+}
+
+#[test]
+fn business_hours_change_trims_schedule() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0, 1, 2]).unwrap();
+    business.assign_block(2, 4, vec![18, 19]).unwrap();
+    let (open, close) = (business.block_time(1), business.block_time(19));
+    business.update_business_hours(open, close, business.block_size);
+    assert_eq!(assigned(&business, 1)[0..3], [3, 3, 1]);
+    assert_eq!(assigned(&business, 2)[16..18], [1, 4]);
+    assert_eq!(business.employees[&2].assignments[0].end, close);
+    assert_consistent(&business);
+}
+
+#[test]
+fn employee_hours_change_trims_schedule() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![2, 3, 4]).unwrap();
+    business.update_employee_hours(1, business.block_time(3), business.block_time(10)).unwrap();
+    assert_eq!(assigned(&business, 1)[2..5], [0, 3, 3]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn assignments_join_up_and_split() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![2, 3]).unwrap();
+    business.assign_block(1, 3, vec![4]).unwrap();
+    assert_eq!(business.employees[&1].assignments.len(), 1);
+    business.remove_block(1, vec![3]).unwrap();
+    let assignments = &business.employees[&1].assignments;
+    assert_eq!(assignments.len(), 2);
+    assert_eq!((assignments[0].end, assignments[1].start), (business.block_time(3), business.block_time(4)));
+}
+
+#[test]
+fn block_size_change_rounds_runs() {
+    let mut business = sample();
+    business.update_business_hours(business.open, business.close, TimeDelta::minutes(20));
+    // 9:20-10:20 and 10:40-11:00
+    business.assign_block(1, 3, vec![1, 2, 3]).unwrap();
+    business.assign_block(1, 4, vec![5]).unwrap();
+    let lost = business.preview_business_hours(business.open, business.close, TimeDelta::minutes(30));
+    business.update_business_hours(business.open, business.close, TimeDelta::minutes(30));
+    // 9:20 rounds to 9:30, 10:20 to 10:30, and 10:40 to 10:30
+    assert_eq!(assigned(&business, 1)[0..5], [1, 3, 3, 4, 1]);
+    assert_eq!(lost, vec![LostTime { employee: 1, role: 3, start: business.block_time(0) + TimeDelta::minutes(20), end: business.block_time(1) }]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn preview_lists_time_outside_new_hours() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0, 1, 2]).unwrap();
+    business.assign_block(2, 2, vec![19]).unwrap();
+    let (open, close) = (business.block_time(2), business.block_time(19));
+    let before = business.employees.clone();
+    let lost = business.preview_business_hours(open, close, business.block_size);
+    assert_eq!(business.employees, before);
+    assert_eq!(lost, vec![
+        LostTime { employee: 1, role: 3, start: business.block_time(0), end: open },
+        LostTime { employee: 2, role: 2, start: close, end: business.close },
+    ]);
+    assert!(business.preview_business_hours(business.open, business.close, TimeDelta::minutes(15)).is_empty());
+}
+
+#[test]
+fn split_shift_leaves_time_off() {
+    let mut business = sample();
+    // 9-11 and 3-7
+    let shifts = vec![
+        Shift { clock_in: business.block_time(0), clock_out: business.block_time(4) },
+        Shift { clock_in: business.block_time(12), clock_out: business.close },
+    ];
+    business.update_employee_shifts(1, shifts).unwrap();
+    business.assign_block(1, 3, (0..business.blocks).collect()).unwrap();
+    assert_eq!(assigned(&business, 1)[2..14], [3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 3, 3]);
+    assert_eq!(business.employees[&1].assignments.len(), 2);
+    assert_eq!(business.totals().employees.iter().find(|totals| totals.employee == 1).unwrap().clocked, TimeDelta::hours(6));
+    assert!(business.assign_span(1, 4, business.block_time(5), business.block_time(9)).is_err());
+    assert_consistent(&business);
+
+    // Joining the shifts back up leaves the time off available
+    business.update_employee_shift(1, 0, business.block_time(0), business.block_time(12)).unwrap();
+    assert_eq!(business.employees[&1].shifts.len(), 1);
+    assert_eq!(assigned(&business, 1)[2..14], [3, 3, 1, 1, 1, 1, 1, 1, 1, 1, 3, 3]);
+    assert!(business.update_employee_shift(1, 0, business.block_time(5), business.block_time(4)).is_err());
+    assert_consistent(&business);
+}
+
+#[test]
+fn adding_and_removing_shifts() {
+    let mut business = sample();
+    business.update_employee_hours(3, business.open, business.block_time(6)).unwrap();
+    business.add_employee_shift(3).unwrap();
+    let shifts = &business.employees[&3].shifts;
+    assert_eq!((shifts[1].clock_in, shifts[1].clock_out), (business.block_time(7), business.close));
+    assert!(matches!(business.add_employee_shift(3), Err(BusinessError::NoTimeForShift)));
+
+    business.assign_block(3, 3, vec![8, 9]).unwrap();
+    business.remove_employee_shift(3, 1).unwrap();
+    assert!(business.employees[&3].assignments.is_empty());
+    assert!(business.remove_employee_shift(3, 0).is_err());
+    assert_consistent(&business);
+}
+
+#[test]
+fn locking_covers_the_whole_run() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![2, 3, 4]).unwrap();
+    business.toggle_lock(1, 3).unwrap();
+    let grid = business.grid();
+    let emp = &business.employees[&1];
+    assert!((2..5).all(|block| emp.locked_at(&grid, block)));
+    assert!(!emp.locked_at(&grid, 5));
+
+    // Growing the run adds unlocked time next to the locked part, and unlocking joins them back up
+    business.assign_block(1, 3, vec![5]).unwrap();
+    assert_eq!(business.employees[&1].assignments.len(), 2);
+    business.toggle_lock(1, 5).unwrap();
+    assert_eq!(business.employees[&1].assignments.len(), 1);
+    assert!(business.employees[&1].assignments[0].locked);
+    business.toggle_lock(1, 2).unwrap();
+    assert!(!business.employees[&1].assignments[0].locked);
+    // Nothing to lock on available time
+    business.toggle_lock(1, 10).unwrap();
+    assert_consistent(&business);
+}
+
+#[test]
+fn copy_lines_up_with_the_selection() {
+    let mut business = sample();
+    business.assign_block(2, 4, vec![2, 3]).unwrap();
+    let selection = GridSelection { employees: vec![99, 2], blocks: vec![2, 3] };
+    assert_eq!(business.copy_blocks(&selection), vec![vec![0, 0], vec![4, 4]]);
+}
+
+#[test]
+fn shift_with_missing_employee_changes_nothing() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![2, 3]).unwrap();
+    business.assign_block(2, 4, vec![2, 3]).unwrap();
+    let before = business.employees.clone();
+    let selection = GridSelection { employees: vec![1, 99, 2], blocks: vec![2, 3] };
+    assert!(matches!(business.shift_blocks(&selection, 1), Err(BusinessError::EmployeeNotFound)));
+    assert_eq!(business.employees, before);
+
+    let selection = GridSelection { employees: vec![1, 2], blocks: vec![2, 3] };
+    business.shift_blocks(&selection, 1).unwrap();
+    assert_eq!(assigned(&business, 1)[2..5], [1, 3, 3]);
+    assert_eq!(assigned(&business, 2)[2..5], [1, 4, 4]);
+    assert_consistent(&business);
+}
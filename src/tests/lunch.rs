@@ -0,0 +1,122 @@
+use chrono::NaiveTime;
+
+use crate::{data::{Business, Shift}, index::ScheduleIndex, notifications::Level, settings::AppSettings};
+
+use super::{assert_consistent, assigned, sample};
+
+/// The blocks each employee is at lunch for
+fn lunches(business: &Business) -> Vec<(usize, Vec<usize>)> {
+    let mut lunches: Vec<(usize, Vec<usize>)> = business.employees.keys()
+        .map(|id| (*id, assigned(business, *id).iter().enumerate().filter(|(_, role)| **role == 2).map(|(block, _)| block).collect()))
+        .collect();
+    lunches.sort();
+    lunches
+}
+
+#[test]
+fn lunches_are_staggered_within_the_window() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0, 1, 2]).unwrap();
+    let settings = AppSettings { lunch_overlap: 1, lunch_keep_roles: false, ..AppSettings::default() };
+    business.schedule_lunch(&settings);
+
+    let (earliest, latest) = (settings.lunch_earliest, settings.lunch_latest);
+    let mut at_once = vec![0; business.blocks];
+    for (id, blocks) in lunches(&business) {
+        let emp = &business.employees[&id];
+        match emp.scheduled {
+            true => assert_eq!(blocks.len(), emp.lunch, "employee {}", id),
+            false => assert!(blocks.is_empty()),
+        }
+        for block in blocks {
+            let (start, end) = business.grid().block_span(block);
+            assert!(start >= earliest && end <= latest, "employee {} at lunch in block {}", id, block);
+            at_once[block] += 1;
+        }
+    }
+    assert!(at_once.iter().all(|count| *count <= 1), "{:?}", at_once);
+    // Without keeping roles, the schedule starts over
+    assert!(!assigned(&business, 1).contains(&3));
+    assert!(business.notices.items.is_empty(), "{:?}", business.notices.items);
+    assert_consistent(&business);
+}
+
+#[test]
+fn lunch_keeps_roles_covered() {
+    let mut business = sample();
+    let all: Vec<usize> = (0..business.blocks).collect();
+    business.assign_block(1, 3, all.clone()).unwrap();
+    business.assign_block(2, 4, all).unwrap();
+    business.schedule_lunch(&AppSettings::default());
+
+    for (id, blocks) in lunches(&business).into_iter().filter(|(id, _)| *id <= 2) {
+        assert_eq!(blocks.len(), business.employees[&id].lunch);
+    }
+    let index = ScheduleIndex::new(&business);
+    for role in [3, 4] {
+        let assigned: Vec<usize> = index.role_assigned(&business.roles[&role]).into();
+        assert!(assigned.iter().all(|emp| *emp != 0), "role {} uncovered: {:?}", role, assigned);
+    }
+    assert!(business.notices.items.iter().all(|(level, _)| *level != Level::Warning), "{:?}", business.notices.items);
+    assert_consistent(&business);
+}
+
+#[test]
+fn lunch_moves_rather_than_piling_up() {
+    let mut business = sample();
+    let settings = AppSettings { lunch_overlap: 2, ..AppSettings::default() };
+    business.schedule_lunch(&settings);
+    business.schedule_lunch(&settings);
+    // Running it again moves lunches rather than adding to them
+    for (id, blocks) in lunches(&business).into_iter().filter(|(id, _)| *id <= 4) {
+        assert_eq!(blocks.len(), business.employees[&id].lunch, "employee {}", id);
+    }
+    assert_consistent(&business);
+}
+
+#[test]
+fn lunch_goes_in_the_longest_shift() {
+    let mut business = sample();
+    // 9-10 and 1-7
+    let shifts = vec![
+        Shift { clock_in: business.block_time(0), clock_out: business.block_time(2) },
+        Shift { clock_in: business.block_time(8), clock_out: business.close },
+    ];
+    business.update_employee_shifts(1, shifts).unwrap();
+    let settings = AppSettings { lunch_latest: business.close, ..AppSettings::default() };
+    business.schedule_lunch(&settings);
+    let lunch = lunches(&business).into_iter().find(|(id, _)| *id == 1).unwrap().1;
+    assert!(lunch.iter().all(|block| *block >= 8), "{:?}", lunch);
+    assert_consistent(&business);
+}
+
+#[test]
+fn lunch_too_long_for_any_shift() {
+    let mut business = sample();
+    // Only in for the first block, too short for a two block lunch
+    business.update_employee_hours(1, business.open, business.block_time(1)).unwrap();
+    business.schedule_lunch(&AppSettings { lunch_earliest: NaiveTime::MIN, ..AppSettings::default() });
+    assert!(!assigned(&business, 1).contains(&2));
+    assert!(business.notices.items.iter().any(|(level, message)| *level == Level::Warning && message.contains("Employee 1")));
+    assert_consistent(&business);
+}
+
+#[test]
+fn locked_assignments_survive_lunch() {
+    let mut business = sample();
+    // 11:00-1:00 on role 3, right where employee 1's lunch would go
+    business.assign_block(1, 3, vec![4, 5, 6, 7]).unwrap();
+    business.toggle_lock(1, 5).unwrap();
+    business.assign_block(2, 4, vec![0, 1]).unwrap();
+    business.schedule_lunch(&AppSettings { lunch_keep_roles: false, ..AppSettings::default() });
+    assert_eq!(assigned(&business, 1)[4..8], [3, 3, 3, 3]);
+    assert!(!assigned(&business, 2).contains(&4));
+    assert_eq!(lunches(&business)[0].1.len(), 2);
+
+    // A locked lunch isn't moved or doubled up
+    let lunch = lunches(&business)[1].1.clone();
+    business.toggle_lock(2, lunch[0]).unwrap();
+    business.schedule_lunch(&AppSettings::default());
+    assert_eq!(lunches(&business)[1].1, lunch);
+    assert_consistent(&business);
+}
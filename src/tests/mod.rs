@@ -0,0 +1,72 @@
+//! Native tests for the scheduling core. Everything here works on [`Business`] and [`Workspace`] directly, since the
+//! page hash and other browser APIs are unavailable outside of wasm.
+
+mod absence;
+mod audit;
+mod backup;
+mod consistency;
+mod core;
+mod lunch;
+mod random;
+mod roles;
+mod roster;
+mod round_trip;
+mod scenarios;
+mod sync;
+mod totals;
+
+use crate::{data::{Business, RoleTrait}, index::ScheduleIndex, scheduler::TimeBlock, settings::Settings, workspace::Workspace};
+
+/// The sample business: 20 half-hour blocks from 9:00 to 19:00, lunch (2) and roles 3-5, employees 1-5
+pub fn sample() -> Business {
+    Business::sample(&Settings::default())
+}
+
+/// The sample business as the editor has it open, with no scenarios
+pub fn workspace() -> Workspace {
+    Workspace::new(sample())
+}
+
+/// Nothing [`Business::check_consistency`] would complain about, and every role view agrees with the employees
+pub fn assert_consistent(business: &Business) {
+    assert_eq!(business.check_consistency(), vec![]);
+    let index = ScheduleIndex::new(business);
+    let views: Vec<(usize, Vec<usize>)> = business.employees.values().map(|emp| (emp.id, emp.assigned(&business.grid()))).collect();
+    for role in business.roles.values() {
+        let assigned: Vec<Vec<usize>> = index.role_assigned(role).into();
+        assert_eq!(assigned.len(), business.blocks, "role {} has the wrong number of blocks", role.id());
+        for (block, emp_ids) in assigned.iter().enumerate() {
+            let mut working: Vec<usize> = views.iter()
+                .filter(|(_, assigned)| assigned[block] == role.id())
+                .map(|(id, _)| *id)
+                .collect();
+            working.sort();
+            let listed: Vec<usize> = emp_ids.iter().copied().filter(|id| *id != 0).collect();
+            assert_eq!(listed, working, "role {} at {}", role.id(), block);
+        }
+    }
+}
+
+/// An employee's schedule laid out on the business's blocks
+pub fn assigned(business: &Business, employee: usize) -> Vec<usize> {
+    business.employees[&employee].assigned(&business.grid())
+}
+
+/// The run of the same role containing the given block, as (role, start, len)
+pub fn run_at(business: &Business, employee: usize, block: usize) -> Option<(usize, usize, usize)> {
+    let assigned = &business.employees.get(&employee)?.assigned(&business.grid());
+    let role = *assigned.get(block)?;
+    let start = (0..=block).rev().take_while(|i| assigned[*i] == role).last()?;
+    let end = (block..assigned.len()).take_while(|i| assigned[*i] == role).last()?;
+    Some((role, start, end - start + 1))
+}
+
+/// Drag the run containing `block` by that block and drop it on the target cell, as the schedule grid does
+pub fn drag_run(business: &mut Business, employee: usize, block: usize, target_emp: usize, target_index: usize) -> crate::data::Result<()> {
+    let (role, start, len) = run_at(business, employee, block).expect("no run to drag");
+    let drag_block = TimeBlock { emp_id: employee, time_index: start, role, len, len_index: 0 };
+    let held_block = TimeBlock { emp_id: employee, time_index: block, role, len, len_index: block - start };
+    let target_role = business.employees.get(&target_emp).and_then(|emp| emp.assigned(&business.grid()).get(target_index).copied()).unwrap_or(role);
+    let target_block = TimeBlock::new_simple(target_emp, target_index, target_role);
+    business.drag_assign_block(&target_block, &drag_block, &held_block)
+}
@@ -0,0 +1,126 @@
+use chrono::{NaiveTime, TimeDelta};
+use proptest::prelude::*;
+
+use crate::data::{Business, RunEdge, Shift};
+
+use super::{assert_consistent, drag_run, run_at, sample};
+
+/// Employee and role ids include some which don't exist, and blocks run past close, so failures are exercised too
+#[derive(Clone, Debug)]
+pub enum Op {
+    Assign { employee: usize, role: usize, blocks: Vec<usize> },
+    Remove { employee: usize, blocks: Vec<usize> },
+    DeleteRole { role: usize },
+    ToggleMulti { role: usize },
+    NewRole,
+    AllowRole { employee: usize, role: usize },
+    Drag { employee: usize, block: usize, target: usize, target_block: usize },
+    Resize { employee: usize, block: usize, end: bool, target: usize },
+    Swap { first: usize, second: usize, blocks: Vec<usize> },
+    DeleteEmployee { employee: usize },
+    Lock { employee: usize, block: usize },
+    /// Clock times as blocks after open
+    Hours { employee: usize, clock_in: usize, clock_out: usize },
+    /// Clock times as blocks after open, in pairs for each shift
+    Shifts { employee: usize, clock_times: Vec<usize> },
+    /// Business hours in whole hours, and block size in minutes
+    BusinessHours { open: u32, close: u32, block_size: i64 },
+}
+
+pub fn op() -> impl Strategy<Value = Op> {
+    let employee = 0..7usize;
+    let role = 1..9usize;
+    let block = 0..24usize;
+    let blocks = prop::collection::vec(0..24usize, 0..8);
+    prop_oneof![
+        4 => (employee.clone(), role.clone(), blocks.clone()).prop_map(|(employee, role, blocks)| Op::Assign { employee, role, blocks }),
+        2 => (employee.clone(), blocks.clone()).prop_map(|(employee, blocks)| Op::Remove { employee, blocks }),
+        1 => role.clone().prop_map(|role| Op::DeleteRole { role }),
+        1 => role.clone().prop_map(|role| Op::ToggleMulti { role }),
+        1 => Just(Op::NewRole),
+        1 => (employee.clone(), role).prop_map(|(employee, role)| Op::AllowRole { employee, role }),
+        3 => (employee.clone(), block.clone(), employee.clone(), block.clone())
+            .prop_map(|(employee, block, target, target_block)| Op::Drag { employee, block, target, target_block }),
+        2 => (employee.clone(), block.clone(), any::<bool>(), block)
+            .prop_map(|(employee, block, end, target)| Op::Resize { employee, block, end, target }),
+        1 => (employee.clone(), employee.clone(), blocks).prop_map(|(first, second, blocks)| Op::Swap { first, second, blocks }),
+        1 => employee.clone().prop_map(|employee| Op::DeleteEmployee { employee }),
+        1 => (employee.clone(), 0..24usize).prop_map(|(employee, block)| Op::Lock { employee, block }),
+        1 => (employee.clone(), 0..20usize, 1..21usize).prop_map(|(employee, clock_in, clock_out)| Op::Hours { employee, clock_in, clock_out }),
+        1 => (employee.clone(), prop::collection::vec(0..21usize, 2..7))
+            .prop_map(|(employee, clock_times)| Op::Shifts { employee, clock_times }),
+        1 => (7..12u32, 15..21u32, prop::sample::select(vec![10i64, 15, 20, 30, 45, 60]))
+            .prop_map(|(open, close, block_size)| Op::BusinessHours { open, close, block_size }),
+    ]
+}
+
+/// Operations which leave the employee in place: deleting them deletes the next id along instead
+pub fn op_keeping(employee: usize) -> impl Strategy<Value = Op> {
+    op().prop_map(move |op| match op {
+        Op::DeleteEmployee { employee: deleted } if deleted == employee => Op::DeleteEmployee { employee: employee + 1 },
+        op => op,
+    })
+}
+
+/// Apply an operation the way the UI would; errors are expected for some inputs and ignored
+pub fn apply(business: &mut Business, op: Op) {
+    let _ = match op {
+        Op::Assign { employee, role, blocks } => business.assign_block(employee, role, blocks).map(|_| ()),
+        Op::Remove { employee, blocks } => business.remove_block(employee, blocks),
+        Op::DeleteRole { role } => { business.delete_role(role); Ok(()) },
+        Op::ToggleMulti { role } => { business.toggle_role_multi(role); Ok(()) },
+        Op::NewRole => { business.new_role("New Role".into()); Ok(()) },
+        Op::AllowRole { employee, role } => business.assign_role(employee, role),
+        Op::Drag { employee, block, target, target_block } => match run_at(business, employee, block) {
+            Some((role, _, _)) if role >= 2 => drag_run(business, employee, block, target, target_block),
+            _ => Ok(()),
+        },
+        Op::Resize { employee, block, end, target } => match run_at(business, employee, block) {
+            Some((role, start, len)) if role >= 2 => {
+                let edge = match end { true => RunEdge::End, false => RunEdge::Start };
+                business.resize_run(employee, role, start, len, edge, target)
+            },
+            _ => Ok(()),
+        },
+        Op::Swap { first, second, blocks } => business.swap_blocks(first, second, blocks),
+        Op::DeleteEmployee { employee } => { business.delete_employee(employee); Ok(()) },
+        Op::Lock { employee, block } => business.toggle_lock(employee, block),
+        Op::Hours { employee, clock_in, clock_out } => {
+            let (clock_in, clock_out) = (business.block_time(clock_in), business.block_time(clock_out.max(clock_in + 1)));
+            business.update_employee_hours(employee, clock_in, clock_out)
+        },
+        Op::Shifts { employee, clock_times } => {
+            let shifts = clock_times.chunks(2)
+                .map(|times| Shift { clock_in: business.block_time(times[0]), clock_out: business.block_time(*times.last().unwrap()) })
+                .collect();
+            business.update_employee_shifts(employee, shifts)
+        },
+        Op::BusinessHours { open, close, block_size } => {
+            let time = |hour| NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+            business.update_business_hours(time(open), time(close), TimeDelta::minutes(block_size));
+            Ok(())
+        },
+    };
+}
+
+proptest! {
+    #[test]
+    fn operations_keep_the_schedule_consistent(ops in prop::collection::vec(op(), 1..60)) {
+        let mut business = sample();
+        for op in ops {
+            apply(&mut business, op);
+            assert_consistent(&business);
+        }
+    }
+
+    #[test]
+    fn remove_block_restores_available((employee, ops) in (1..5usize).prop_flat_map(|employee| (Just(employee), prop::collection::vec(op_keeping(employee), 1..30)))) {
+        let mut business = sample();
+        for op in ops {
+            apply(&mut business, op);
+        }
+        business.remove_block(employee, (0..business.blocks).collect()).unwrap();
+        prop_assert!(business.employees[&employee].assignments.is_empty());
+        assert_consistent(&business);
+    }
+}
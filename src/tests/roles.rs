@@ -0,0 +1,120 @@
+use crate::{automation::{Outcome, Rejection}, data::Business, settings::AppSettings};
+
+use super::{assert_consistent, assigned, sample};
+
+#[test]
+fn fill_explains_every_block() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0, 1]).unwrap();
+    let trace = business.schedule_roles(&AppSettings::default());
+    assert_eq!(trace.len(), business.blocks * 3);
+    for decision in trace.iter() {
+        let chosen: Vec<usize> = decision.candidates.iter().filter(|(_, rejection)| rejection.is_none()).map(|(id, _)| *id).collect();
+        match decision.outcome {
+            Outcome::AlreadyCovered { employee } | Outcome::Continued { employee, .. } => {
+                assert!(decision.candidates.is_empty());
+                assert_eq!(assigned(&business, employee)[decision.block], decision.role);
+            },
+            Outcome::Chosen { employee } => {
+                assert_eq!(chosen, vec![employee]);
+                assert_eq!(assigned(&business, employee)[decision.block], decision.role);
+            },
+            Outcome::Empty => assert!(chosen.is_empty()),
+        }
+    }
+    let first = trace.iter().find(|decision| decision.role == 3 && decision.block == 0).unwrap();
+    assert_eq!(first.outcome, Outcome::AlreadyCovered { employee: 1 });
+    // Employee 4 doesn't come in until 11
+    let early = trace.iter().find(|decision| decision.role == 4 && decision.block == 0).unwrap();
+    assert!(early.candidates.contains(&(4, Some(Rejection::NotClockedIn))));
+    assert!(early.candidates.contains(&(5, Some(Rejection::NotScheduled))));
+    assert!(early.candidates.contains(&(1, Some(Rejection::Busy { role: 3 }))));
+    assert_consistent(&business);
+}
+
+#[test]
+fn fill_explains_empty_blocks() {
+    let mut business = sample();
+    business.toggle_employee_scheduled(2).unwrap();
+    business.restrict_role(3, 5).unwrap();
+    business.restrict_role(4, 5).unwrap();
+    let trace = business.schedule_roles(&AppSettings::default());
+    for decision in trace.iter().filter(|decision| decision.role == 5) {
+        assert_eq!(decision.outcome, Outcome::Empty);
+        let mut candidates = decision.candidates.clone();
+        candidates.sort_by_key(|(id, _)| *id);
+        assert_eq!(candidates, vec![
+            (1, Some(Rejection::NotAllowed)),
+            (2, Some(Rejection::NotScheduled)),
+            (3, Some(Rejection::NotAllowed)),
+            (4, Some(Rejection::NotAllowed)),
+            (5, Some(Rejection::NotScheduled)),
+        ]);
+    }
+    // Anyone free but not chosen is told so
+    assert!(trace.iter().any(|decision| decision.candidates.iter().any(|(_, rejection)| *rejection == Some(Rejection::Outranked))));
+}
+
+#[test]
+fn fill_is_the_same_every_time() {
+    // Each sample has its own hash order, which used to change who got what
+    let mut first = sample();
+    first.schedule_roles(&AppSettings::default());
+    for _ in 0..5 {
+        let mut again = sample();
+        again.schedule_roles(&AppSettings::default());
+        for id in 1..=5 {
+            assert_eq!(assigned(&first, id), assigned(&again, id));
+        }
+    }
+}
+
+#[test]
+fn fill_shares_roles_out() {
+    let mut business = sample();
+    business.delete_role(5);
+    let settings = AppSettings { shift_length: 2, max_role_switches: 0, ..Default::default() };
+    business.schedule_roles(&settings);
+    // Nobody ends up with more than a shift's more time on a role than anyone else who can work it
+    let fairness = business.fairness(false);
+    for role in fairness.roles.iter() {
+        assert!(fairness.spread(*role) <= 60, "role {} spread {} in {:?}", role, fairness.spread(*role), fairness);
+    }
+    assert_consistent(&business);
+}
+
+#[test]
+fn fill_limits_role_switches() {
+    let switches = |business: &Business| -> usize { business.fairness(false).employees.iter().map(|emp| emp.switches).sum() };
+    let mut unlimited = sample();
+    unlimited.schedule_roles(&AppSettings { shift_length: 2, max_role_switches: 0, ..Default::default() });
+    let mut business = sample();
+    business.schedule_roles(&AppSettings { shift_length: 2, max_role_switches: 2, ..Default::default() });
+    assert!(switches(&business) < switches(&unlimited));
+    let over: Vec<usize> = business.fairness(false).employees.iter().filter(|emp| emp.switches > 2).map(|emp| emp.employee).collect();
+    // Only when nobody else could cover, which is noted
+    for employee in over.iter() {
+        let name = business.employees[employee].name.to_string();
+        assert!(business.notices.items.iter().any(|(_, message)| message.contains(&name)));
+    }
+    assert_consistent(&business);
+}
+
+#[test]
+fn week_balances_across_days() {
+    let mut business = sample();
+    business.assign_block(1, 3, (0..16).collect()).unwrap();
+    business.record_day();
+    assert_eq!(business.days_recorded, 1);
+    assert_eq!(business.employees[&1].week_minutes[&3], 16 * 30);
+    business.employees.values_mut().for_each(|emp| emp.clear_assigned());
+    let settings = AppSettings { fairness_week: true, ..Default::default() };
+    business.schedule_roles(&settings);
+    // Whoever had the role all yesterday is the last choice for it today
+    assert_eq!(business.fairness(false).employees[0].minutes[0], Some(0));
+    assert_eq!(business.fairness(true).employees[0].minutes[0], Some(16 * 30));
+    business.clear_week();
+    assert_eq!(business.days_recorded, 0);
+    assert!(business.employees.values().all(|emp| emp.week_minutes.is_empty()));
+    assert_consistent(&business);
+}
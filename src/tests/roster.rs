@@ -0,0 +1,101 @@
+use chrono::NaiveTime;
+
+use crate::{data::{RoleTrait, Shift}, roster::parse_csv};
+
+use super::{assert_consistent, sample};
+
+fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+#[test]
+fn csv_fields_can_be_quoted() {
+    let records = parse_csv("name,roles\r\n\"Doe, Jane\",\"Role 1; \"\"Role 2\"\"\"\n\n,\n\"Two\nlines\",x\nlast");
+    assert_eq!(records, vec![
+        (1, vec!["name".to_string(), "roles".to_string()]),
+        (2, vec!["Doe, Jane".to_string(), "Role 1; \"Role 2\"".to_string()]),
+        (5, vec!["Two\nlines".to_string(), "x".to_string()]),
+        (7, vec!["last".to_string()]),
+    ]);
+
+    // Pasted from a spreadsheet
+    assert_eq!(parse_csv("name\thours\nSam\t9:00-17:00, x"), vec![
+        (1, vec!["name".to_string(), "hours".to_string()]),
+        (2, vec!["Sam".to_string(), "9:00-17:00, x".to_string()]),
+    ]);
+}
+
+#[test]
+fn preview_checks_each_row() {
+    let business = sample();
+    let roster = "Name,Roles,Hours,Lunch,Wage,Notes\n\
+        employee 1,Role 1;Stock,9:00-13:00; 14:00-17:00,45,16.25,\n\
+        Pat,,8:00 to 12:00,,,\n\
+        ,Role 1,,,,\n\
+        Employee 1,,,,,\n\
+        Lee,,17:00-9:00,half,lots,";
+    let preview = business.preview_roster(roster, true);
+    assert_eq!(preview.warnings, vec!["The \"Notes\" column isn't used".to_string()]);
+    assert_eq!(preview.new_roles, vec!["Stock".to_string()]);
+    assert_eq!(preview.counts(), (1, 1));
+
+    let rows = &preview.rows;
+    assert_eq!(rows[0].employee, Some(1));
+    assert_eq!(rows[0].shifts, Some(vec![Shift { clock_in: time(9, 0), clock_out: time(13, 0) }, Shift { clock_in: time(14, 0), clock_out: time(17, 0) }]));
+    // 45 minutes is half way between blocks, so rounds down
+    assert_eq!((rows[0].lunch, rows[0].wage), (Some(1), Some(1625)));
+    assert_eq!(rows[1].employee, None);
+    assert_eq!(rows[1].warnings, vec!["Works outside business hours".to_string()]);
+    assert_eq!(rows[2].errors, vec!["No name".to_string()]);
+    assert_eq!(rows[3].errors, vec!["Employee 1 is already on line 2".to_string()]);
+    assert_eq!(rows[4].errors.len(), 3);
+    assert!(rows.iter().all(|row| row.line > 1));
+
+    assert_eq!(business.preview_roster("roles\nRole 1", true).errors.len(), 1);
+    assert!(business.preview_roster(roster, false).new_roles.is_empty());
+}
+
+#[test]
+fn import_merges_by_name() {
+    let mut business = sample();
+    business.assign_block(1, 4, vec![0, 1]).unwrap();
+    let before = business.employees.len();
+    let preview = business.import_roster("name,roles,hours,lunch,wage\n\
+        Employee 1,Role 1;Stock,10:00-15:00,60,\n\
+        Pat,stock,,30,14\n\
+        Bad,,x,,", true);
+    assert_eq!(preview.counts(), (1, 1));
+    // Named twice, but only added once
+    assert_eq!(preview.new_roles, vec!["Stock".to_string()]);
+
+    let stock = business.roles.values().find(|role| role.name() == "Stock").unwrap().id();
+    let emp = &business.employees[&1];
+    assert_eq!(emp.roles, vec![2, 3, stock]);
+    assert_eq!(emp.shifts, vec![Shift { clock_in: time(10, 0), clock_out: time(15, 0) }]);
+    assert_eq!((emp.lunch, emp.wage), (2, 1500));
+    // Role 2 was taken away, and the first block is before the new hours anyway
+    assert!(emp.assigned(&business.grid()).iter().all(|role| *role != 4));
+
+    assert_eq!(business.employees.len(), before + 1);
+    let pat = business.employees.values().find(|emp| emp.name == "Pat").unwrap();
+    assert_eq!(pat.roles, vec![2, stock]);
+    assert_eq!((pat.lunch, pat.wage), (1, 1400));
+    assert_consistent(&business);
+}
+
+#[test]
+fn exported_rosters_import_unchanged() {
+    let mut business = sample();
+    business.update_employee_shifts(2, vec![Shift { clock_in: time(9, 0), clock_out: time(12, 0) }, Shift { clock_in: time(13, 0), clock_out: time(17, 30) }]).unwrap();
+    business.new_role("Stock".into());
+    business.assign_role(3, 6).unwrap();
+    business.employees.get_mut(&3).unwrap().name = "Doe, Jane".into();
+    let roster = business.roster_csv();
+    assert!(roster.contains("\"Doe, Jane\",Role 1; Role 2; Role 3; Stock,"));
+
+    let before = business.employees.clone();
+    let preview = business.import_roster(&roster, false);
+    assert!(preview.rows.iter().all(|row| row.errors.is_empty() && row.warnings.is_empty()), "{:?}", preview.rows);
+    assert_eq!(preview.counts(), (0, before.len()));
+    assert_eq!(business.employees, before);
+}
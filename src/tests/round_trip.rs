@@ -0,0 +1,158 @@
+use chrono::TimeDelta;
+use proptest::prelude::*;
+
+use crate::{data::{Business, RoleTrait, Shift}, persistence::{csv_to_schedule, schedule_to_csv}, settings::{AppSettings, Settings}};
+
+use super::{assert_consistent, random::{apply, op}, sample};
+
+/// Save a business the way the page hash does (before compression) and load it again
+fn ron_round_trip(business: &Business) -> Business {
+    let serialized = ron::to_string(business).unwrap();
+    let mut loaded: Business = ron::from_str(&serialized).unwrap();
+    loaded.init(business.open, business.close, business.block_size);
+    loaded
+}
+
+#[test]
+fn ron_keeps_roles_and_employees() {
+    let mut business = sample();
+    business.new_role("Register".into());
+    business.toggle_role_multi(4);
+    business.update_role_color(3, "#123456".into());
+    business.assign_role(1, 6).unwrap();
+    business.employees.get_mut(&2).unwrap().lunch = 3;
+
+    let loaded = ron_round_trip(&business);
+    assert_eq!(loaded.roles.len(), business.roles.len());
+    for (id, role) in business.roles.iter() {
+        let other = &loaded.roles[id];
+        assert_eq!((other.name(), other.sort(), other.color(), other.is_multi()), (role.name(), role.sort(), role.color(), role.is_multi()));
+    }
+    assert_eq!(loaded.role_colors[&3], "#123456");
+    for (id, emp) in business.employees.iter() {
+        let other = &loaded.employees[id];
+        assert_eq!((&other.name, &other.roles, other.lunch), (&emp.name, &emp.roles, emp.lunch));
+    }
+    assert_consistent(&loaded);
+}
+
+#[test]
+fn csv_keeps_schedule() {
+    let mut business = sample();
+    business.assign_block(1, 3, vec![0, 1, 2]).unwrap();
+    business.assign_block(2, 2, vec![4, 5]).unwrap();
+    business.assign_block(3, 2, vec![4, 5]).unwrap();
+    business.toggle_employee_scheduled(5).unwrap();
+
+    let mut loaded = sample();
+    let problems = loaded.load_schedule(schedule_to_csv(&business));
+    assert!(problems.is_empty(), "{:?}", problems);
+    for (id, emp) in business.employees.iter() {
+        let other = &loaded.employees[id];
+        assert_eq!(other.scheduled, emp.scheduled);
+        if emp.scheduled {
+            assert_eq!((&other.shifts, &other.assignments), (&emp.shifts, &emp.assignments));
+        }
+    }
+    assert_consistent(&loaded);
+}
+
+#[test]
+fn csv_rejects_malformed_rows() {
+    assert!(csv_to_schedule("1,09:00:00,--".to_string()).is_err());
+    assert!(csv_to_schedule("x,false,--".to_string()).is_err());
+    assert!(csv_to_schedule("1,9am,17:00:00,1,--".to_string()).is_err());
+    assert!(csv_to_schedule("1,09:00:00,17:00:00,3@09:00:00,--".to_string()).is_err());
+    assert!(csv_to_schedule("1,09:00:00,17:00:00,3@09:00:00-noon,--".to_string()).is_err());
+    assert!(csv_to_schedule("1,false,--2,09:00:00,10:00:00,1,3,--".to_string()).is_ok());
+}
+
+#[test]
+fn csv_keeps_split_shifts_and_locks() {
+    let mut business = sample();
+    let shifts = vec![
+        Shift { clock_in: business.open, clock_out: business.block_time(4) },
+        Shift { clock_in: business.block_time(12), clock_out: business.block_time(16) },
+    ];
+    business.update_employee_shifts(2, shifts).unwrap();
+    business.assign_block(2, 3, vec![2, 3, 12, 13]).unwrap();
+    business.assign_block(2, 3, vec![1]).unwrap();
+    business.toggle_lock(2, 12).unwrap();
+
+    let mut loaded = sample();
+    let problems = loaded.load_schedule(schedule_to_csv(&business));
+    assert!(problems.is_empty(), "{:?}", problems);
+    assert_eq!(loaded.employees[&2].shifts, business.employees[&2].shifts);
+    assert_eq!(loaded.employees[&2].assignments, business.employees[&2].assignments);
+    assert!(csv_to_schedule("1,09:00:00,11:00:00,15:00:00,--".to_string()).is_err());
+    assert_consistent(&loaded);
+}
+
+#[test]
+fn csv_keeps_times_off_the_grid() {
+    let mut business = sample();
+    let (start, end) = (business.block_time(2) + TimeDelta::minutes(10), business.block_time(4));
+    business.assign_span(1, 3, start, end).unwrap();
+
+    let mut loaded = sample();
+    loaded.update_business_hours(loaded.open, loaded.close, TimeDelta::minutes(15));
+    let problems = loaded.load_schedule(schedule_to_csv(&business));
+    assert!(problems.is_empty(), "{:?}", problems);
+    assert_eq!(loaded.employees[&1].assignments, business.employees[&1].assignments);
+    assert_consistent(&loaded);
+}
+
+#[test]
+fn csv_with_block_codes_still_loads() {
+    let mut loaded = sample();
+    let codes = "1,1,3,3,2,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1";
+    let problems = loaded.load_schedule(format!("1,09:00:00,19:00:00,{},--", codes));
+    assert!(problems.is_empty(), "{:?}", problems);
+    assert_eq!(loaded.employees[&1].assigned(&loaded.grid())[0..6], [1, 1, 3, 3, 2, 1]);
+    assert_consistent(&loaded);
+}
+
+#[test]
+fn csv_for_another_day_length_is_skipped() {
+    let mut loaded = sample();
+    let problems = loaded.load_schedule("1,09:00:00,10:00:00,3,3,--".to_string());
+    assert_eq!(problems.len(), 1);
+    assert_consistent(&loaded);
+}
+
+#[test]
+fn fragment_settings_round_trip() {
+    let mut settings = Settings::default();
+    settings.app.block_size = TimeDelta::minutes(15);
+    settings.app.lunch_overlap = 1;
+    assert_eq!(Settings::from_fragment(&settings.fragment_string()).unwrap(), settings);
+    // Would never finish laying out the day
+    assert_eq!(Settings::from_fragment("app(|block_size:0|),").unwrap().app.block_size, AppSettings::default().block_size);
+}
+
+proptest! {
+    /// Block sizes which can't lay out a day, as a hand-edited link might have, fall back to the default
+    #[test]
+    fn fragment_rejects_unusable_block_sizes(minutes in prop_oneof![-100i64..=0, Just(7), Just(25), 1441i64..10000]) {
+        let settings = Settings::from_fragment(&format!("app(|block_size:{}|),", minutes)).unwrap();
+        prop_assert_eq!(settings.app.block_size, AppSettings::default().block_size);
+    }
+
+    #[test]
+    fn csv_and_ron_round_trip_any_schedule(ops in prop::collection::vec(op(), 1..40)) {
+        let mut business = sample();
+        for op in ops {
+            apply(&mut business, op);
+        }
+        let csv = schedule_to_csv(&business);
+
+        // Loaded onto the saved roles and employees, as a copied schedule would be
+        let mut loaded = ron_round_trip(&business);
+        loaded.load_schedule(csv.clone());
+        assert_consistent(&loaded);
+        for (id, emp) in business.employees.iter().filter(|(_, emp)| emp.scheduled) {
+            prop_assert_eq!(&loaded.employees[id].assignments, &emp.assignments);
+        }
+        prop_assert_eq!(schedule_to_csv(&loaded).len(), csv.len());
+    }
+}
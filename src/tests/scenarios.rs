@@ -0,0 +1,120 @@
+use std::rc::Rc;
+
+use chrono::{NaiveTime, TimeDelta};
+
+use crate::scenarios::BlockChange;
+
+use super::{assert_consistent, assigned, workspace};
+
+#[test]
+fn scenarios_leave_the_plan_alone() {
+    let mut business = workspace();
+    business.assign_block(1, 3, vec![0, 1, 2, 3]).unwrap();
+    business.new_scenario("Employee 4 calls out".into());
+    assert_eq!(business.editing, Some(0));
+    business.toggle_employee_scheduled(4).unwrap();
+    business.assign_block(1, 4, vec![2, 3]).unwrap();
+    assert_eq!(&assigned(business.plan(), 1)[0..4], &[3, 3, 3, 3]);
+    assert!(business.plan().employees[&4].scheduled);
+
+    business.open_scenario(None).unwrap();
+    assert_eq!(business.editing, None);
+    assert_eq!(&assigned(&business, 1)[0..4], &[3, 3, 3, 3]);
+    assert!(business.employees[&4].scheduled);
+    assert_consistent(&business);
+
+    business.open_scenario(Some(0)).unwrap();
+    assert_eq!(&assigned(&business, 1)[0..4], &[3, 3, 4, 4]);
+    assert!(!business.employees[&4].scheduled);
+    assert_consistent(&business);
+    assert!(business.open_scenario(Some(1)).is_err());
+}
+
+#[test]
+fn comparing_a_scenario() {
+    let mut business = workspace();
+    business.assign_block(1, 3, vec![0, 1]).unwrap();
+    business.new_scenario("".into());
+    assert_eq!(business.scenarios[0].name.as_str(), "Scenario 1");
+    business.assign_block(1, 4, vec![1]).unwrap();
+    business.toggle_employee_scheduled(4).unwrap();
+    business.open_scenario(None).unwrap();
+
+    let diff = business.compare_scenario(0).unwrap();
+    assert_eq!(diff.grid.blocks, 20);
+    assert_eq!(diff.changes[0], BlockChange { employee: 1, block: 1, before: 3, after: 4 });
+    // Employee 4 is out from 11 to close
+    assert_eq!(diff.changes.iter().filter(|change| change.employee == 4 && change.before == 1 && change.after == 0).count(), 16);
+    assert_eq!(diff.paid, -TimeDelta::hours(8));
+    assert_eq!(diff.cost, -8 * 1500);
+    assert_eq!(diff.coverage, TimeDelta::zero());
+    assert!(business.compare_scenario(1).is_err());
+}
+
+#[test]
+fn comparing_different_hours() {
+    let mut business = workspace();
+    business.new_scenario("Close at 6".into());
+    let (open, block_size) = (business.open, business.block_size);
+    business.update_business_hours(open, NaiveTime::from_hms_opt(18, 0, 0).unwrap(), block_size);
+    let diff = business.compare_scenario(0).unwrap();
+    assert_eq!(diff.grid.blocks, 20);
+    assert!(diff.changes.iter().all(|change| change.block >= 18 && change.after == 0));
+    assert_eq!(diff.changes.len(), 2 * 3);
+}
+
+#[test]
+fn promoting_keeps_the_old_plan() {
+    let mut business = workspace();
+    business.new_scenario("First".into());
+    business.assign_block(2, 3, vec![5]).unwrap();
+    business.open_scenario(None).unwrap();
+    business.new_scenario("Second".into());
+    business.assign_block(3, 4, vec![5]).unwrap();
+
+    business.promote_scenario(0).unwrap();
+    assert_eq!(business.editing, None);
+    assert_eq!(assigned(&business, 2)[5], 3);
+    assert_eq!(assigned(&business, 3)[5], 1);
+    assert_eq!(business.scenarios[0].name.as_str(), "Plan before First");
+    assert_eq!(assigned(business.scenario(0).unwrap(), 2)[5], 1);
+    // The scenario which was open is kept as it was left
+    assert_eq!(assigned(business.scenario(1).unwrap(), 3)[5], 4);
+    assert_consistent(&business);
+}
+
+#[test]
+fn deleting_scenarios() {
+    let mut business = workspace();
+    business.new_scenario("First".into());
+    business.open_scenario(None).unwrap();
+    business.new_scenario("Second".into());
+    business.assign_block(1, 3, vec![0]).unwrap();
+
+    business.delete_scenario(0).unwrap();
+    assert_eq!(business.editing, Some(0));
+    assert_eq!(business.scenarios[0].name.as_str(), "Second");
+    assert_eq!(assigned(&business, 1)[0], 3);
+
+    business.delete_scenario(0).unwrap();
+    assert_eq!(business.editing, None);
+    assert!(business.scenarios.is_empty());
+    assert_eq!(assigned(&business, 1)[0], 1);
+    assert!(business.delete_scenario(0).is_err());
+}
+
+#[test]
+fn copies_share_scenarios_until_changed() {
+    let mut business = workspace();
+    business.new_scenario("First".into());
+    business.open_scenario(None).unwrap();
+    let copy = business.clone();
+    assert!(Rc::ptr_eq(&copy.scenarios[0].business, &business.scenarios[0].business));
+
+    business.open_scenario(Some(0)).unwrap();
+    business.assign_block(1, 3, vec![0]).unwrap();
+    business.open_scenario(None).unwrap();
+    assert_eq!(assigned(business.scenario(0).unwrap(), 1)[0], 3);
+    assert_eq!(assigned(copy.scenario(0).unwrap(), 1)[0], 1);
+    assert_ne!(business, copy);
+}
@@ -0,0 +1,166 @@
+use chrono::{NaiveTime, TimeDelta};
+
+use crate::{data::RoleTrait, events::BusinessEvents, settings::AppSettings, sync::{parse_message, Presence, SyncEvent}, workspace::Workspace};
+
+use super::{assert_consistent, assigned, sample, workspace};
+
+/// An editor connected to the relay as the given client, with the room's history caught up on
+fn join(business: &mut Workspace, client: u64, history: u64) {
+    business.receive_sync(SyncEvent::Connecting { url: "ws://localhost:9031/test".into(), name: format!("Editor {}", client) });
+    business.receive_sync(SyncEvent::Welcome { client, history });
+}
+
+/// The action as the relay passes it on, written out and read back in
+fn received(seq: u64, client: u64, action: &BusinessEvents) -> SyncEvent {
+    let action = ron::from_str(&ron::to_string(action).unwrap()).unwrap();
+    SyncEvent::Received { seq, client, action: Box::new(action) }
+}
+
+/// Take an action here, as the reducer does
+fn act(business: &mut Workspace, action: BusinessEvents) {
+    let editing = business.editing;
+    business.apply(action.clone());
+    business.share(action, editing, None);
+}
+
+#[test]
+fn relay_messages_are_read() {
+    assert_eq!(parse_message("W 3 12"), Some(SyncEvent::Welcome { client: 3, history: 12 }));
+    assert_eq!(parse_message("L 2"), Some(SyncEvent::Presence { client: 2, presence: None }));
+
+    let action = BusinessEvents::AssignBlock { employee: 1, role: 3, blocks: vec![0, 1] };
+    let message = format!("M 7 2 {}", ron::to_string(&action).unwrap());
+    assert_eq!(parse_message(&message), Some(received(7, 2, &action)));
+
+    let presence = Presence { name: "Sam".into(), employee: Some(1), action: "Assigned Employee 1 to Register at 9:00".into() };
+    let message = format!("P 2 {}", ron::to_string(&presence).unwrap());
+    assert_eq!(parse_message(&message), Some(SyncEvent::Presence { client: 2, presence: Some(presence) }));
+
+    assert_eq!(parse_message("R"), Some(SyncEvent::SnapshotWanted));
+    assert_eq!(parse_message("M 7 2 not an action"), None);
+    assert_eq!(parse_message("X"), None);
+}
+
+#[test]
+fn editors_converge_on_the_relay_order() {
+    let (mut first, mut second) = (workspace(), workspace());
+    second.assign_block(3, 5, vec![0, 1]).unwrap();
+
+    // The first editor starts the room, so their business is shared
+    join(&mut first, 1, 0);
+    let snapshot = first.sync.as_ref().unwrap().pending[0].clone();
+    assert!(matches!(snapshot, BusinessEvents::Snapshot { .. }));
+    assert!(first.receive_sync(received(1, 1, &snapshot)).is_none());
+    assert!(first.sync.as_ref().unwrap().pending.is_empty());
+
+    // The second joins and takes it over
+    join(&mut second, 2, 1);
+    let edit = second.receive_sync(received(1, 1, &snapshot)).unwrap();
+    assert_eq!(edit.editor, Some("Editor 1".into()));
+    assert!(!edit.hours_changed);
+    assert_eq!(second.employees, first.employees);
+
+    // Both give away the same block at once, and the relay puts the second editor's first
+    let by_first = BusinessEvents::AssignBlock { employee: 1, role: 3, blocks: vec![4, 5] };
+    let by_second = BusinessEvents::AssignBlock { employee: 2, role: 3, blocks: vec![5, 6] };
+    act(&mut first, by_first.clone());
+    act(&mut second, by_second.clone());
+    assert_eq!(first.sync.as_ref().unwrap().pending.len(), 1);
+
+    // The first editor's own change is kept on top of the second's until the relay sends it back
+    first.receive_sync(received(2, 2, &by_second));
+    assert_eq!(assigned(&first, 1)[4], 3);
+    assert_eq!(assigned(&first, 2)[6], 3);
+    first.receive_sync(received(3, 1, &by_first));
+    // Repeats are ignored
+    first.receive_sync(received(3, 1, &by_first));
+
+    second.receive_sync(received(2, 2, &by_second));
+    second.receive_sync(received(3, 1, &by_first));
+
+    let mut expected = sample();
+    expected.apply(snapshot);
+    expected.apply(by_second);
+    expected.apply(by_first);
+    for editor in [&first, &second] {
+        let sync = editor.sync.as_ref().unwrap();
+        assert!(sync.pending.is_empty());
+        assert_eq!(sync.seq, 3);
+        assert_eq!(editor.employees, expected.employees);
+        assert_eq!(sync.confirmed.employees, expected.employees);
+        assert_consistent(editor);
+    }
+}
+
+#[test]
+fn scenarios_stay_local_until_promoted() {
+    let mut business = workspace();
+    join(&mut business, 1, 3);
+    let pending = |business: &Workspace| business.sync.as_ref().unwrap().pending.clone();
+
+    act(&mut business, BusinessEvents::NewScenario { name: "Busy day".into() });
+    act(&mut business, BusinessEvents::AssignBlock { employee: 1, role: 3, blocks: vec![0] });
+    assert!(pending(&business).is_empty());
+
+    act(&mut business, BusinessEvents::PromoteScenario { scenario: 0 });
+    let pending = pending(&business);
+    assert_eq!(pending.len(), 1);
+    let mut other = sample();
+    other.apply(pending[0].clone());
+    assert_eq!(assigned(&other, 1)[0], 3);
+
+    // Losing the connection stops sharing, and says so
+    business.notices.items.clear();
+    business.receive_sync(SyncEvent::Closed);
+    assert!(business.sync.is_none());
+    assert_eq!(business.notices.items.len(), 1);
+}
+
+#[test]
+fn filling_roles_agrees_across_editors() {
+    // Roles which sort the same are filled in name then id order, rather than each editor's own hash order
+    let tied = || {
+        let mut business = workspace();
+        let sort = business.roles[&3].sort();
+        for role in [4, 5] {
+            business.roles.get_mut(&role).unwrap().sort_set(sort);
+        }
+        join(&mut business, 2, 1);
+        business
+    };
+    let fill = BusinessEvents::ScheduleRoles { settings: AppSettings::default() };
+    for _ in 0..5 {
+        let (mut first, mut second) = (tied(), tied());
+        first.receive_sync(received(1, 1, &fill)).unwrap();
+        second.receive_sync(received(1, 1, &fill)).unwrap();
+        assert_eq!(first.employees, second.employees);
+        assert_consistent(&first);
+    }
+}
+
+#[test]
+fn long_histories_are_replaced_with_a_snapshot() {
+    let mut business = workspace();
+    join(&mut business, 1, 3);
+    act(&mut business, BusinessEvents::AssignBlock { employee: 1, role: 3, blocks: vec![0] });
+    business.receive_sync(SyncEvent::SnapshotWanted);
+    let pending = business.sync.as_ref().unwrap().pending.clone();
+    assert_eq!(pending.len(), 2);
+    let mut other = sample();
+    other.apply(pending[1].clone());
+    assert_eq!(other.employees, business.employees);
+}
+
+#[test]
+fn remote_hours_are_reported() {
+    let mut business = workspace();
+    join(&mut business, 2, 1);
+    let close = NaiveTime::from_hms_opt(17, 0, 0).unwrap();
+    let hours = BusinessEvents::UpdateBusinessHours { open: business.open, close, block_size: TimeDelta::minutes(15) };
+    let edit = business.receive_sync(received(1, 1, &hours)).unwrap();
+    assert!(edit.hours_changed);
+    assert_eq!((business.close, business.block_size), (close, TimeDelta::minutes(15)));
+
+    let assign = BusinessEvents::AssignBlock { employee: 1, role: 3, blocks: vec![0] };
+    assert!(!business.receive_sync(received(2, 1, &assign)).unwrap().hours_changed);
+}
@@ -0,0 +1,64 @@
+use chrono::TimeDelta;
+
+use crate::totals::{format_duration, format_money, parse_money};
+
+use super::{assert_consistent, assigned, sample};
+
+#[test]
+fn clock_times_off_the_grid() {
+    let mut business = sample();
+    let clock_in = business.block_time(0) + TimeDelta::minutes(10);
+    let clock_out = business.block_time(10) + TimeDelta::minutes(20);
+    business.update_employee_hours(1, clock_in, clock_out).unwrap();
+    // In for 20 of the first block's 30 minutes and 20 of the last's, so both can still be worked
+    assert_eq!(assigned(&business, 1)[0..2], [1, 1]);
+    assert_eq!(assigned(&business, 1)[10..12], [1, 0]);
+    business.assign_block(1, 3, vec![0, 10]).unwrap();
+    let assignments = &business.employees[&1].assignments;
+    assert_eq!((assignments[0].start, assignments[0].end), (clock_in, business.block_time(1)));
+    assert_eq!((assignments[1].start, assignments[1].end), (business.block_time(10), clock_out));
+    assert_eq!(assigned(&business, 1)[0..2], [3, 1]);
+    assert_consistent(&business);
+}
+
+#[test]
+fn block_shows_role_worked_most() {
+    let mut business = sample();
+    let start = business.block_time(4);
+    business.assign_span(1, 3, start, start + TimeDelta::minutes(10)).unwrap();
+    business.assign_span(1, 4, start + TimeDelta::minutes(10), start + TimeDelta::minutes(30)).unwrap();
+    assert_eq!(assigned(&business, 1)[4], 4);
+    // Already working role 4 for most of the block still assigns the rest of it
+    assert_eq!(business.assign_block(1, 4, vec![4]).unwrap(), 1);
+    assert_eq!(business.employees[&1].assignments.len(), 1);
+}
+
+#[test]
+fn totals_count_minutes() {
+    let mut business = sample();
+    let clock_out = business.block_time(16) + TimeDelta::minutes(15);
+    business.update_employee_hours(1, business.open, clock_out).unwrap();
+    business.assign_block(1, 2, vec![6, 7]).unwrap();
+    business.assign_block(1, 3, vec![0, 1, 2]).unwrap();
+    let totals = business.totals();
+    let emp = &totals.employees[0];
+    assert_eq!((emp.employee, emp.clocked, emp.lunch), (1, TimeDelta::minutes(495), TimeDelta::hours(1)));
+    // 7:15 at 15.00 an hour
+    assert_eq!(emp.cost(), 10875);
+    assert_eq!(totals.roles.iter().find(|(role, _)| *role == 3), Some(&(3, TimeDelta::minutes(90))));
+    // Employee 5 isn't scheduled
+    assert_eq!(totals.employees.len(), 4);
+    assert_eq!(totals.cost(), totals.employees.iter().map(|emp| emp.cost()).sum::<usize>());
+}
+
+#[test]
+fn money_and_durations() {
+    assert_eq!(parse_money("15.5"), Some(1550));
+    assert_eq!(parse_money("$12"), Some(1200));
+    assert_eq!(parse_money(".75"), Some(75));
+    assert_eq!(parse_money("1.234"), None);
+    assert_eq!(parse_money("-3"), None);
+    assert_eq!(parse_money(""), None);
+    assert_eq!(format_money(1205), "12.05");
+    assert_eq!(format_duration(TimeDelta::minutes(465)), "7:45");
+}
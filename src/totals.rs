@@ -0,0 +1,101 @@
+use chrono::TimeDelta;
+
+use crate::data::{Business, Role, RoleTrait};
+
+/// Time and pay for one employee's day, counted to the minute rather than by block
+#[derive(Clone, PartialEq, Debug)]
+pub struct EmployeeTotals {
+    pub employee: usize,
+    /// From clock-in to clock-out
+    pub clocked: TimeDelta,
+    /// Time on lunch, which is unpaid
+    pub lunch: TimeDelta,
+    /// Hourly pay in cents
+    pub wage: usize,
+} impl EmployeeTotals {
+    pub fn paid(&self) -> TimeDelta {
+        self.clocked - self.lunch
+    }
+    /// Cost of the paid time in cents, rounded to the nearest cent
+    pub fn cost(&self) -> usize {
+        let seconds = self.paid().num_seconds().max(0) as usize;
+        (seconds * self.wage + 1800) / 3600
+    }
+}
+
+/// Hours and cost of the schedule
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Totals {
+    /// Every scheduled employee, by id
+    pub employees: Vec<EmployeeTotals>,
+    /// Time each role is worked, in role order
+    pub roles: Vec<(usize, TimeDelta)>,
+} impl Totals {
+    pub fn paid(&self) -> TimeDelta {
+        self.employees.iter().map(|emp| emp.paid()).sum()
+    }
+    pub fn cost(&self) -> usize {
+        self.employees.iter().map(|emp| emp.cost()).sum()
+    }
+}
+
+impl Business {
+
+    pub fn totals(&self) -> Totals {
+        let mut employees: Vec<_> = self.employees.values().filter(|emp| emp.scheduled).collect();
+        employees.sort_by_key(|emp| emp.id);
+        let mut roles: Vec<&Role> = self.roles.values().collect();
+        roles.sort();
+        let mut role_time: Vec<(usize, TimeDelta)> = roles.iter().map(|role| (role.id(), TimeDelta::zero())).collect();
+
+        let mut totals = vec![];
+        for emp in employees {
+            let mut lunch = TimeDelta::zero();
+            for assignment in emp.assignments.iter() {
+                let length = assignment.end - assignment.start;
+                if assignment.role == 2 {
+                    lunch += length;
+                }
+                if let Some((_, time)) = role_time.iter_mut().find(|(role, _)| *role == assignment.role) {
+                    *time += length;
+                }
+            }
+            totals.push(EmployeeTotals {
+                employee: emp.id,
+                clocked: emp.shifts.iter().map(|shift| shift.clock_out - shift.clock_in).sum(),
+                lunch,
+                wage: emp.wage,
+            });
+        }
+        Totals { employees: totals, roles: role_time }
+    }
+
+}
+
+/// Cents as dollars, e.g. 1550 as "15.50"
+pub fn format_money(cents: usize) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+/// Dollars with up to two decimal places, e.g. "15.5", as cents
+pub fn parse_money(input: &str) -> Option<usize> {
+    let input = input.trim().trim_start_matches('$');
+    if !input.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (whole, fraction) = input.split_once('.').unwrap_or((input, ""));
+    if fraction.len() > 2 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let whole: usize = match whole {
+        "" => 0,
+        whole => whole.parse().ok()?,
+    };
+    let cents: usize = format!("{:0<2}", fraction).parse().ok()?;
+    whole.checked_mul(100)?.checked_add(cents)
+}
+
+/// Hours and minutes, e.g. "7:45"
+pub fn format_duration(duration: TimeDelta) -> String {
+    format!("{}:{:02}", duration.num_hours(), duration.num_minutes() % 60)
+}
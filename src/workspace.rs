@@ -0,0 +1,38 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{absence::Absence, automation::Decision, data::Business, scenarios::Scenario, sync::SyncState};
+
+/// The business being edited, with what this editor keeps beside it for the session. None of that is saved or shared,
+/// so it is kept out of [`Business`] and isn't copied along with it. Reads through to the business being edited.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Workspace {
+    /// The plan, or the scenario being edited in its place
+    pub business: Business,
+    /// Alternative versions of the business to try changes on, see [`Workspace::new_scenario`]
+    pub scenarios: Vec<Scenario>,
+    /// Which scenario is being edited in place of the plan, if any
+    pub editing: Option<usize>,
+    /// The employee last marked absent and what they left uncovered, until dismissed
+    pub absence: Option<Absence>,
+    /// What the last run of [`Business::schedule_roles`] decided for each block of each role, until the schedule next
+    /// changes
+    pub role_trace: Vec<Decision>,
+    /// Other editors' actions and this editor's unconfirmed ones while editing live with others, see [`crate::sync`]
+    pub sync: Option<Box<SyncState>>,
+    /// How many times other editors have changed the plan's business hours, for the settings to follow
+    pub remote_hours: u64,
+} impl Workspace {
+    pub fn new(business: Business) -> Workspace {
+        Workspace { business, scenarios: vec![], editing: None, absence: None, role_trace: vec![], sync: None, remote_hours: 0 }
+    }
+} impl Deref for Workspace {
+    type Target = Business;
+
+    fn deref(&self) -> &Business {
+        &self.business
+    }
+} impl DerefMut for Workspace {
+    fn deref_mut(&mut self) -> &mut Business {
+        &mut self.business
+    }
+}